@@ -1,51 +1,91 @@
 mod model;
 mod types;
 mod logger;
+mod nep171;
+mod nep141;
+mod nep178;
+mod nep181;
+#[cfg(test)]
+mod tests;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_sdk::collections::{UnorderedMap, Vector, LookupMap, UnorderedSet};
 use near_sdk::{env, near_bindgen, AccountId, Balance, Promise};
-use crate::types::{TokenId, AccountIdHash, EditionNumber, TokenPrice, CollectionId};
-use crate::model::{Metadata, Token, Edition, Collection, Bid};
-use std::borrow::Borrow;
-use std::ops::{Add, Div, Mul, Sub};
+use crate::types::{TokenId, EditionNumber, TokenPrice, CollectionId};
+use crate::model::{Metadata, LegacyMetadata, Token, Edition, Collection, LegacyCollection, Bid, LegacyBid, StoredVector, StoredSet, EditionExport, Auction, ActivityRecord, AllowanceInfo, InvariantReport, SealedAuction, SealedCommit, EditionCard, ClaimableBreakdown, ConfigSnapshot, FtOffer, AccountSummary};
+use std::convert::TryFrom;
+use std::ops::Div;
 use std::str::FromStr;
-use near_sdk::env::sha256;
 use near_sdk::serde::{Serialize, Deserialize};
+use near_sdk::json_types::{Base58PublicKey, Base64VecU8};
 
 static METADATA_ERROR: &str = "Metadata exceeds character limits.";
 static TOKEN_LOCKED: &str = "This edition is burned or locked.";
 static PAUSED_ERR: &str = "Maintenance going on. Minting and transfers are temporarily disabled.";
+static TOKEN_PAUSED: &str = "Trading of this token is temporarily frozen.";
 static ONLY_OWNER: &str = "Only contract owner can call this method.";
+static ONLY_MINTER_ADMIN: &str = "Only contract owner or minter admin can call this method.";
 static ONLY_MINTER: &str = "Only whitelisted artists can call this method.";
 static ONLY_TOKEN_OWNER: &str = "Only token owner can call this method.";
 static ONLY_COLLECTION_MINTER: &str = "Only collection minter can call this method.";
 static ONLY_ESCROW: &str = "You don't have rights to access this account's funds.";
 static ACC_NOT_VALID: &str = "Account ID is invalid.";
+static ACC_DENIED: &str = "This account is blocked from using the contract.";
 static DEPOSIT_NOT_ENOUGH: &str = "Deposit not enough to cover metadata storage fee.";
+static FRACTIONALIZED: &str = "This edition is co-owned and can only change hands through a sale.";
+static PAYOUT_UNDERFLOW: &str = "Fee and royalty exceed the sale amount. Check trade fee and royalty configuration.";
+static TRADE_WINDOW_CLOSED: &str = "Trading for this collection is closed outside its trade window.";
+static MIGRATING: &str = "A state migration is in progress. State-changing calls are disabled until it is finished.";
+static HOLD_PERIOD: &str = "This edition is still within the token's minimum hold period and can't be resold yet.";
+static INVALID_AMOUNT: &str = "Invalid amount, expected a whole number of yoctoNEAR.";
+static LEGACY_API_DISABLED: &str = "Legacy NEP-4 transfer and access methods are disabled, use the NEP-171 nft_* methods instead.";
+static ALREADY_LISTED: &str = "This edition is already listed, cancel the sale before listing it at a new price.";
+static NOT_AVAILABLE_TO_RELIST: &str = "Only an available edition can be relisted, it is listed, locked or burned.";
+static DEPOSIT_NOT_EXACT: &str = "Deposit must exactly match the required fee.";
+// yoctoNEAR staked per byte of contract state, 1 NEAR per 100kb
+static STORAGE_PRICE_PER_BYTE: Balance = 10_000_000_000_000_000_000;
+static MAX_LISTING_PRICE: Balance = u128::MAX / 10_000;
+static MAX_SHAREHOLDERS: usize = 10;
+static MAX_ROYALTY_RECIPIENTS: usize = 10;
+static MAX_EXPORT_OFFERS: usize = 50;
+static MAX_BATCH: usize = 50;
+static MAX_SWEEP: u64 = 10;
+static MAX_COLLECTION_OFFERS: u64 = 50;
+static MAX_SET_SIZE: usize = 10;
+static MAX_REEMIT: u64 = 20;
+static MAX_COLLECTION_ACTIVITY: u64 = 100;
+static MAX_INVARIANT_SCAN: u64 = 100;
+static MAX_AUCTION_DURATION: u64 = 30 * 24 * 60 * 60 * 1_000_000_000;
+static MAX_PRUNE_REWARD: Balance = 1_000_000_000_000_000_000_000;
 static EVENT_MINT: &str = "Mint";
-static EVENT_BURN_TOKEN: &str = "BurnToken";
-static EVENT_BURN_EDITION: &str = "BurnEdition";
-static EVENT_CREATE_COLLECTION: &str = "CreateCollection";
-static EVENT_MINTER_ADD: &str = "MinterAdd";
 static EVENT_OFFER: &str = "Offer";
 static EVENT_CANCEL_OFFER: &str = "OfferCancel";
 static EVENT_ACCEPT_OFFER: &str = "OfferAccept";
 static EVENT_TRANSFER: &str = "Transfer";
 static EVENT_TRANSFER_BATCH: &str = "TransferBatch";
-static EVENT_APPROVAL: &str = "Approval";
-static EVENT_OWNERSHIP_TRANSFERRED: &str = "OwnershipTransferred";
 static EVENT_MARKET_UPDATE: &str = "MarketUpdate";
 static EVENT_MARKET_BATCH_UPDATE: &str = "MarketBatchUpdate";
 static EVENT_MARKET_DELETE: &str = "MarketDelete";
 static EVENT_MARKET_BUY: &str = "MarketBuy";
+static EVENT_AUCTION_SETTLED: &str = "AuctionSettled";
+static EVENT_OTC_SALE: &str = "OTC";
+static EVENT_RECALL: &str = "Recall";
+static EVENT_CLAIM: &str = "Claim";
+// why an edition changed hands, logged with every transfer activity
+static REASON_TRANSFER: &str = "TRANSFER";
+static REASON_SALE: &str = "SALE";
+static REASON_AUCTION: &str = "AUCTION";
+static REASON_OFFER_ACCEPT: &str = "OFFER_ACCEPT";
+static REASON_OTC: &str = "OTC";
+static REASON_RECALL: &str = "RECALL";
+static REASON_CLAIM: &str = "CLAIM";
 
 
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
 
-#[derive(Serialize, Deserialize, BorshDeserialize, BorshSerialize)]
+#[derive(Serialize, Deserialize, BorshDeserialize, BorshSerialize, PartialEq)]
 pub enum EditionState {
     AVAILABLE,
     LISTED,
@@ -54,12 +94,24 @@ pub enum EditionState {
 }
 
 
+#[derive(Serialize, Deserialize, BorshDeserialize, BorshSerialize, PartialEq)]
+pub enum OfferStatus {
+    ACTIVE,
+    ACCEPTED,
+    CANCELLED,
+    EXPIRED,
+    NOTFOUND,
+}
+
+
 #[derive(Serialize, Deserialize, BorshDeserialize, BorshSerialize)]
 pub enum TransferReason {
     ROYALTY,
     SALE,
     FEE,
+    RECALL,
     NEARFOLIO,
+    REFUND,
 }
 
 
@@ -69,7 +121,10 @@ pub trait NEP4 {
     // Grant the access to the given `accountId` for the given `tokenId`.
     // Requirements:
     // * The caller of the function (`predecessor_id`) should have access to the token.
-    fn grant_access(&mut self, escrow_account_id: AccountId);
+    // * If `deadline` is given, the grant can't be used for transfer_from past it.
+    // * If `single_use` is true, the grant is bound to the current `transfer_nonce(predecessor_id, escrow)` and
+    // the first transfer_from through it consumes the nonce and the grant.
+    fn grant_access(&mut self, escrow_account_id: AccountId, deadline: Option<u64>, single_use: Option<bool>);
 
     // Revoke the access to the given `accountId` for the given `tokenId`.
     // Requirements:
@@ -79,6 +134,7 @@ pub trait NEP4 {
     // Transfer the given `tokenId` to the given `accountId`. Account `accountId` becomes the new owner.
     // Requirements:
     // * The caller of the function (`predecessor_id`) should have access to the token.
+    // * The deadline and nonce of the escrow grant set by `from` in grant_access are enforced.
     fn transfer_from(&mut self, from: AccountId, to: AccountId, token_id: u64, edition_number: u64);
 
     // Transfer the given `tokenId` to the given `accountId`. Account `accountId` becomes the new owner.
@@ -98,25 +154,182 @@ pub trait NEP4 {
 // Begin implementation
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize)]
+#[allow(non_snake_case)]
 pub struct NonFungibleToken {
     pub owner_id: AccountId,
     pub current_supply: u64,
     pub total_editions: u64,
     pub total_collections: u64,
     pub minters: UnorderedSet<AccountId>,
+    pub denylist: UnorderedSet<AccountId>,
+    pub verified_creators: UnorderedSet<AccountId>,
+    pub paused_tokens: UnorderedSet<TokenId>,
+    // fungible tokens the owner accepts as payment currency and their decimals
+    pub accepted_fts: UnorderedMap<AccountId, u8>,
+    // currency of listings priced in an accepted FT, other listings are priced in NEAR
+    pub listing_fts: LookupMap<u64, AccountId>,
+    // offers escrowed in an accepted FT per edition, until accepted or cancelled by the bidder
+    pub ft_offers: LookupMap<u64, Vec<FtOffer>>,
     pub metadata: LookupMap<TokenId, Metadata>,
     pub tokens: LookupMap<TokenId, Token>,
     pub collections: LookupMap<CollectionId, Collection>,
     pub editions: LookupMap<u64, Edition>,
     pub edition_states: LookupMap<u64, EditionState>,
     pub marketplace: LookupMap<u64, TokenPrice>,
+    // absolute edition indices currently in the marketplace, for paging over all listings
+    pub listings: UnorderedSet<u64>,
+    // when each edition was last listed
+    pub listed_at: LookupMap<u64, u64>,
+    // listing fee paid for the current listing of an edition, refunded when it is cancelled within the grace period
+    pub listing_fees: LookupMap<u64, Balance>,
     pub account_gives_access: LookupMap<AccountId, UnorderedSet<AccountId>>,
     pub edition_allowances: LookupMap<u64, UnorderedSet<AccountId>>,
+    // optional expiry timestamp of an edition allowance, keyed by edition_index::account
+    pub allowance_expiry: LookupMap<String, u64>,
+    // NEP-178 approval id of each edition allowance, same idx::account key as allowance_expiry
+    pub approval_ids: LookupMap<String, u64>,
+    // next approval id per absolute edition index, only ever grows so a revoked approval id is never reused
+    pub next_approval_id: LookupMap<u64, u64>,
+    // accounts allowed to burn an edition on the owner's behalf, per absolute edition index
+    pub burn_allowances: LookupMap<u64, UnorderedSet<AccountId>>,
+    // reverse index of edition_allowances, absolute edition indices an account is allowed on
+    pub allowances_granted: LookupMap<AccountId, UnorderedSet<u64>>,
+    // absolute edition indices each account currently owns
+    pub account_to_editions: LookupMap<AccountId, UnorderedSet<u64>>,
+    // offers per "{token_id}::{edition_number}". each Vector<Bid> is stored under "ov{n}", n taken from offer_lists
+    // when the edition gets its first offer. lists created before that keep their sha256 prefix.
     pub offers: LookupMap<String, Vector<Bid>>,
+    // active offers per bidder as (token_id, edition_number, offer index)
+    pub bidder_offers: LookupMap<AccountId, UnorderedSet<(TokenId, EditionNumber, u64)>>,
+    // co-owner shares in basis points per absolute edition index, only present for fractionalized editions
+    pub edition_shares: LookupMap<u64, Vec<(AccountId, u16)>>,
+    // running english auctions per absolute edition index, the edition is LOCKED while one exists
+    pub auctions: LookupMap<u64, Auction>,
+    // sealed-bid auctions per absolute edition index and their commits keyed by edition_index::bidder
+    pub sealed_auctions: LookupMap<u64, SealedAuction>,
+    pub sealed_commits: LookupMap<String, SealedCommit>,
+    // like counters and the accounts that liked, per absolute edition index
+    pub like_counts: LookupMap<u64, u64>,
+    pub likes: LookupMap<u64, UnorderedSet<AccountId>>,
+    // ring buffer of the latest MAX_COLLECTION_ACTIVITY records per collection and the total ever written to it
+    pub collection_activity: LookupMap<CollectionId, Vector<ActivityRecord>>,
+    pub collection_activity_count: LookupMap<CollectionId, u64>,
+    // tokens whose secondary sales need the creator's approval of the buyer, and those approvals keyed by edition_index::buyer
+    pub resale_approval_tokens: UnorderedSet<TokenId>,
+    pub resale_approvals: LookupMap<String, bool>,
+    // creators that opted into royalty accrual and their withdrawable balances
+    pub accrue_earnings: UnorderedSet<AccountId>,
+    pub creator_balance: LookupMap<AccountId, Balance>,
+    // editions reserved for a free claim by one account, the edition is LOCKED until claimed or revoked
+    pub claimables: LookupMap<u64, AccountId>,
+    // ed25519 public key a collection's creator signs allowlisted account ids with, kept off-chain otherwise
+    pub claim_keys: LookupMap<CollectionId, Vec<u8>>,
+    // editions reserved for the first account on their collection's allowlist to claim them
+    pub allowlist_claimables: LookupMap<u64, bool>,
+    // absolute indexes of each collection's reserved editions, claimed in the order they were reserved
+    pub allowlist_queue: LookupMap<CollectionId, Vec<u64>>,
+    // collection::account pairs that already claimed an edition through the allowlist
+    pub allowlist_claimed: LookupMap<String, bool>,
+    // tokens minted together through mint_set, per set id and the set id of each member token
+    pub sets: LookupMap<u64, Vec<TokenId>>,
+    pub token_sets: LookupMap<TokenId, u64>,
+    // start of the current rate limit window and mints within it, per minting account
+    pub mint_rates: LookupMap<AccountId, (u64, u64)>,
+    // tokens minted per creator, in mint order
+    pub creator_tokens: LookupMap<AccountId, Vector<TokenId>>,
+    // royalty in basis points per token, filled at mint and by migrate_royalties for tokens minted before it
+    pub royalty_bps: LookupMap<TokenId, u32>,
+    // the collection's royalty split as it was when the token was minted, absent when the collection had none
+    pub token_royalties: LookupMap<TokenId, Vec<(AccountId, u32)>>,
+    // price the creator pays the current owner to recall an edition, only present for tokens minted as recallable
+    pub recall_prices: LookupMap<TokenId, Balance>,
+    // royalty escrowed by the lister per absolute edition index, consumed by the next sale or refunded
+    pub royalty_prepayments: LookupMap<u64, (AccountId, Balance)>,
+    // opt-in minimum time an owner has to hold an edition of the token before reselling it
+    pub min_hold_ns: LookupMap<TokenId, u64>,
+    // bids on any edition of a collection, addressed by their position like edition offers
+    pub collection_offers: LookupMap<CollectionId, Vector<Bid>>,
+    // number of bids in collection_offers that are neither accepted nor cancelled
+    pub active_collection_offers: LookupMap<CollectionId, u64>,
+    // when the current owner got each absolute edition index, editions never transferred count from the mint date
+    pub acquired_at: LookupMap<u64, u64>,
+    // set once an absolute edition index has been sold for the first time, primary_sale_fee_bps no longer applies
+    pub first_sale_done: LookupMap<u64, bool>,
+    // total spent per account on buys and accepted offers
+    pub collector_volume: LookupMap<AccountId, Balance>,
+    // price of the most recent sale per absolute edition index
+    pub last_sales: LookupMap<u64, Balance>,
+    // next usable transfer_from nonce per owner::escrow pair
+    pub transfer_nonces: LookupMap<String, u64>,
+    // deadline an escrow grant was given with, per owner::escrow pair
+    pub access_deadlines: LookupMap<String, u64>,
+    // nonce a single-use escrow grant was bound to, per owner::escrow pair
+    pub access_nonces: LookupMap<String, u64>,
     // Vec<u8> is sha256 of account, makes it safer and is how fungible token also works
     pub mint_storage_fee: Balance,
     pub edition_storage_fee: Balance,
     pub create_collection_fee: Balance,
+    // platform cut of secondary sales in basis points, a divisor of the sale amount until migrate_trade_fee ran
+    pub trade_fee: Balance,
+    pub paused: bool,
+    pub fee_receiver: AccountId,
+    // name and description limits count unicode characters, not bytes
+    pub MAX_NAME_LENGTH: u8,
+    pub MAX_DESCRIPTION_LENGTH: u8,
+    pub IPFS_HASH_LENGTH: u8,
+    pub MAX_EDITIONS: u8,
+    pub MAX_EXTERNAL_LINK: u8,
+    pub strict_deposits: bool,
+    // unlocks the owner's emergency tools such as force_unlock_edition
+    pub emergency_stop: bool,
+    pub like_fee: Balance,
+    pub min_royalty_bps: u32,
+    pub otc_royalty_required: bool,
+    pub outbid_fee_bps: u32,
+    pub migration_in_progress: bool,
+    pub total_sets: u64,
+    pub offer_lists: u64,
+    // editions burned so far, nft_total_supply leaves them out
+    pub burned_editions: u64,
+    // last edition index migrate_owned_editions counted burns up to, u64::MAX for state that never needed it
+    pub burned_scan_index: u64,
+    pub minter_admin: Option<AccountId>,
+    pub listing_grace_ns: u64,
+    pub listing_fee: Balance,
+    pub mint_rate_limit: u64,
+    pub mint_rate_window: u64,
+    pub primary_sale_fee_bps: u32,
+    pub legacy_api_enabled: bool,
+    // mint and collection fees not yet withdrawn to fee_receiver
+    pub collected_fees: Balance,
+    // false while trade_fee still holds the divisor of a migrated deployment, migrate_trade_fee sets it
+    pub trade_fee_in_bps: bool,
+    // allowed listing price range, the upper bound keeps bps fee math from overflowing
+    pub min_listing_price: Balance,
+    pub max_listing_price: Balance,
+}
+
+// stored layout of NonFungibleToken as first deployed, only read by migrate
+#[derive(BorshDeserialize, BorshSerialize)]
+#[allow(non_snake_case)]
+pub struct OldNonFungibleToken {
+    pub owner_id: AccountId,
+    pub current_supply: u64,
+    pub total_editions: u64,
+    pub total_collections: u64,
+    pub minters: UnorderedSet<AccountId>,
+    pub metadata: LookupMap<TokenId, Metadata>,
+    pub tokens: LookupMap<TokenId, Token>,
+    pub collections: LookupMap<CollectionId, Collection>,
+    pub editions: LookupMap<u64, Edition>,
+    pub edition_states: LookupMap<u64, EditionState>,
+    pub marketplace: LookupMap<u64, TokenPrice>,
+    pub account_gives_access: LookupMap<AccountId, UnorderedSet<AccountId>>,
+    pub edition_allowances: LookupMap<u64, UnorderedSet<AccountId>>,
+    pub offers: LookupMap<String, Vector<Bid>>,
+    pub mint_storage_fee: Balance,
+    pub edition_storage_fee: Balance,
+    pub create_collection_fee: Balance,
     pub trade_fee: Balance,
     pub paused: bool,
     pub fee_receiver: AccountId,
@@ -128,6 +341,17 @@ pub struct NonFungibleToken {
 }
 
 
+/// parses a yoctoNEAR amount passed as a string, panicking with a readable message instead of a bare unwrap
+pub fn parse_balance(amount: &str) -> Balance {
+    Balance::from_str(amount).unwrap_or_else(|_| env::panic(INVALID_AMOUNT.as_bytes()))
+}
+
+/// overwrites the raw value stored at `key` with a migrated one. the migrations write directly, inserting through
+/// the collection would deserialize the legacy value it replaces and fail on the old layout.
+fn rewrite_raw<T: BorshSerialize>(key: &[u8], value: &T) {
+    env::storage_write(key, &value.try_to_vec().unwrap());
+}
+
 impl Default for NonFungibleToken {
     fn default() -> Self {
         panic!("NFT should be initialized before usage")
@@ -140,25 +364,124 @@ impl NonFungibleToken {
     pub fn new(owner_id: AccountId, fee_receiver: AccountId) -> Self {
         assert!(env::is_valid_account_id(owner_id.as_bytes()), "Owner's account ID is invalid.");
         assert!(!env::state_exists(), "Already initialized");
+        let contract = Self::with_defaults(owner_id, fee_receiver);
+        logger::contract_initialized(&contract.config_snapshot());
+        contract
+    }
+
+    /// upgrades the state of a deployment that still has the OldNonFungibleToken layout, call it right after
+    /// deploying this code. the old collections are taken over as they are and everything added since starts from
+    /// its default. state-changing calls stay blocked until finish_migration, run the batched migrations first:
+    /// migrate_metadata, migrate_collections, migrate_offers, migrate_owned_editions, migrate_allowances,
+    /// migrate_royalties and migrate_trade_fee. the batched ones page over `limit` entries from a cursor, skip what is
+    /// already migrated so they are safe to run again, and return the cursor to continue from.
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let old: OldNonFungibleToken = env::state_read().unwrap_or_else(|| env::panic(b"NO STATE TO MIGRATE"));
+        assert_eq!(env::predecessor_account_id(), old.owner_id, "{}", ONLY_OWNER);
+        let mut contract = Self::with_defaults(old.owner_id, old.fee_receiver);
+        contract.current_supply = old.current_supply;
+        contract.total_editions = old.total_editions;
+        contract.total_collections = old.total_collections;
+        contract.minters = old.minters;
+        contract.metadata = old.metadata;
+        contract.tokens = old.tokens;
+        contract.collections = old.collections;
+        contract.editions = old.editions;
+        contract.edition_states = old.edition_states;
+        contract.marketplace = old.marketplace;
+        contract.account_gives_access = old.account_gives_access;
+        contract.edition_allowances = old.edition_allowances;
+        contract.offers = old.offers;
+        contract.mint_storage_fee = old.mint_storage_fee;
+        contract.edition_storage_fee = old.edition_storage_fee;
+        contract.create_collection_fee = old.create_collection_fee;
+        // still the old divisor, migrate_trade_fee converts it
+        contract.trade_fee = old.trade_fee;
+        contract.trade_fee_in_bps = false;
+        contract.paused = old.paused;
+        contract.MAX_NAME_LENGTH = old.MAX_NAME_LENGTH;
+        contract.MAX_DESCRIPTION_LENGTH = old.MAX_DESCRIPTION_LENGTH;
+        contract.IPFS_HASH_LENGTH = old.IPFS_HASH_LENGTH;
+        contract.MAX_EDITIONS = old.MAX_EDITIONS;
+        contract.MAX_EXTERNAL_LINK = old.MAX_EXTERNAL_LINK;
+        contract.migration_in_progress = true;
+        // burns of the old deployment are counted by migrate_owned_editions
+        contract.burned_scan_index = 0;
+        contract
+    }
+
+    fn with_defaults(owner_id: AccountId, fee_receiver: AccountId) -> Self {
         Self {
             owner_id,
             current_supply: 0,
             total_editions: 0,
             total_collections: 0,
             minters: UnorderedSet::new(b"mt".to_vec()),
+            denylist: UnorderedSet::new(b"dl".to_vec()),
+            verified_creators: UnorderedSet::new(b"vc".to_vec()),
+            paused_tokens: UnorderedSet::new(b"pt".to_vec()),
+            accepted_fts: UnorderedMap::new(b"ft".to_vec()),
+            listing_fts: LookupMap::new(b"lt".to_vec()),
+            ft_offers: LookupMap::new(b"fo".to_vec()),
             metadata: LookupMap::new(b"md".to_vec()),
             tokens: LookupMap::new(b"t".to_vec()),
             collections: LookupMap::new(b"c".to_vec()),
             editions: LookupMap::new(b"e".to_vec()),
             edition_states: LookupMap::new(b"st".to_vec()),
             marketplace: LookupMap::new(b"mp".to_vec()),
+            listings: UnorderedSet::new(b"al".to_vec()),
+            listed_at: LookupMap::new(b"la".to_vec()),
+            listing_fees: LookupMap::new(b"lf".to_vec()),
             account_gives_access: LookupMap::new(b"esc".to_vec()),
             edition_allowances: LookupMap::new(b"ea".to_vec()),
+            allowances_granted: LookupMap::new(b"ag".to_vec()),
+            account_to_editions: LookupMap::new(b"ate".to_vec()),
+            burn_allowances: LookupMap::new(b"ba".to_vec()),
+            allowance_expiry: LookupMap::new(b"ax".to_vec()),
+            approval_ids: LookupMap::new(b"aid".to_vec()),
+            next_approval_id: LookupMap::new(b"nai".to_vec()),
             offers: LookupMap::new(b"O".to_vec()),
+            bidder_offers: LookupMap::new(b"bo".to_vec()),
+            edition_shares: LookupMap::new(b"sh".to_vec()),
+            transfer_nonces: LookupMap::new(b"n".to_vec()),
+            access_deadlines: LookupMap::new(b"gd".to_vec()),
+            access_nonces: LookupMap::new(b"gn".to_vec()),
+            last_sales: LookupMap::new(b"ls".to_vec()),
+            collector_volume: LookupMap::new(b"cv".to_vec()),
+            royalty_bps: LookupMap::new(b"rb".to_vec()),
+            token_royalties: LookupMap::new(b"tr".to_vec()),
+            creator_tokens: LookupMap::new(b"cr".to_vec()),
+            mint_rates: LookupMap::new(b"mr".to_vec()),
+            sets: LookupMap::new(b"set".to_vec()),
+            claimables: LookupMap::new(b"cl".to_vec()),
+            claim_keys: LookupMap::new(b"ck".to_vec()),
+            allowlist_claimables: LookupMap::new(b"ak".to_vec()),
+            allowlist_queue: LookupMap::new(b"aw".to_vec()),
+            allowlist_claimed: LookupMap::new(b"ac".to_vec()),
+            accrue_earnings: UnorderedSet::new(b"ae".to_vec()),
+            creator_balance: LookupMap::new(b"cb".to_vec()),
+            resale_approval_tokens: UnorderedSet::new(b"rat".to_vec()),
+            resale_approvals: LookupMap::new(b"ra".to_vec()),
+            token_sets: LookupMap::new(b"tset".to_vec()),
+            recall_prices: LookupMap::new(b"rc".to_vec()),
+            royalty_prepayments: LookupMap::new(b"rp".to_vec()),
+            min_hold_ns: LookupMap::new(b"mh".to_vec()),
+            collection_offers: LookupMap::new(b"co".to_vec()),
+            active_collection_offers: LookupMap::new(b"coa".to_vec()),
+            acquired_at: LookupMap::new(b"aq".to_vec()),
+            first_sale_done: LookupMap::new(b"fs".to_vec()),
+            collection_activity: LookupMap::new(b"ca".to_vec()),
+            collection_activity_count: LookupMap::new(b"cac".to_vec()),
+            auctions: LookupMap::new(b"au".to_vec()),
+            sealed_auctions: LookupMap::new(b"sa".to_vec()),
+            sealed_commits: LookupMap::new(b"sc".to_vec()),
+            like_counts: LookupMap::new(b"lc".to_vec()),
+            likes: LookupMap::new(b"lk".to_vec()),
             mint_storage_fee: 300_000_000_000_000_000_000_000,
             edition_storage_fee: 35_000_000_000_000_000_000_000,
             create_collection_fee: 2_000_000_000_000_000_000,
-            trade_fee: 13,
+            trade_fee: 769,
             paused: true,
             fee_receiver,
             MAX_NAME_LENGTH: 30,
@@ -166,22 +489,56 @@ impl NonFungibleToken {
             IPFS_HASH_LENGTH: 46,
             MAX_EDITIONS: 25,
             MAX_EXTERNAL_LINK: 100,
+            strict_deposits: false,
+            emergency_stop: false,
+            like_fee: 1_000_000_000_000_000_000_000,
+            min_royalty_bps: 0,
+            otc_royalty_required: false,
+            outbid_fee_bps: 0,
+            migration_in_progress: false,
+            total_sets: 0,
+            offer_lists: 0,
+            burned_editions: 0,
+            burned_scan_index: u64::MAX,
+            minter_admin: None,
+            listing_grace_ns: 0,
+            listing_fee: 0,
+            mint_rate_limit: 0,
+            mint_rate_window: 0,
+            primary_sale_fee_bps: 0,
+            legacy_api_enabled: true,
+            collected_fees: 0,
+            trade_fee_in_bps: true,
+            min_listing_price: 10_000_000_000_000_000_000_000,
+            max_listing_price: MAX_LISTING_PRICE,
         }
     }
 }
 
 #[near_bindgen]
 impl NEP4 for NonFungibleToken {
-    fn grant_access(&mut self, escrow_account_id: AccountId) {
+    fn grant_access(&mut self, escrow_account_id: AccountId, deadline: Option<u64>, single_use: Option<bool>) {
+        self.not_migrating();
+        self.legacy_api();
         let mut acc = self.account_gives_access.get(&env::predecessor_account_id()).unwrap_or(UnorderedSet::new(env::sha256(env::predecessor_account_id().as_bytes()).to_vec()));
-        assert_eq!(acc.contains(&escrow_account_id), false, "{}", "ALREADY GRANTED ACCESS");
+        assert!(!acc.contains(&escrow_account_id), "{}", "ALREADY GRANTED ACCESS");
+        let key = self.gen_nonce_key(&env::predecessor_account_id(), &escrow_account_id);
+        if let Some(deadline) = deadline {
+            assert!(deadline > env::block_timestamp(), "{}", "DEADLINE MUST BE IN THE FUTURE");
+            self.access_deadlines.insert(&key, &deadline);
+        }
+        if single_use.unwrap_or(false) {
+            self.access_nonces.insert(&key, &self.transfer_nonces.get(&key).unwrap_or(0));
+        }
         acc.insert(&escrow_account_id);
         self.account_gives_access.insert(&env::predecessor_account_id(), &acc);
         logger::add_escrow(env::predecessor_account_id(), acc.to_vec());
     }
     fn revoke_access(&mut self, escrow_account_id: AccountId) {
+        self.not_migrating();
         let mut acc = self.account_gives_access.get(&env::predecessor_account_id()).unwrap_or(UnorderedSet::new(env::sha256(env::predecessor_account_id().as_bytes()).to_vec()));
         acc.remove(&escrow_account_id);
+        self._clear_access_terms(&env::predecessor_account_id(), &escrow_account_id);
         self.account_gives_access.insert(&env::predecessor_account_id(), &acc);
         logger::add_escrow(env::predecessor_account_id(), acc.to_vec());
     }
@@ -189,39 +546,34 @@ impl NEP4 for NonFungibleToken {
 
     #[payable]
     fn transfer_from(&mut self, from: AccountId, to: AccountId, token_id: u64, edition_number: u64) {
+        self.not_migrating();
+        self.legacy_api();
         let index = self.tokens.get(&token_id).unwrap().edition_index + edition_number;
-        assert_eq!(self.is_paused(), false, "{}", PAUSED_ERR);
-        assert_eq!(self.check_access(from.clone(), env::predecessor_account_id()) ||
-                       self._is_allowed(index, env::predecessor_account_id()),
-                   true, "{}", ONLY_ESCROW);
-        self._internal_transfer(from, to, token_id, edition_number, index);
+        assert!(!self.is_paused(), "{}", PAUSED_ERR);
+        self.not_denied(&env::predecessor_account_id());
+        self.not_denied(&to);
+        let escrow = env::predecessor_account_id();
+        // an edition allowance carries its own expiry, the owner's grant terms only apply to escrow access
+        if !self._is_allowed(index, escrow.clone()) {
+            assert!(self.check_access(from.clone(), escrow.clone()), "{}", ONLY_ESCROW);
+            self._use_access_grant(&from, &escrow);
+        }
+        self._internal_transfer(from.clone(), to.clone(), token_id, edition_number, index, REASON_TRANSFER);
+        self._record_collection_activity(token_id, edition_number, EVENT_TRANSFER, 0, from, to);
     }
 
     #[payable]
     fn transfer(&mut self, to: AccountId, token_id: TokenId, edition_number: EditionNumber) {
-        assert_eq!(self.is_paused(), false, "{}", PAUSED_ERR);
-       // self.check_valid_account(to.clone());
+        self.not_migrating();
+        self.legacy_api();
+        assert!(!self.is_paused(), "{}", PAUSED_ERR);
+        self.not_denied(&env::predecessor_account_id());
+        self.not_denied(&to);
         self.only_token_owner(token_id, edition_number);
-        let index = self.tokens.get(&token_id).unwrap().edition_index;
-        let mut edition = self.editions.get(&u64::from(edition_number + index)).unwrap();
-        let state = self.edition_states.get(&(&edition_number + index)).unwrap();
-        // ensure token is available
-        match state {
-            EditionState::LOCKED => {
-                env::panic(TOKEN_LOCKED.as_bytes());
-            }
-            EditionState::LISTED => {
-                self.marketplace.remove(&(edition_number + index));
-                //self.events.push(&Event::new_event(EVENT_MARKET_DELETE.to_string(), env::predecessor_account_id(),
-                //                                   env::predecessor_account_id(), env::predecessor_account_id(), token_id, edition_number, 0));
-            }
-            _ => {}
-        }
-        assert_eq!(edition.edition_owner == env::predecessor_account_id() && edition.edition_number == edition_number, true, "{}", ONLY_TOKEN_OWNER);
-        edition.edition_owner = to.clone();
-        self.editions.insert(&u64::from(edition_number + index), &edition);
-        self._clear_allowance(u64::from(edition_number + index));
-        logger::transfer_edition(edition, u64::from(edition_number + index), to);
+        let index = self.tokens.get(&token_id).unwrap().edition_index + edition_number;
+        // same checks, bookkeeping and logs as every other transfer
+        self._internal_transfer(env::predecessor_account_id(), to.clone(), token_id, edition_number, index, REASON_TRANSFER);
+        self._record_collection_activity(token_id, edition_number, EVENT_TRANSFER, 0, env::predecessor_account_id(), to);
     }
     fn check_access(&self, account_id: AccountId, escrow_id: AccountId) -> bool {
         let acc = self.account_gives_access.get(&account_id).unwrap_or(UnorderedSet::new(account_id.as_bytes().to_vec()));
@@ -229,27 +581,22 @@ impl NEP4 for NonFungibleToken {
         acc.contains(&escrow_id)
     }
     fn grant_edition_allowance(&mut self, token_id: TokenId, edition_id: u64, account: AccountId) {
+        self.not_migrating();
         self.only_token_owner(token_id, edition_id);
         let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
-        let mut allowances = self.edition_allowances.get(&idx).unwrap();
-        assert_eq!(allowances.contains(&account), false, "ALREADY GRANTED ALLOWANCE");
-        allowances.insert(&account);
-        self.edition_allowances.insert(&idx, &allowances);
-        logger::edition_allowance(token_id, edition_id, idx, allowances.as_vector().to_vec())
+        assert!(!self._has_allowance(idx, &account), "ALREADY GRANTED ALLOWANCE");
+        self._approve(token_id, edition_id, idx, &account);
     }
     fn remove_edition_allowance(&mut self, token_id: TokenId, edition_id: u64, account: AccountId) {
+        self.not_migrating();
         self.only_token_owner(token_id, edition_id);
         let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
-        let mut allowances = self.edition_allowances.get(&idx).unwrap();
-        assert_eq!(allowances.contains(&account), true, "ALREADY GRANTED ALLOWANCE");
-        allowances.remove(&account);
-        self.edition_allowances.insert(&idx, &allowances);
-        logger::edition_allowance(token_id, edition_id, idx, allowances.as_vector().to_vec())
+        assert!(self._has_allowance(idx, &account), "ALREADY GRANTED ALLOWANCE");
+        self._revoke(token_id, edition_id, idx, &account);
     }
     fn check_allowance(&self, token_id: TokenId, edition_id: u64, account: AccountId) -> bool {
         let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
-        let allowances = self.edition_allowances.get(&idx).unwrap();
-        allowances.contains(&account)
+        self._is_allowed(idx, account)
     }
 }
 
@@ -258,7 +605,8 @@ impl NEP4 for NonFungibleToken {
 #[near_bindgen]
 impl NonFungibleToken {
     pub fn add_minter(&mut self, minter: AccountId) {
-        self.only_owner();
+        self.not_migrating();
+        self.only_minter_admin();
         self.minters.insert(&minter);
 
         logger::minter_added(minter);
@@ -266,42 +614,245 @@ impl NonFungibleToken {
         //                                   env::current_account_id().to_string(), minter, 0, 0, 0));
     }
     pub fn add_collection_minter(&mut self, collection_id: CollectionId, person: AccountId) {
+        self.not_migrating();
         let mut target = self.collections.get(&collection_id).unwrap();
-        assert_eq!(target.creator == env::predecessor_account_id(), true, "{}", ONLY_COLLECTION_MINTER);
-        assert_eq!(target.minters.contains(&person), false, "{}", "USER ALREADY AUTHORIZED");
+        assert!(target.creator == env::predecessor_account_id(), "{}", ONLY_COLLECTION_MINTER);
+        assert!(!target.minters.contains(&person), "{}", "USER ALREADY AUTHORIZED");
         target.minters.push(person);
         // self.minters.insert(&person);
         self.collections.insert(&collection_id, &target);
-        logger::collection_minter_update(target.clone(), collection_id.clone());
+        logger::collection_minter_update(target.clone(), collection_id);
     }
     pub fn remove_collection_minter(&mut self, collection_id: CollectionId, person: AccountId) {
+        self.not_migrating();
         let mut target = self.collections.get(&collection_id).unwrap();
-        assert_eq!(target.creator == env::predecessor_account_id(), true, "{}", ONLY_COLLECTION_MINTER);
-        assert_eq!(target.minters.contains(&person) == true, true, "{}", "USER NOT AUTHORIZED");
+        assert!(target.creator == env::predecessor_account_id(), "{}", ONLY_COLLECTION_MINTER);
+        assert!(target.minters.contains(&person), "{}", "USER NOT AUTHORIZED");
         let idx = target.minters.iter().position(|r| r.eq(&person)).unwrap();
         target.minters.remove(idx);
-        logger::collection_minter_update(target.clone(), collection_id.clone());
+        self.collections.insert(&collection_id, &target);
+        logger::collection_minter_update(target.clone(), collection_id);
     }
-    pub fn remove_minter(&mut self, minter: AccountId) {
+    /// blocks an account from minting, buying, bidding and transferring. it can still cancel its offers to get funds back.
+    pub fn deny(&mut self, account: AccountId) {
+        self.not_migrating();
+        self.only_owner();
+        assert!(self.denylist.insert(&account), "{}", "ACCOUNT ALREADY DENIED");
+        logger::denylist_update(account, true);
+    }
+    pub fn undeny(&mut self, account: AccountId) {
+        self.not_migrating();
+        self.only_owner();
+        assert!(self.denylist.remove(&account), "{}", "ACCOUNT NOT DENIED");
+        logger::denylist_update(account, false);
+    }
+    pub fn is_denied(&self, account: AccountId) -> bool {
+        self.denylist.contains(&account)
+    }
+    /// revokes every escrow the caller has granted access to
+    pub fn revoke_all_access(&mut self) {
+        self.not_migrating();
+        if let Some(mut acc) = self.account_gives_access.get(&env::predecessor_account_id()) {
+            acc.clear();
+            self.account_gives_access.insert(&env::predecessor_account_id(), &acc);
+        }
+        logger::add_escrow(env::predecessor_account_id(), Vec::new());
+    }
+    /// transfers several editions of a token to `to` in one call, at most MAX_BATCH. every edition is checked before
+    /// any is moved, one locked, burned or foreign edition rejects the whole batch.
+    pub fn transfer_batch(&mut self, to: AccountId, token_id: TokenId, edition_numbers: Vec<EditionNumber>) {
+        self.not_migrating();
+        assert!(!self.paused, "{}", PAUSED_ERR);
+        self.not_denied(&env::predecessor_account_id());
+        self.not_denied(&to);
+        self.check_valid_account(to.clone());
+        assert!(!edition_numbers.is_empty() && edition_numbers.len() <= MAX_BATCH, "{}", "INVALID NUMBER OF EDITIONS");
+        let from = env::predecessor_account_id();
+        let token = self.tokens.get(&token_id).unwrap();
+        for edition_number in edition_numbers.iter() {
+            let idx = token.edition_index + edition_number;
+            match self.edition_states.get(&idx) {
+                Some(EditionState::AVAILABLE) | Some(EditionState::LISTED) => {}
+                _ => env::panic(TOKEN_LOCKED.as_bytes()),
+            }
+            assert!(self.editions.get(&idx).is_some_and(|edition| edition.edition_owner == from), "{}", ONLY_TOKEN_OWNER);
+        }
+        for edition_number in edition_numbers.iter() {
+            self._internal_transfer(from.clone(), to.clone(), token_id, *edition_number, token.edition_index + edition_number, REASON_TRANSFER);
+            self._record_collection_activity(token_id, *edition_number, EVENT_TRANSFER, 0, from.clone(), to.clone());
+        }
+        logger::transfer_batch(token_id, EVENT_TRANSFER_BATCH.to_string(), edition_numbers, to);
+    }
+    pub fn revoke_access_batch(&mut self, escrows: Vec<AccountId>) {
+        self.not_migrating();
+        assert!(escrows.len() <= MAX_BATCH, "{}", "TOO MANY ESCROWS");
+        let mut acc = self.account_gives_access.get(&env::predecessor_account_id()).unwrap_or(UnorderedSet::new(env::sha256(env::predecessor_account_id().as_bytes()).to_vec()));
+        for escrow in escrows.iter() {
+            acc.remove(escrow);
+            self._clear_access_terms(&env::predecessor_account_id(), escrow);
+        }
+        self.account_gives_access.insert(&env::predecessor_account_id(), &acc);
+        logger::add_escrow(env::predecessor_account_id(), acc.to_vec());
+    }
+    /// owner attestation behind the "verified creator" badge
+    pub fn verify_creator(&mut self, account: AccountId) {
+        self.not_migrating();
+        self.only_owner();
+        assert!(self.verified_creators.insert(&account), "{}", "CREATOR ALREADY VERIFIED");
+        logger::creator_verification(account, true);
+    }
+    pub fn unverify_creator(&mut self, account: AccountId) {
+        self.not_migrating();
+        self.only_owner();
+        assert!(self.verified_creators.remove(&account), "{}", "CREATOR NOT VERIFIED");
+        logger::creator_verification(account, false);
+    }
+    pub fn is_verified_creator(&self, account: AccountId) -> bool {
+        self.verified_creators.contains(&account)
+    }
+    /// registers a fungible token contract for the UI's currency list. listings, offers and payouts are denominated
+    /// in NEAR only and take no currency argument, so there is nothing to check against the registry yet. rejecting
+    /// listings and offers in unregistered FTs is left to the FT settlement change that introduces them.
+    pub fn add_accepted_ft(&mut self, ft: AccountId, decimals: u8) {
+        self.not_migrating();
+        self.only_owner();
+        assert!(env::is_valid_account_id(ft.as_bytes()), "{}", ACC_NOT_VALID);
+        self.accepted_fts.insert(&ft, &decimals);
+        logger::accepted_ft_update(ft, Some(decimals));
+    }
+    pub fn remove_accepted_ft(&mut self, ft: AccountId) {
+        self.not_migrating();
+        self.only_owner();
+        assert!(self.accepted_fts.remove(&ft).is_some(), "{}", "FT NOT ACCEPTED");
+        logger::accepted_ft_update(ft, None);
+    }
+    pub fn accepted_fts(&self) -> Vec<AccountId> {
+        self.accepted_fts.keys().collect()
+    }
+    pub fn is_accepted_ft(&self, ft: AccountId) -> bool {
+        self.accepted_fts.get(&ft).is_some()
+    }
+    pub fn ft_decimals(&self, ft: AccountId) -> Option<u8> {
+        self.accepted_fts.get(&ft)
+    }
+    /// delegates add_minter/remove_minter to `admin` next to the owner, None takes the delegation back
+    pub fn set_minter_admin(&mut self, admin: Option<AccountId>) {
+        self.not_migrating();
         self.only_owner();
-        assert_eq!(self.minters.contains(&minter), true, "{}", ACC_NOT_VALID);
+        if let Some(admin) = admin.as_ref() {
+            assert!(env::is_valid_account_id(admin.as_bytes()), "{}", ACC_NOT_VALID);
+        }
+        self.minter_admin = admin;
+        logger::config_update("minter_admin", self.minter_admin.clone().unwrap_or_default());
+    }
+    pub fn minter_admin(&self) -> Option<AccountId> {
+        self.minter_admin.clone()
+    }
+    pub fn remove_minter(&mut self, minter: AccountId) {
+        self.not_migrating();
+        self.only_minter_admin();
+        assert!(self.minters.contains(&minter), "{}", ACC_NOT_VALID);
         self.minters.remove(&minter);
         logger::minter_removed(minter);
     }
 
 
     #[payable]
-    pub fn mint_token(&mut self, mut metadata: Metadata) {
-        assert!(env::attached_deposit() >= (self.mint_storage_fee + (self.edition_storage_fee * metadata.editions as u128)), "{} {}", DEPOSIT_NOT_ENOUGH, (self.mint_storage_fee + (self.edition_storage_fee * metadata.editions as u128)));
+    pub fn mint_token(&mut self, metadata: Metadata) {
+        self.not_migrating();
+        let required = self._mint_cost(metadata.editions);
+        self._check_deposit(required);
+        self._mint(metadata, env::predecessor_account_id());
+        self._settle_deposit(required);
+    }
+
+    /// mints a token whose editions the creator can take back at any time for `recall_price`, see recall_edition
+    #[payable]
+    pub fn mint_recallable(&mut self, metadata: Metadata, recall_price: String) {
+        self.not_migrating();
+        let recall_price = parse_balance(&recall_price);
+        let required = self._mint_cost(metadata.editions);
+        self._check_deposit(required);
+        let token_id = self._mint(metadata, env::predecessor_account_id());
+        self.recall_prices.insert(&token_id, &recall_price);
+        self._settle_deposit(required);
+    }
+    /// creator takes an edition of a recallable token back, the attached recall price is paid to the current owner
+    #[payable]
+    pub fn recall_edition(&mut self, token_id: TokenId, edition_id: EditionNumber) {
+        self.not_migrating();
+        let recall_price = self.recall_prices.get(&token_id).expect("TOKEN IS NOT RECALLABLE");
+        let token = self.tokens.get(&token_id).unwrap();
+        let md = self.metadata.get(&token.metadata).unwrap();
+        assert!(md.creator == env::predecessor_account_id(), "{}", "ONLY THE CREATOR CAN RECALL");
+        assert!(env::attached_deposit() >= recall_price, "{} {}", DEPOSIT_NOT_ENOUGH, recall_price);
+        let edition_index = token.edition_index + edition_id;
+        let owner = self.editions.get(&edition_index).unwrap().edition_owner;
+        assert!(owner != md.creator, "{}", "EDITION IS ALREADY OWNED BY THE CREATOR");
+        self._internal_transfer(owner.clone(), md.creator.clone(), token_id, edition_id, edition_index, REASON_RECALL);
+        logger::insert_activity(token_id, edition_id, EVENT_RECALL.to_string(), env::attached_deposit().to_string(), owner.clone());
+        self._record_collection_activity(token_id, edition_id, EVENT_RECALL, env::attached_deposit(), owner.clone(), md.creator);
+        Promise::new(owner.clone()).transfer(env::attached_deposit());
+        logger::near_transfer(owner, env::attached_deposit(), TransferReason::RECALL, env::block_timestamp());
+    }
+    pub fn recall_price(&self, token_id: TokenId) -> Option<String> {
+        self.recall_prices.get(&token_id).map(|price| price.to_string())
+    }
+
+    /// mints several tokens linked as one set, e.g. the panels of a diptych. the deposit must cover all of them.
+    /// set members stay individual tokens that can be traded separately.
+    #[payable]
+    pub fn mint_set(&mut self, items: Vec<Metadata>) -> Vec<TokenId> {
+        self.not_migrating();
+        assert!(items.len() >= 2 && items.len() <= MAX_SET_SIZE, "{}", "A SET NEEDS 2 TO 10 TOKENS");
+        let required = items.iter().map(|metadata| self._mint_cost(metadata.editions)).sum();
+        self._check_deposit(required);
+        let set_id = self.total_sets + 1;
+        let token_ids: Vec<TokenId> = items.into_iter().map(|metadata| self._mint(metadata, env::predecessor_account_id())).collect();
+        for token_id in token_ids.iter() {
+            self.token_sets.insert(token_id, &set_id);
+        }
+        self.sets.insert(&set_id, &token_ids);
+        self.total_sets = set_id;
+        logger::token_set(set_id, token_ids.clone());
+        self._settle_deposit(required);
+        token_ids
+    }
+    pub fn tokens_in_set(&self, set_id: u64) -> Vec<TokenId> {
+        self.sets.get(&set_id).unwrap_or_default()
+    }
+    pub fn set_of(&self, token_id: TokenId) -> Option<u64> {
+        self.token_sets.get(&token_id)
+    }
+
+    /// mints a token straight into `recipient`'s wallet. the minter stays the creator and earns the royalties.
+    #[payable]
+    pub fn mint_to(&mut self, metadata: Metadata, recipient: AccountId) {
+        self.not_migrating();
+        assert!(env::is_valid_account_id(recipient.as_bytes()), "{}", ACC_NOT_VALID);
+        let editions = metadata.editions;
+        let required = self._mint_cost(editions);
+        self._check_deposit(required);
+        let token_id = self._mint(metadata, recipient.clone());
+        for edition_number in 1..=editions {
+            logger::insert_activity(token_id, edition_number, "Transfer".to_string(), recipient.clone(), env::predecessor_account_id());
+        }
+        self._settle_deposit(required);
+    }
+
+    fn _mint(&mut self, mut metadata: Metadata, owner: AccountId) -> TokenId {
+        self.not_denied(&env::predecessor_account_id());
+        self.not_denied(&owner);
 
         self.only_whitelisted();
+        self._use_mint_rate();
         self._validate_token(metadata.clone());
         let new_token_id: TokenId = self.current_supply;
         let new_edition_index = self.total_editions + 1;
         metadata.creator = env::predecessor_account_id();
         metadata.date = env::block_timestamp().to_string();
         // check collection permission if metadata contains
-        let mut col = self.collections.get(&metadata.collection_id).unwrap();
+        let col = self.collections.get(&metadata.collection_id).unwrap();
         // check if sender is authorized to mint in that collection
         if metadata.collection_id > 0 {
             assert!(col.minters.contains(&(env::predecessor_account_id() as AccountId)), "{}", ONLY_COLLECTION_MINTER);
@@ -309,7 +860,7 @@ impl NonFungibleToken {
         // if collection exists
         // get token_id for new token
         // create new token
-        let mut new_token = Token {
+        let new_token = Token {
             edition_index: self.total_editions,
             editions: metadata.editions,
             metadata: new_token_id,
@@ -321,61 +872,286 @@ impl NonFungibleToken {
         // insert balances
         self.tokens.insert(&new_token_id, &new_token);
         self.metadata.insert(&new_token_id, &metadata);
+        self.royalty_bps.insert(&new_token_id, &metadata.royalty);
+        if !col.royalties.is_empty() {
+            self.token_royalties.insert(&new_token_id, &col.royalties);
+        }
+        let mut minted = self.creator_tokens.get(&metadata.creator).unwrap_or_else(|| Vector::new(format!("cr{}", metadata.creator).into_bytes()));
+        minted.push(&new_token_id);
+        self.creator_tokens.insert(&metadata.creator, &minted);
         // update user balance
-        self.generate_editions(new_token_id.clone(), metadata.clone(), env::predecessor_account_id(), new_edition_index);
+        self.generate_editions(new_token_id, metadata.clone(), owner.clone(), new_edition_index);
         // save states.
         self.current_supply += 1;
-        self.total_editions += metadata.editions as u64;
-        logger::log_mint(metadata, new_token_id, env::predecessor_account_id());
+        self.total_editions += metadata.editions;
+        self._record_collection_activity(new_token_id, 0, EVENT_MINT, 0, env::predecessor_account_id(), owner.clone());
+        logger::nep171_mint(owner, new_token_id, metadata.editions);
+        logger::log_mint(metadata, new_token_id);
+        new_token_id
+    }
+    /// counts a mint against the caller's rate limit window, starting a new window once the old one has passed
+    fn _use_mint_rate(&mut self) {
+        if self.mint_rate_limit == 0 {
+            return;
+        }
+        let account = env::predecessor_account_id();
+        let now = env::block_timestamp();
+        let (start, count) = match self.mint_rates.get(&account) {
+            Some((start, count)) if now < start + self.mint_rate_window => (start, count),
+            _ => (now, 0),
+        };
+        assert!(count < self.mint_rate_limit, "{}", "MINT RATE LIMIT REACHED, TRY AGAIN LATER");
+        self.mint_rates.insert(&account, &(start, count + 1));
+    }
+    /// max mints per account within `window` nanoseconds, 0 disables the limit
+    pub fn set_mint_rate_limit(&mut self, limit: u64, window: u64) {
+        self.not_migrating();
+        self.only_owner();
+        assert!(limit == 0 || window > 0, "{}", "RATE LIMIT WINDOW MUST NOT BE 0");
+        self.mint_rate_limit = limit;
+        self.mint_rate_window = window;
+    }
+    pub fn mint_rate_limit(&self) -> (u64, u64) {
+        (self.mint_rate_limit, self.mint_rate_window)
+    }
+    /// mints left for `account` in its current window, u64::MAX when unlimited
+    pub fn mints_remaining(&self, account: AccountId) -> u64 {
+        if self.mint_rate_limit == 0 {
+            return u64::MAX;
+        }
+        match self.mint_rates.get(&account) {
+            Some((start, count)) if env::block_timestamp() < start + self.mint_rate_window => self.mint_rate_limit.saturating_sub(count),
+            _ => self.mint_rate_limit,
+        }
+    }
+    fn _mint_cost(&self, editions: EditionNumber) -> Balance {
+        self.mint_storage_fee + (self.edition_storage_fee * editions as u128)
+    }
+    /// deposit must cover the required fee, and match it exactly when strict deposits are on
+    fn _check_deposit(&self, required: Balance) {
+        assert!(env::attached_deposit() >= required, "{} {}", DEPOSIT_NOT_ENOUGH, required);
+        if self.strict_deposits {
+            assert_eq!(env::attached_deposit(), required, "{} {}", DEPOSIT_NOT_EXACT, required);
+        }
+    }
+    /// books `required` as collected fees, withdrawable by the owner with withdraw_fees, and sends back whatever was
+    /// attached above it
+    fn _settle_deposit(&mut self, required: Balance) {
+        self.collected_fees += required;
+        if env::attached_deposit() > required {
+            Promise::new(env::predecessor_account_id()).transfer(env::attached_deposit() - required);
+        }
     }
     fn _validate_token(&self, meta: Metadata) {
-        assert_eq!(meta.editions <= self.MAX_EDITIONS as u64, true, "{}: {}", METADATA_ERROR, "Max Edition Number is 20.");
-        assert_eq!(meta.description.len() <= self.MAX_DESCRIPTION_LENGTH as usize, true, "{}: {}", METADATA_ERROR, "Description must be under 250 characters long.");
-        assert_eq!(meta.name.len() < self.MAX_NAME_LENGTH as usize, true, "{}: {}", METADATA_ERROR, "Name must be under 50 characters long.");
-        assert_eq!(meta.external_link.len() <= self.MAX_EXTERNAL_LINK as usize, true, "{}: {}", METADATA_ERROR, "External link must be under 100 characters long. Please use a url shortener or ipfs.");
-        assert_eq!(meta.tags.len() <= 3, true, "{}: {}", METADATA_ERROR, "Only 3 tags allowed.");
+        if let Err(reason) = self.validate_metadata(meta) {
+            env::panic(reason.as_bytes());
+        }
+    }
+    /// runs the mint checks without minting, returns the first failing reason
+    pub fn validate_metadata(&self, meta: Metadata) -> Result<(), String> {
+        let fail = |reason: &str| Err(format!("{}: {}", METADATA_ERROR, reason));
+        if meta.editions == 0 {
+            return fail("A token needs at least one edition.");
+        }
+        if meta.editions > self.MAX_EDITIONS as u64 {
+            return fail("Max Edition Number is 20.");
+        }
+        if meta.description.chars().count() > self.MAX_DESCRIPTION_LENGTH as usize {
+            return fail("Description must be under 250 characters long.");
+        }
+        if meta.name.chars().count() >= self.MAX_NAME_LENGTH as usize {
+            return fail("Name must be under 50 characters long.");
+        }
+        if meta.external_link.len() > self.MAX_EXTERNAL_LINK as usize {
+            return fail("External link must be under 100 characters long. Please use a url shortener or ipfs.");
+        }
+        let link = meta.external_link.to_lowercase();
+        if !link.is_empty() && !["http://", "https://", "ipfs://"].iter().any(|scheme| link.starts_with(scheme)) {
+            return fail("External link must start with http://, https:// or ipfs://.");
+        }
+        if meta.tags.len() > 3 {
+            return fail("Only 3 tags allowed.");
+        }
+        if meta.media_type.as_ref().is_some_and(|mime| mime.len() > 100 || !mime.contains('/')) {
+            return fail("Media type must be a MIME type like image/png.");
+        }
+        if meta.width == Some(0) || meta.height == Some(0) || meta.duration_ms == Some(0) {
+            return fail("Media dimensions and duration must be positive when given.");
+        }
+        if meta.royalty > 10_000 {
+            return fail("Royalty cannot exceed 10000 bps.");
+        }
+        if meta.royalty < self.min_royalty_bps {
+            return fail(&format!("Royalty is below the platform minimum of bps {}", self.min_royalty_bps));
+        }
+        Ok(())
         //assert_eq!(meta.thumbnail.len() == self.IPFS_HASH_LENGTH as usize, true, "{}: {}", METADATA_ERROR, "IPFS Hash must be 46 bytes long");
         //assert_eq!(meta.main.len() == self.IPFS_HASH_LENGTH as usize, true, "{}: {}", METADATA_ERROR, "IPFS Hash must be 46 bytes long");
     }
+    /// tokens minted before royalties were in bps store a divisor of the seller's proceeds (0 = none, 1 = all, n = 1/n),
+    /// this converts it to bps
+    fn _royalty_bps(&self, royalty: u32) -> u32 {
+        10_000u32.checked_div(royalty).unwrap_or(0)
+    }
+    /// collection creator limits trading of its editions to [trade_start, trade_end], either bound can be left open.
+    /// transfers are not affected.
+    pub fn set_trade_window(&mut self, collection_id: CollectionId, trade_start: Option<u64>, trade_end: Option<u64>) {
+        self.not_migrating();
+        let mut collection = self.collections.get(&collection_id).unwrap();
+        assert_eq!(collection.creator, env::predecessor_account_id(), "{}", "ONLY COLLECTION CREATOR CAN SET THE TRADE WINDOW");
+        if let (Some(start), Some(end)) = (trade_start, trade_end) {
+            assert!(start < end, "{}", "TRADE WINDOW MUST START BEFORE IT ENDS");
+        }
+        collection.trade_start = trade_start;
+        collection.trade_end = trade_end;
+        self.collections.insert(&collection_id, &collection);
+        logger::log_collection(collection, collection_id);
+    }
+    /// collection creator splits the royalty of its editions between recipients, in basis points of the seller's
+    /// proceeds. tokens keep the split the collection had when they were minted, changes only apply to later mints.
+    /// an empty list gives later mints their own creator royalty.
+    pub fn set_collection_royalties(&mut self, collection_id: CollectionId, royalties: Vec<(AccountId, u32)>) {
+        self.not_migrating();
+        let mut collection = self.collections.get(&collection_id).unwrap();
+        assert_eq!(collection.creator, env::predecessor_account_id(), "{}", "ONLY COLLECTION CREATOR CAN SET ROYALTIES");
+        self._validate_royalties(&royalties);
+        collection.royalties = royalties;
+        self.collections.insert(&collection_id, &collection);
+        logger::log_collection(collection, collection_id);
+    }
+    fn _validate_royalties(&self, royalties: &[(AccountId, u32)]) {
+        assert!(royalties.len() <= MAX_ROYALTY_RECIPIENTS, "{}", "TOO MANY ROYALTY RECIPIENTS");
+        for (account, _) in royalties.iter() {
+            assert!(env::is_valid_account_id(account.as_bytes()), "{}", ACC_NOT_VALID);
+        }
+        let total: u64 = royalties.iter().map(|(_, bps)| u64::from(*bps)).sum();
+        assert!(total <= 10_000, "{}", "ROYALTIES CANNOT EXCEED 10000 BPS");
+    }
+    fn _check_trade_window(&self, token_id: TokenId) {
+        let collection_id = self.metadata.get(&self.tokens.get(&token_id).unwrap().metadata).unwrap().collection_id;
+        if let Some(collection) = self.collections.get(&collection_id) {
+            let now = env::block_timestamp();
+            assert!(collection.trade_start.is_none_or(|start| now >= start) && collection.trade_end.is_none_or(|end| now <= end), "{}", TRADE_WINDOW_CLOSED);
+        }
+    }
     fn _validate_collection(&self, meta: Collection) {
-        assert_eq!(meta.name.len() <= self.MAX_NAME_LENGTH as usize, true, "{}: {}", METADATA_ERROR, "Name must be under 50 characters long.");
-        assert_eq!(meta.description.len() <= self.MAX_DESCRIPTION_LENGTH as usize, true, "{}: {}", METADATA_ERROR, "Description must be under 250 characters long.");
-        assert_eq!(meta.thumbnail.len() == self.IPFS_HASH_LENGTH as usize, true, "{}: {}", METADATA_ERROR, "IPFS Hash must be 46 bytes long");
+        assert!(meta.name.chars().count() <= self.MAX_NAME_LENGTH as usize, "{}: {}", METADATA_ERROR, "Name must be under 50 characters long.");
+        assert!(meta.description.chars().count() <= self.MAX_DESCRIPTION_LENGTH as usize, "{}: {}", METADATA_ERROR, "Description must be under 250 characters long.");
+        assert!(meta.thumbnail.len() == self.IPFS_HASH_LENGTH as usize, "{}: {}", METADATA_ERROR, "IPFS Hash must be 46 bytes long");
+        self._validate_royalties(&meta.royalties);
     }
     fn generate_editions(&mut self, new_token_id: TokenId, metadata: Metadata, pred: AccountId, current_edition: u64) {
-        // generate each unique edition
+        // generate each unique edition. indices only ever grow with total_editions, an index that was used before,
+        // burned ones included, is never handed out again
         for i in 0..metadata.editions {
-            self.editions.insert(&u64::from(&current_edition + i), &Edition {
+            assert!(self.edition_states.get(&(current_edition + i)).is_none(), "{}", "EDITION INDEX ALREADY ALLOCATED");
+            self.editions.insert(&(current_edition + i), &Edition {
                 edition_owner: pred.clone(),
                 edition_number: i + 1,
                 token_id: new_token_id,
             });
-            self.edition_states.insert(&u64::from(&current_edition + i), &EditionState::AVAILABLE);
-            // account_to_editions.insert(&u64::from(&current_edition + i));
-            let new_allowance: UnorderedSet<AccountId> = UnorderedSet::new(self.prefix(&current_edition.to_string()));
-            self.edition_allowances.insert(&u64::from(&current_edition + i), &new_allowance);
+            self.edition_states.insert(&(current_edition + i), &EditionState::AVAILABLE);
+            self._add_owned(&pred, current_edition + i);
+            let new_allowance: UnorderedSet<AccountId> = UnorderedSet::new(self.allowances_prefix(current_edition + i));
+            self.edition_allowances.insert(&(current_edition + i), &new_allowance);
             logger::log_mint_editions(Edition {
                 edition_owner: pred.clone(),
                 edition_number: i + 1,
                 token_id: new_token_id,
-            }, &current_edition + i);
+            }, current_edition + i);
         }
-        // self.account_to_editions.insert(&env::predecessor_account_id(), &account_to_editions);
     }
-    fn prefix(&self, account_id: &AccountId) -> Vec<u8> {
-        format!("o{}", account_id).into_bytes()
+    /// every edition has its own allowance set. editions minted before shared one per token under "o{first index}",
+    /// migrate_allowances moves them to their own
+    fn allowances_prefix(&self, edition_idx: u64) -> Vec<u8> {
+        format!("ep{}", edition_idx).into_bytes()
+    }
+    fn owned_editions_prefix(&self, account_id: &AccountId) -> Vec<u8> {
+        format!("oe{}", account_id).into_bytes()
+    }
+    fn _add_owned(&mut self, account: &AccountId, edition_idx: u64) {
+        let mut owned = self.account_to_editions.get(account).unwrap_or_else(|| UnorderedSet::new(self.owned_editions_prefix(account)));
+        owned.insert(&edition_idx);
+        self.account_to_editions.insert(account, &owned);
+    }
+    fn _remove_owned(&mut self, account: &AccountId, edition_idx: u64) {
+        if let Some(mut owned) = self.account_to_editions.get(account) {
+            owned.remove(&edition_idx);
+            self.account_to_editions.insert(account, &owned);
+        }
     }
-    //
-    // fn owned_editions_prefix(&self, account_id: &AccountId) -> Vec<u8> {
-    //     format!("oe{}", account_id).into_bytes()
-    // }
 
     // burns single, owned edition of a token. not every token! be careful using it. you will lose ownership of edition and edition will be lost forever.
 
     pub fn burn_edition(&mut self, token_id: TokenId, edition_id: EditionNumber) {
+        self.not_migrating();
+        self.only_token_owner(token_id, edition_id);
+        self._burn(token_id, edition_id, env::predecessor_account_id());
+    }
+
+    /// burns `owner`'s edition on their behalf, the caller needs a burn allowance on it
+    pub fn burn_edition_from(&mut self, owner: AccountId, token_id: TokenId, edition_id: EditionNumber) {
+        self.not_migrating();
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        assert_eq!(self.editions.get(&idx).unwrap().edition_owner, owner, "{}", ONLY_TOKEN_OWNER);
+        assert!(self._burn_allowances(idx).contains(&env::predecessor_account_id()), "{}", ONLY_ESCROW);
+        self._burn(token_id, edition_id, owner);
+    }
+
+    pub fn grant_burn_allowance(&mut self, token_id: TokenId, edition_id: EditionNumber, account: AccountId) {
+        self.not_migrating();
+        self.only_token_owner(token_id, edition_id);
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        let mut allowances = self._burn_allowances(idx);
+        assert!(allowances.insert(&account), "{}", "ALREADY GRANTED BURN ALLOWANCE");
+        self.burn_allowances.insert(&idx, &allowances);
+        logger::burn_allowance(token_id, edition_id, idx, allowances.to_vec());
+    }
+
+    pub fn remove_burn_allowance(&mut self, token_id: TokenId, edition_id: EditionNumber, account: AccountId) {
+        self.not_migrating();
+        self.only_token_owner(token_id, edition_id);
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        let mut allowances = self._burn_allowances(idx);
+        assert!(allowances.remove(&account), "{}", "BURN ALLOWANCE NOT GRANTED");
+        self.burn_allowances.insert(&idx, &allowances);
+        logger::burn_allowance(token_id, edition_id, idx, allowances.to_vec());
+    }
+
+    /// limits an already granted edition allowance to `expires_at` (block timestamp, nanoseconds)
+    pub fn set_allowance_expiry(&mut self, token_id: TokenId, edition_id: EditionNumber, account: AccountId, expires_at: u64) {
+        self.not_migrating();
         self.only_token_owner(token_id, edition_id);
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        assert!(self._has_allowance(idx, &account), "{}", "ALLOWANCE NOT GRANTED");
+        self.allowance_expiry.insert(&self.gen_allowance_key(idx, &account), &expires_at);
+    }
+
+    /// every delegate of an edition with what it is allowed to do
+    pub fn get_allowance_details(&self, token_id: TokenId, edition_id: EditionNumber) -> Vec<AllowanceInfo> {
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        let transfer: Vec<AccountId> = self.edition_allowances.get(&idx).unwrap()
+            .iter()
+            .filter(|account| self._has_allowance(idx, account))
+            .collect();
+        let burn = self._burn_allowances(idx);
+        let mut accounts = transfer.clone();
+        accounts.extend(burn.iter().filter(|account| !transfer.contains(account)));
+        accounts.into_iter().map(|account| AllowanceInfo {
+            expires_at: self.allowance_expiry.get(&self.gen_allowance_key(idx, &account)),
+            can_transfer: transfer.contains(&account),
+            can_burn: burn.contains(&account),
+            account,
+        }).collect()
+    }
 
+    pub fn get_burn_allowances(&self, token_id: TokenId, edition_id: EditionNumber) -> Vec<AccountId> {
+        self._burn_allowances(self.tokens.get(&token_id).unwrap().edition_index + edition_id).to_vec()
+    }
+
+    fn _burn(&mut self, token_id: TokenId, edition_id: EditionNumber, owner: AccountId) {
         let to_burn_idx = edition_id + self.tokens.get(&token_id).unwrap().edition_index;
+        assert!(!self.edition_shares.contains_key(&to_burn_idx), "{}", FRACTIONALIZED);
         let state = self.edition_states.get(&to_burn_idx).unwrap();
         match state {
             EditionState::LOCKED => {
@@ -383,23 +1159,26 @@ impl NonFungibleToken {
             }
             EditionState::LISTED => {
                 self.marketplace.remove(&to_burn_idx);
+                self.listings.remove(&to_burn_idx);
+                self._refund_prepaid_royalty(to_burn_idx);
             }
             _ => {}
         }
 
-        //  let mut owned = self.account_to_editions.get(&env::predecessor_account_id()).unwrap();
-        //   owned.remove(&to_burn_idx);
-        //   self.account_to_editions.insert(&env::predecessor_account_id(), &owned);
+        self._remove_owned(&owner, to_burn_idx);
 
         self.editions.remove(&to_burn_idx);
         self.edition_states.insert(&to_burn_idx, &EditionState::BURNED);
+        self.burned_editions += 1;
         self._clear_allowance(to_burn_idx);
-        logger::burn(token_id, edition_id, to_burn_idx, env::predecessor_account_id())
+        logger::nep171_burn(owner.clone(), token_id, edition_id);
+        logger::burn(token_id, edition_id, to_burn_idx, owner, env::predecessor_account_id())
     }
 
     #[payable]
     pub fn create_collection(&mut self, mut collection: Collection) {
-        assert!(env::attached_deposit() >= self.create_collection_fee, "{}", DEPOSIT_NOT_ENOUGH);
+        self.not_migrating();
+        self._check_deposit(self.create_collection_fee);
         self._validate_collection(collection.clone());
         self.only_whitelisted();
         let new_collection_id = self.total_collections + 1;
@@ -411,50 +1190,194 @@ impl NonFungibleToken {
         //self.events.push(&Event::new_event(EVENT_CREATE_COLLECTION.to_string(), env::predecessor_account_id(),
         //                                 env::current_account_id().to_string(), env::predecessor_account_id(), new_collection_id, new_collection_id, 0));
         self.total_collections += 1;
+        self._settle_deposit(self.create_collection_fee);
 
         logger::log_collection(collection, new_collection_id);
     }
 
+    /// the listing fee, if one is set, is attached per listed edition
+    #[payable]
     pub fn set_price(&mut self, token_id: TokenId, edition_id: EditionNumber, price_as_yoctonear: String) {
+        self.not_migrating();
         // check if its owner
         self.only_token_owner(token_id, edition_id);
-        let price = u128::from_str(&price_as_yoctonear).unwrap();
-        self._set_price(token_id, edition_id, price);
+        let price = parse_balance(&price_as_yoctonear);
+        assert!(env::attached_deposit() >= self.listing_fee, "{} {}", "DEPOSIT NOT ENOUGH", self.listing_fee);
+        self._set_price(token_id, edition_id, price, None);
+        self._settle_deposit(self.listing_fee);
+    }
+
+    /// set_price for an edition that has to be AVAILABLE, with an error of its own for listed, locked and burned ones
+    #[payable]
+    pub fn relist(&mut self, token_id: TokenId, edition_id: EditionNumber, price_as_yoctonear: String) {
+        self.not_migrating();
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        assert!(self.edition_states.get(&idx) == Some(EditionState::AVAILABLE), "{}", NOT_AVAILABLE_TO_RELIST);
+        self.only_token_owner(token_id, edition_id);
+        let price = parse_balance(&price_as_yoctonear);
+        assert!(env::attached_deposit() >= self.listing_fee, "{} {}", "DEPOSIT NOT ENOUGH", self.listing_fee);
+        self._set_price(token_id, edition_id, price, None);
+        self._settle_deposit(self.listing_fee);
     }
 
+    #[payable]
     pub fn batch_set_price(&mut self, token_id: TokenId, edition_ids: Vec<EditionNumber>, price_as_yoctonear: String) {
-        assert_eq!(edition_ids.len() > 0, true, "EDITIONS CANNOT BE EMPTY");
-        let price = u128::from_str(&price_as_yoctonear).unwrap();
-        for i in 0..edition_ids.len() {
-            self._set_price(token_id, edition_ids[i], price);
+        self.not_migrating();
+        assert!(!edition_ids.is_empty(), "EDITIONS CANNOT BE EMPTY");
+        let price = parse_balance(&price_as_yoctonear);
+        let required = self.listing_fee * edition_ids.len() as u128;
+        assert!(env::attached_deposit() >= required, "{} {}", "DEPOSIT NOT ENOUGH", required);
+        for edition_id in edition_ids {
+            self._set_price(token_id, edition_id, price, None);
+        }
+        self._settle_deposit(required);
+    }
+
+    /// lists multiple editions of a token, each at its own price, logged as a single batch update
+    #[payable]
+    pub fn set_prices(&mut self, token_id: TokenId, entries: Vec<(EditionNumber, String)>) {
+        self.not_migrating();
+        let token = self.tokens.get(&token_id).unwrap();
+        assert!(!entries.is_empty() && entries.len() as u64 <= token.editions, "{}", "INVALID NUMBER OF EDITIONS");
+        let required = self.listing_fee * entries.len() as u128;
+        assert!(env::attached_deposit() >= required, "{} {}", "DEPOSIT NOT ENOUGH", required);
+        // parse everything before any listing is touched
+        let listings: Vec<(EditionNumber, u128)> = entries.iter()
+            .map(|(edition_id, price)| (*edition_id, parse_balance(price)))
+            .collect();
+        for (edition_id, price) in listings.iter() {
+            self._list_edition(token_id, *edition_id, *price, None);
         }
+        self._settle_deposit(required);
+        logger::marketplace_batch_update(token_id, EVENT_MARKET_BATCH_UPDATE.to_string(), listings, env::predecessor_account_id());
+    }
+
+    fn _set_price(&mut self, token_id: TokenId, edition_id: EditionNumber, price: u128, ft: Option<AccountId>) {
+        self._list_edition(token_id, edition_id, price, ft);
+        logger::insert_activity(token_id, edition_id, EVENT_MARKET_UPDATE.to_string(), price.to_string(), env::predecessor_account_id());
     }
 
-    fn _set_price(&mut self, token_id: TokenId, edition_id: EditionNumber, price: u128) {
+    /// lists at `price` in NEAR, or in `ft` which has to be accepted. the NEAR price range doesn't apply to FT prices.
+    fn _list_edition(&mut self, token_id: TokenId, edition_id: EditionNumber, price: u128, ft: Option<AccountId>) {
+        self.not_token_paused(token_id);
         // add token to marketplace
         let token = self.tokens.get(&token_id).unwrap();
         let index = token.edition_index;
-        let edition = self.editions.get(&(u64::from(edition_id as u64 + index as u64))).unwrap();
-        assert_eq!(edition.edition_owner == env::predecessor_account_id(), true, "{}", ONLY_TOKEN_OWNER);
-        self.marketplace.insert(&(edition_id as u64 + index as u64), &price);
-        self.edition_states.insert(&(edition_id as u64 + index as u64), &EditionState::LISTED);
+        // burned editions have no entry in editions anymore, check the state first for a readable error
+        match self.edition_states.get(&(index + edition_id)) {
+            Some(EditionState::AVAILABLE) => {}
+            Some(EditionState::LISTED) => env::panic(ALREADY_LISTED.as_bytes()),
+            _ => env::panic(TOKEN_LOCKED.as_bytes()),
+        }
+        let edition = self.editions.get(&(edition_id + index)).unwrap();
+        assert!(edition.edition_owner == env::predecessor_account_id(), "{}", ONLY_TOKEN_OWNER);
+        self._check_min_hold(token_id, index + edition_id, &edition.edition_owner);
+        match ft {
+            Some(ft) => {
+                self._only_accepted_ft(&ft);
+                assert!(price > 0, "{}", "PRICE OUT OF RANGE");
+                self.listing_fts.insert(&(index + edition_id), &ft);
+            }
+            None => {
+                assert!(price >= self.min_listing_price && price <= self.max_listing_price, "{} {} - {}", "PRICE OUT OF RANGE", self.min_listing_price, self.max_listing_price);
+                self.listing_fts.remove(&(index + edition_id));
+            }
+        }
+        self.marketplace.insert(&(edition_id + index), &price);
+        self.listings.insert(&(index + edition_id));
+        self.listed_at.insert(&(index + edition_id), &env::block_timestamp());
+        if self.listing_fee > 0 {
+            self.listing_fees.insert(&(index + edition_id), &self.listing_fee);
+        } else {
+            self.listing_fees.remove(&(index + edition_id));
+        }
+        self.edition_states.insert(&(edition_id + index), &EditionState::LISTED);
 
         logger::marketplace_insert(edition, index + edition_id, price);
-        logger::insert_activity(token_id, edition_id, EVENT_MARKET_UPDATE.to_string(), price.to_string(), env::predecessor_account_id());
     }
 
+    /// page over every listed edition in the contract as (token_id, edition_number, price)
+    pub fn all_listings(&self, from_index: u64, limit: u64) -> Vec<(TokenId, EditionNumber, String)> {
+        let listed = self.listings.as_vector();
+        (from_index..listed.len().min(from_index.saturating_add(limit)))
+            .filter_map(|i| {
+                let idx = listed.get(i)?;
+                let edition = self.editions.get(&idx)?;
+                let price = self.marketplace.get(&idx)?;
+                Some((edition.token_id, edition.edition_number, price.to_string()))
+            })
+            .collect()
+    }
+    /// true while a listed edition is still within listing_grace_ns of being listed, cancelling it then refunds
+    /// the listing fee paid for it
+    pub fn is_within_listing_grace(&self, token_id: TokenId, edition_id: EditionNumber) -> bool {
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        self.listings.contains(&idx) && self._within_listing_grace(idx)
+    }
+    fn _within_listing_grace(&self, idx: u64) -> bool {
+        self.listed_at.get(&idx).is_some_and(|listed| env::block_timestamp() <= listed.saturating_add(self.listing_grace_ns))
+    }
+    /// fee in yoctoNEAR charged per listed edition, booked with the other collected fees
+    pub fn set_listing_fee(&mut self, fee: String) {
+        self.not_migrating();
+        self.only_owner();
+        self.listing_fee = parse_balance(&fee);
+    }
+    pub fn listing_fee(&self) -> String {
+        self.listing_fee.to_string()
+    }
+    pub fn set_listing_grace(&mut self, grace_ns: u64) {
+        self.not_migrating();
+        self.only_owner();
+        self.listing_grace_ns = grace_ns;
+    }
+    pub fn listing_grace(&self) -> u64 {
+        self.listing_grace_ns
+    }
+    /// bounds for listing prices in yoctoNEAR, the max can't exceed u128::MAX / 10000
+    pub fn set_listing_price_range(&mut self, min_price: String, max_price: String) {
+        self.not_migrating();
+        self.only_owner();
+        let (min_price, max_price) = (parse_balance(&min_price), parse_balance(&max_price));
+        assert!(min_price <= max_price && max_price <= MAX_LISTING_PRICE, "{}", "INVALID LISTING PRICE RANGE");
+        self.min_listing_price = min_price;
+        self.max_listing_price = max_price;
+        logger::config_update("listing_price_range", format!("{}-{}", min_price, max_price));
+    }
+    pub fn listing_price_range(&self) -> (String, String) {
+        (self.min_listing_price.to_string(), self.max_listing_price.to_string())
+    }
+    pub fn listings_count(&self) -> u64 {
+        self.listings.len()
+    }
     pub fn get_price(&self, token_id: TokenId, edition_id: EditionNumber) -> TokenPrice {
         let index = self.tokens.get(&token_id).unwrap().edition_index;
-        self.marketplace.get(&(edition_id as u64 + index as u64)).unwrap()
+        self.marketplace.get(&(edition_id + index)).unwrap()
     }
 
     pub fn cancel_sale(&mut self, token_id: TokenId, edition_id: u64) {
+        self.not_migrating();
         self.only_token_owner(token_id, edition_id);
         // remove token from marketplace
         let index = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
         let edition = self.editions.get(&index).unwrap();
-        assert_eq!(edition.edition_owner == env::predecessor_account_id(), true, "{}", ONLY_TOKEN_OWNER);
-        self.marketplace.remove(&edition_id);
+        assert!(edition.edition_owner == env::predecessor_account_id(), "{}", ONLY_TOKEN_OWNER);
+        // marketplace is keyed by the absolute edition index, like everywhere else.
+        // only a listed edition goes back to available, a locked one stays locked
+        assert!(self.marketplace.remove(&index).is_some(), "{}", "EDITION IS NOT LISTED");
+        self.listings.remove(&index);
+        self.edition_states.insert(&index, &EditionState::AVAILABLE);
+        self._refund_prepaid_royalty(index);
+        // a listing cancelled within the grace period gets its listing fee back out of the collected fees. fees
+        // withdrawn since are gone, only what is still booked is refunded so other users' escrow is never touched
+        if let Some(fee) = self.listing_fees.remove(&index) {
+            let refund = std::cmp::min(fee, self.collected_fees);
+            if refund > 0 && self._within_listing_grace(index) {
+                self.collected_fees -= refund;
+                Promise::new(edition.edition_owner.clone()).transfer(refund);
+                logger::near_transfer(edition.edition_owner.clone(), refund, TransferReason::REFUND, env::block_timestamp());
+            }
+        }
         logger::marketplace_remove(edition, index);
         // self.events.push(&Event::new_event(EVENT_MARKET_DELETE.to_string(), env::predecessor_account_id(),
         //                                   env::current_account_id().to_string(), env::predecessor_account_id(), token_id, edition_id, 0));
@@ -462,173 +1385,1391 @@ impl NonFungibleToken {
 
     #[payable]
     pub fn buy(&mut self, token_id: TokenId, edition_id: u64) {
+        self.not_migrating();
+        self.not_denied(&env::predecessor_account_id());
         // check price & deposit & check if token available
         let token = self.tokens.get(&token_id).unwrap();
         let idx = token.edition_index;
         let edition_index = idx + edition_id;
         let listed = self.marketplace.get(&edition_index).unwrap();
-        /// return money if deposit not enough
-        assert_eq!(env::attached_deposit() >= listed, true, "{}", "DEPOSIT NOT ENOUGH");
-        let mut target = self.editions.get(&edition_index).unwrap();
-        let old_owner = target.edition_owner.clone();
-        assert_eq!(env::predecessor_account_id() != old_owner.clone(), true, "{}", "CANNOT BUY YOUR OWN TOKEN");
+        assert!(!self.listing_fts.contains_key(&edition_index), "{}", "LISTING IS PRICED IN AN FT");
+        // return money if deposit not enough
+        assert!(env::attached_deposit() >= listed, "{}", "DEPOSIT NOT ENOUGH");
+        self._buy(token_id, edition_id, env::predecessor_account_id(), env::attached_deposit(), None);
+    }
 
-        // send money to their owners, calculate royalties
-        self._internal_transfer(old_owner.clone(), env::predecessor_account_id(), token_id, edition_id, edition_index.clone());
-        logger::insert_activity(token_id, edition_id, EVENT_MARKET_BUY.to_string(), env::attached_deposit().to_string(), old_owner.clone());
-        logger::marketplace_remove(target.clone(), edition_index);
-        let nearfolio_fee: u128 = env::attached_deposit().div(self.trade_fee);
-        let rest = env::attached_deposit() - nearfolio_fee;
-        let mut sellers: u128 = 0;
-        Promise::new(self.fee_receiver.clone()).transfer(nearfolio_fee);
-        logger::near_transfer(self.fee_receiver.clone(), nearfolio_fee, TransferReason::FEE, env::block_timestamp());
-        let md = self.metadata.get(&token.metadata).unwrap();
-        let mut royalty_fee = 0;
-        if md.creator != target.edition_owner {
-            if md.royalty == 1 {
-                Promise::new(md.creator.clone()).transfer(rest);
-                logger::near_transfer(md.creator.clone(), rest.clone(), TransferReason::ROYALTY, env::block_timestamp());
-                //   env::log(format!("Sent royalties. {} $NEAR to {}", rest, md.creator.clone()).as_bytes());
-            } else if md.royalty > 1 {
-                royalty_fee = rest.div((u128::from(md.royalty)));
-                sellers = rest.sub(royalty_fee);
-                if royalty_fee > 0 {
-                    Promise::new(md.creator.clone()).transfer(royalty_fee);
-                    logger::near_transfer(md.creator, royalty_fee, TransferReason::ROYALTY, env::block_timestamp());
+    /// buys the `count` cheapest listed editions of a token in one go. the deposit must cover their summed price,
+    /// each edition is paid out like a regular buy and whatever is left over is refunded.
+    #[payable]
+    pub fn sweep(&mut self, token_id: TokenId, count: u64) {
+        self.not_migrating();
+        self.not_denied(&env::predecessor_account_id());
+        assert!(count > 0 && count <= MAX_SWEEP, "{}", "INVALID SWEEP COUNT");
+        let token = self.tokens.get(&token_id).unwrap();
+        let mut listed: Vec<(TokenPrice, EditionNumber)> = (1..=token.editions)
+            .filter_map(|edition_id| {
+                let idx = token.edition_index + edition_id;
+                match (self.marketplace.get(&idx), self.editions.get(&idx)) {
+                    (Some(price), Some(edition)) if edition.edition_owner != env::predecessor_account_id() && !self.listing_fts.contains_key(&idx) => Some((price, edition_id)),
+                    _ => None,
                 }
-            } else {
-                sellers = rest
-            }
-        } else {
-            sellers = rest
+            })
+            .collect();
+        assert!(listed.len() as u64 >= count, "{}", "NOT ENOUGH LISTED EDITIONS");
+        listed.sort();
+        listed.truncate(count as usize);
+        let total: Balance = listed.iter().map(|(price, _)| price).sum();
+        assert!(env::attached_deposit() >= total, "{} {}", "DEPOSIT NOT ENOUGH", total);
+        for (price, edition_id) in listed {
+            self._buy(token_id, edition_id, env::predecessor_account_id(), price, None);
         }
-        if sellers > 0 {
-            Promise::new(old_owner.clone()).transfer(sellers.clone());
-            logger::near_transfer(old_owner.clone(), sellers, TransferReason::SALE, env::block_timestamp());
+        if env::attached_deposit() > total {
+            Promise::new(env::predecessor_account_id()).transfer(env::attached_deposit() - total);
         }
     }
 
-    #[payable]
-    pub fn offer(&mut self, token_id: TokenId, edition_id: EditionNumber) {
-        assert_eq!(!self.paused, true, "{}", PAUSED_ERR);
+    /// sells a listed edition to `buyer` for `payment`, paid out in `ft` for listings priced in an FT
+    pub(crate) fn _buy(&mut self, token_id: TokenId, edition_id: EditionNumber, buyer: AccountId, payment: Balance, ft: Option<AccountId>) {
+        self._check_trade_window(token_id);
         let token = self.tokens.get(&token_id).unwrap();
-        let edition = self.editions.get(&(token.edition_index + edition_id as u64)).unwrap();
-        assert_eq!(env::attached_deposit() > self.mint_storage_fee, true, "{}", "NOTHING DEPOSITED");
-        assert_eq!(edition.edition_owner != env::predecessor_account_id(), true, "YOU CANNOT BID ON YOUR OWN TOKEN");
-        let tok_x_edition: String = self.gen_token_x_edition(token_id, edition_id);
-        let bid: Bid = Bid {
-            bidder: env::predecessor_account_id(),
-            amount: env::attached_deposit(),
-            date: env::block_timestamp().to_string(),
-            executed: false,
-        };
-        let mut current_offers = self.offers.get(&tok_x_edition).unwrap_or(Vector::new(sha256(tok_x_edition.as_bytes()).to_vec()));
-        current_offers.push(&bid);
-
-
-        logger::new_offer(bid.clone(), current_offers.len() - 1, token_id.clone(), edition_id.clone());
-        self.offers.insert(&tok_x_edition, &current_offers);
+        let edition_index = token.edition_index + edition_id;
+        let target = self.editions.get(&edition_index).unwrap();
+        let old_owner = target.edition_owner.clone();
+        assert!(buyer != old_owner, "{}", "CANNOT BUY YOUR OWN TOKEN");
+        self._use_resale_approval(token.metadata, edition_index, &old_owner, &buyer);
+        self._check_min_hold(token_id, edition_index, &old_owner);
 
-        logger::insert_activity(token_id, edition_id, EVENT_OFFER.to_string(), bid.amount.to_string(), edition.edition_owner);
+        // a sale buys out every co-owner, the buyer becomes the sole owner
+        let shares = self.edition_shares.remove(&edition_index);
+        // send money to their owners, calculate royalties
+        self._internal_transfer(old_owner.clone(), buyer.clone(), token_id, edition_id, edition_index, REASON_SALE);
+        logger::insert_activity(token_id, edition_id, EVENT_MARKET_BUY.to_string(), payment.to_string(), old_owner.clone());
+        logger::marketplace_remove(target, edition_index);
+        self._record_collection_activity(token_id, edition_id, EVENT_MARKET_BUY, payment, old_owner.clone(), buyer.clone());
+        match ft {
+            // last sales and collector volume are kept in NEAR only
+            Some(ft) => self._distribute_ft_sale(ft, token.metadata, edition_id, old_owner, buyer, payment, shares),
+            None => {
+                self.last_sales.insert(&edition_index, &payment);
+                self._add_collector_volume(buyer.clone(), payment);
+                self._distribute_sale(token.metadata, edition_id, old_owner, buyer, payment, shares);
+            }
+        }
     }
 
-    pub fn accept_offer(&mut self, token_id: TokenId, edition_id: EditionNumber, idx: u64) {
-        /// accept, /remove other offers/, transfer money, transfer nft
-        let tokxedition = self.gen_token_x_edition(token_id, edition_id);
+    /// transfers an edition sold off-chain to `buyer` and records the agreed price as its last sale, no NEAR is moved
+    /// except the royalty on that price, computed after the trade fee like a marketplace sale. it has to be attached
+    /// in full when otc_royalty_required is on, otherwise a partial deposit is shared out between the royalty
+    /// recipients pro rata. anything attached above the royalty goes back to the seller.
+    #[payable]
+    pub fn record_otc_sale(&mut self, token_id: TokenId, edition_id: EditionNumber, buyer: AccountId, price: String) {
+        self.not_migrating();
+        self.not_denied(&env::predecessor_account_id());
+        self.not_denied(&buyer);
+        let seller = env::predecessor_account_id();
+        assert!(seller != buyer, "{}", "CANNOT SELL TO YOURSELF");
+        let price = parse_balance(&price);
         let token = self.tokens.get(&token_id).unwrap();
-        let edition_idx = token.edition_index + edition_id as u64;
-        let mut edition = self.editions.get(&edition_idx).unwrap();
-        assert_eq!(edition.edition_owner == env::predecessor_account_id(), true, "{}", ONLY_TOKEN_OWNER);
-        let old_owner = edition.edition_owner.clone();
-        let mut offers = self.offers.get(&tokxedition).unwrap();
-        let mut to_be_accepted = offers.get(idx).unwrap();
-        assert_eq!(to_be_accepted.executed == false, true, "{}", "OFFER IS CANCELLED OR ACCEPTED.");
-        self._internal_transfer(env::predecessor_account_id(), to_be_accepted.bidder.clone(), token_id, edition_id, edition_idx.clone());
-
-        self.edition_states.insert(&(edition_idx as u64), &EditionState::AVAILABLE);
-        // send money to their owners
-        let nearfolio_fee: u128 = to_be_accepted.amount.div(self.trade_fee);
-        let rest = to_be_accepted.amount - nearfolio_fee;
-        let mut sellers: u128 = 0;
-        Promise::new(self.fee_receiver.clone()).transfer(nearfolio_fee);
-        logger::near_transfer(self.fee_receiver.clone(), nearfolio_fee.clone(), TransferReason::FEE, env::block_timestamp());
+        let edition_index = token.edition_index + edition_id;
         let md = self.metadata.get(&token.metadata).unwrap();
-        let mut royalty_fee = 0;
-        if md.creator != edition.edition_owner {
-            if md.royalty == 1 {
-                Promise::new(md.creator.clone()).transfer(rest);
-                logger::near_transfer(md.creator, rest.clone(), TransferReason::ROYALTY, env::block_timestamp());
-                // env::log(format!("Sent royalties. {} $NEAR to {}", rest, md.creator.clone()).as_bytes());
-            } else if md.royalty > 1 {
-                royalty_fee = rest.div((u128::from(md.royalty)));
-                sellers = rest.sub(royalty_fee);
-                if royalty_fee > 0 {
-                    Promise::new(md.creator.clone()).transfer(royalty_fee);
-                    logger::near_transfer(md.creator, royalty_fee, TransferReason::ROYALTY, env::block_timestamp());
-                    // env::log(format!("Sent royalties. {} $NEAR to {}", royalty_fee, md.creator.clone()).as_bytes());
+        let royalties = if md.creator != seller {
+            self._royalty_split(token.metadata, &md, self._checked_payout(price, self._trade_fee_of(price)))
+        } else {
+            Vec::new()
+        };
+        let royalty_fee: Balance = royalties.iter().map(|(_, amount)| amount).sum();
+        if self.otc_royalty_required {
+            self._check_deposit(royalty_fee);
+        }
+        self._internal_transfer(seller.clone(), buyer.clone(), token_id, edition_id, edition_index, REASON_OTC);
+        self.last_sales.insert(&edition_index, &price);
+        logger::insert_activity(token_id, edition_id, EVENT_OTC_SALE.to_string(), price.to_string(), seller.clone());
+        self._record_collection_activity(token_id, edition_id, EVENT_OTC_SALE, price, seller.clone(), buyer);
+        let mut refund = env::attached_deposit();
+        // share of the royalty covered by the deposit in bps, so every recipient is short by the same fraction
+        if let Some(covered_bps) = (std::cmp::min(refund, royalty_fee) * 10_000).checked_div(royalty_fee) {
+            for (account, amount) in royalties {
+                let paid = if covered_bps == 10_000 { amount } else { amount * covered_bps / 10_000 };
+                if paid > 0 {
+                    refund -= paid;
+                    Promise::new(account.clone()).transfer(paid);
+                    logger::near_transfer(account, paid, TransferReason::ROYALTY, env::block_timestamp());
                 }
-            } else {
-                sellers = rest
             }
-        } else {
-            sellers = rest
         }
-        if sellers > 0 {
-            Promise::new(old_owner.clone()).transfer(sellers.clone());
-            logger::near_transfer(old_owner.clone(), sellers.clone(), TransferReason::SALE, env::block_timestamp());
+        if refund > 0 {
+            Promise::new(seller.clone()).transfer(refund);
+            logger::near_transfer(seller, refund, TransferReason::REFUND, env::block_timestamp());
         }
-        logger::marketplace_remove(edition.clone(), edition_idx.clone());
-        logger::accept_offer(to_be_accepted.amount.clone(), env::predecessor_account_id(), idx.clone(), token_id.clone(), edition_id.clone(), env::block_timestamp());
-        logger::transfer_edition(edition.clone(), edition_idx.clone(), to_be_accepted.bidder.clone());
-        logger::insert_activity(token_id, edition_id, EVENT_ACCEPT_OFFER.to_string(), to_be_accepted.amount.to_string(), to_be_accepted.bidder.clone());
-        to_be_accepted.executed = true;
-        to_be_accepted.bidder = "".to_string();
-        to_be_accepted.amount = 0;
-        offers.replace(idx, &to_be_accepted);
-        self.offers.insert(&tokxedition, &offers);
     }
 
-    pub fn cancel_offer(&mut self, token_id: TokenId, edition_id: EditionNumber, idx: u64) {
-        let tokxedition = self.gen_token_x_edition(token_id, edition_id);
-        let mut offer = self.offers.get(&tokxedition).unwrap();
-        let mut to_be_cancelled = offer.get(idx).unwrap();
-        assert_eq!(to_be_cancelled.executed == false, true, "{}", "OFFER IS CANCELLED OR ACCEPTED.");
-        assert_eq!(to_be_cancelled.bidder == env::predecessor_account_id(), true, "{}", "ONLY OFFER OWNER CAN CANCEL");
-
-        let mut cut_storage_fee = 0;
-        if to_be_cancelled.amount > self.edition_storage_fee {
-            cut_storage_fee = to_be_cancelled.amount - self.edition_storage_fee;
-            Promise::new(env::predecessor_account_id()).transfer(cut_storage_fee);
-        }
-        offer.replace(idx, &to_be_cancelled);
-        self.offers.insert(&tokxedition, &offer);
-
-        self.offers.insert(&tokxedition, &offer);
-        logger::execute_offer(to_be_cancelled.clone(), idx, token_id.clone(), edition_id.clone());
-        logger::insert_activity(token_id, edition_id, EVENT_CANCEL_OFFER.to_string(), to_be_cancelled.amount.to_string(), to_be_cancelled.bidder.clone());
-        to_be_cancelled.bidder = String::from("::");
-        to_be_cancelled.executed = true;
+    /// reserves an available edition for `claimant`, who can take it for free with claim and pays the gas for it
+    pub fn make_claimable(&mut self, token_id: TokenId, edition_id: EditionNumber, claimant: AccountId) {
+        self.not_migrating();
+        assert!(env::is_valid_account_id(claimant.as_bytes()), "{}", ACC_NOT_VALID);
+        let idx = self._reserve_claimable(token_id, edition_id);
+        self.claimables.insert(&idx, &claimant);
+        logger::insert_activity(token_id, edition_id, "Claimable".to_string(), claimant, env::predecessor_account_id());
+    }
+    /// reserves an available edition for whoever on its collection's allowlist claims it first with claim_with_proof
+    pub fn make_claimable_by_allowlist(&mut self, token_id: TokenId, edition_id: EditionNumber) {
+        self.not_migrating();
+        let collection_id = self.metadata.get(&self.tokens.get(&token_id).unwrap().metadata).unwrap().collection_id;
+        assert!(self.claim_keys.contains_key(&collection_id), "{}", "COLLECTION HAS NO CLAIM KEY");
+        let idx = self._reserve_claimable(token_id, edition_id);
+        self.allowlist_claimables.insert(&idx, &true);
+        let mut queue = self.allowlist_queue.get(&collection_id).unwrap_or_default();
+        queue.push(idx);
+        self.allowlist_queue.insert(&collection_id, &queue);
+        logger::insert_activity(token_id, edition_id, "Claimable".to_string(), format!("allowlist_{}", collection_id), env::predecessor_account_id());
+    }
+    pub fn revoke_claimable(&mut self, token_id: TokenId, edition_id: EditionNumber) {
+        self.not_migrating();
+        self.only_token_owner(token_id, edition_id);
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        let claimant = match self.claimables.remove(&idx) {
+            Some(claimant) => claimant,
+            None => {
+                self.allowlist_claimables.remove(&idx).unwrap_or_else(|| env::panic(b"EDITION IS NOT CLAIMABLE"));
+                self._dequeue_allowlist_claimable(token_id, idx);
+                "allowlist".to_string()
+            }
+        };
+        self.edition_states.insert(&idx, &EditionState::AVAILABLE);
+        logger::insert_activity(token_id, edition_id, "ClaimableRevoked".to_string(), claimant, env::predecessor_account_id());
+    }
+    pub fn claim(&mut self, token_id: TokenId, edition_id: EditionNumber) {
+        self.not_migrating();
+        self.not_denied(&env::predecessor_account_id());
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        let claimant = self.claimables.get(&idx).unwrap_or_else(|| env::panic(b"EDITION IS NOT CLAIMABLE"));
+        assert_eq!(claimant, env::predecessor_account_id(), "{}", "ONLY THE DESIGNATED CLAIMANT CAN CLAIM");
+        self.claimables.remove(&idx);
+        self._complete_claim(token_id, edition_id, idx, claimant);
+    }
+    /// collection creator registers the ed25519 key that signs its allowlist, None removes it. the allowlist
+    /// itself stays off-chain, each allowlisted account gets the creator's signature over its account id.
+    pub fn set_claim_key(&mut self, collection_id: CollectionId, public_key: Option<Base58PublicKey>) {
+        self.not_migrating();
+        let collection = self.collections.get(&collection_id).unwrap_or_else(|| env::panic(b"COLLECTION DOES NOT EXIST"));
+        assert_eq!(collection.creator, env::predecessor_account_id(), "{}", "ONLY COLLECTION CREATOR CAN SET THE CLAIM KEY");
+        match public_key {
+            Some(public_key) => {
+                // Base58PublicKey leads with the curve type, 0 being ed25519
+                assert!(public_key.0.len() == 33 && public_key.0[0] == 0, "{}", "CLAIM KEY MUST BE AN ED25519 KEY");
+                self.claim_keys.insert(&collection_id, &public_key.0[1..].to_vec());
+            }
+            None => {
+                self.claim_keys.remove(&collection_id);
+            }
+        }
+    }
+    /// claims the edition of the collection reserved longest ago with make_claimable_by_allowlist. `signature` is the
+    /// collection's claim key signing the caller's account id. each account can claim one edition per collection.
+    pub fn claim_with_proof(&mut self, collection_id: CollectionId, signature: Base64VecU8) {
+        self.not_migrating();
+        let claimant = env::predecessor_account_id();
+        self.not_denied(&claimant);
+        let key = self.claim_keys.get(&collection_id).unwrap_or_else(|| env::panic(b"COLLECTION HAS NO CLAIM KEY"));
+        assert!(self._verify_claim_signature(&key, &claimant, &signature.0), "{}", "INVALID ALLOWLIST SIGNATURE");
+        let claimed_key = format!("{}::{}", collection_id, claimant);
+        assert!(!self.allowlist_claimed.contains_key(&claimed_key), "{}", "ALREADY CLAIMED FROM THIS COLLECTION");
+        let mut queue = self.allowlist_queue.get(&collection_id).unwrap_or_default();
+        assert!(!queue.is_empty(), "{}", "NO EDITION OF THIS COLLECTION IS CLAIMABLE");
+        let idx = queue.remove(0);
+        self.allowlist_queue.insert(&collection_id, &queue);
+        self.allowlist_claimed.insert(&claimed_key, &true);
+        self.allowlist_claimables.remove(&idx);
+        let edition = self.editions.get(&idx).unwrap();
+        self._complete_claim(edition.token_id, edition.edition_number, idx, claimant);
+    }
+    pub fn has_claimed_from_allowlist(&self, collection_id: CollectionId, account: AccountId) -> bool {
+        self.allowlist_claimed.contains_key(&format!("{}::{}", collection_id, account))
+    }
+    fn _verify_claim_signature(&self, key: &[u8], account: &AccountId, signature: &[u8]) -> bool {
+        let key = match ed25519_dalek::PublicKey::from_bytes(key) {
+            Ok(key) => key,
+            Err(_) => return false,
+        };
+        match ed25519_dalek::Signature::try_from(signature) {
+            Ok(signature) => key.verify_strict(account.as_bytes(), &signature).is_ok(),
+            Err(_) => false,
+        }
+    }
+    /// drops a reserved edition from its collection's claim queue once it's revoked or force unlocked
+    fn _dequeue_allowlist_claimable(&mut self, token_id: TokenId, idx: u64) {
+        let collection_id = self.metadata.get(&self.tokens.get(&token_id).unwrap().metadata).unwrap().collection_id;
+        if let Some(mut queue) = self.allowlist_queue.get(&collection_id) {
+            queue.retain(|queued| *queued != idx);
+            self.allowlist_queue.insert(&collection_id, &queue);
+        }
+    }
+    /// checks an edition can be reserved for a claim and locks it, returns its absolute index
+    fn _reserve_claimable(&mut self, token_id: TokenId, edition_id: EditionNumber) -> u64 {
+        assert!(!self.paused, "{}", PAUSED_ERR);
+        self.not_token_paused(token_id);
+        self.only_token_owner(token_id, edition_id);
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        assert!(!self.edition_shares.contains_key(&idx), "{}", FRACTIONALIZED);
+        match self.edition_states.get(&idx).unwrap() {
+            EditionState::AVAILABLE => {}
+            _ => env::panic(b"EDITION MUST BE AVAILABLE TO BE MADE CLAIMABLE"),
+        }
+        self.edition_states.insert(&idx, &EditionState::LOCKED);
+        idx
+    }
+    fn _complete_claim(&mut self, token_id: TokenId, edition_id: EditionNumber, idx: u64, claimant: AccountId) {
+        self.edition_states.insert(&idx, &EditionState::AVAILABLE);
+        let owner = self.editions.get(&idx).unwrap().edition_owner;
+        self._internal_transfer(owner.clone(), claimant.clone(), token_id, edition_id, idx, REASON_CLAIM);
+        self._record_collection_activity(token_id, edition_id, EVENT_CLAIM, 0, owner, claimant);
+    }
+    pub fn claimant_of(&self, token_id: TokenId, edition_id: EditionNumber) -> Option<AccountId> {
+        self.claimables.get(&(self.tokens.get(&token_id).unwrap().edition_index + edition_id))
+    }
+
+    /// locks an available edition into an english auction running for `duration` nanoseconds.
+    /// bids must meet the reserve and beat the highest bid, the outbid bidder is refunded right away.
+    pub fn start_auction(&mut self, token_id: TokenId, edition_id: EditionNumber, reserve_price: String, duration: u64) {
+        self.not_migrating();
+        assert!(!self.paused, "{}", PAUSED_ERR);
+        self.not_token_paused(token_id);
+        self.only_token_owner(token_id, edition_id);
+        assert!(duration > 0 && duration <= MAX_AUCTION_DURATION, "{}", "INVALID AUCTION DURATION");
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        match self.edition_states.get(&idx).unwrap() {
+            EditionState::AVAILABLE => {}
+            _ => env::panic(b"EDITION MUST BE AVAILABLE TO START AN AUCTION"),
+        }
+        self._check_auction_allowed(token_id, env::block_timestamp() + duration);
+        self._check_min_hold(token_id, idx, &env::predecessor_account_id());
+        let auction = Auction {
+            seller: env::predecessor_account_id(),
+            reserve: parse_balance(&reserve_price),
+            end: env::block_timestamp() + duration,
+            highest_bid: 0,
+            highest_bidder: None,
+        };
+        self.edition_states.insert(&idx, &EditionState::LOCKED);
+        self.auctions.insert(&idx, &auction);
+        logger::auction_started(token_id, edition_id, &auction);
+    }
+
+    #[payable]
+    pub fn auction_bid(&mut self, token_id: TokenId, edition_id: EditionNumber) {
+        self.not_migrating();
+        assert!(!self.paused, "{}", PAUSED_ERR);
+        self.not_token_paused(token_id);
+        self.not_denied(&env::predecessor_account_id());
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        let mut auction = self.auctions.get(&idx).unwrap_or_else(|| env::panic(b"NO AUCTION FOR THIS EDITION"));
+        assert!(env::block_timestamp() < auction.end, "{}", "AUCTION HAS ENDED");
+        self._check_trade_window(token_id);
+        assert!(auction.seller != env::predecessor_account_id(), "{}", "YOU CANNOT BID ON YOUR OWN TOKEN");
+        assert!(env::attached_deposit() >= auction.reserve && env::attached_deposit() > auction.highest_bid, "{}", "BID TOO LOW");
+        if let Some(outbid) = auction.highest_bidder.take() {
+            let fee = auction.highest_bid * u128::from(self.outbid_fee_bps) / 10_000;
+            if fee > 0 {
+                Promise::new(self.fee_receiver.clone()).transfer(fee);
+                logger::near_transfer(self.fee_receiver.clone(), fee, TransferReason::FEE, env::block_timestamp());
+            }
+            Promise::new(outbid).transfer(auction.highest_bid - fee);
+        }
+        auction.highest_bid = env::attached_deposit();
+        auction.highest_bidder = Some(env::predecessor_account_id());
+        self.auctions.insert(&idx, &auction);
+        logger::auction_bid(token_id, edition_id, &auction);
+    }
+
+    /// closes an ended auction, callable by anyone. the highest bidder gets the edition and the sale is paid out,
+    /// without bids the edition simply returns to AVAILABLE.
+    pub fn settle_auction(&mut self, token_id: TokenId, edition_id: EditionNumber) {
+        self.not_migrating();
+        let token = self.tokens.get(&token_id).unwrap();
+        let idx = token.edition_index + edition_id;
+        let auction = self.auctions.get(&idx).unwrap_or_else(|| env::panic(b"NO AUCTION FOR THIS EDITION"));
+        assert!(env::block_timestamp() >= auction.end, "{}", "AUCTION HAS NOT ENDED");
+        match auction.highest_bidder.clone() {
+            Some(winner) => {
+                let shares = self.edition_shares.remove(&idx);
+                // the auction is removed only after the transfer, which checks it to release the lock
+                self._internal_transfer(auction.seller.clone(), winner.clone(), token_id, edition_id, idx, REASON_AUCTION);
+                self.last_sales.insert(&idx, &auction.highest_bid);
+                self._add_collector_volume(winner.clone(), auction.highest_bid);
+                self._record_collection_activity(token_id, edition_id, EVENT_AUCTION_SETTLED, auction.highest_bid, auction.seller.clone(), winner.clone());
+                self._distribute_sale(token.metadata, edition_id, auction.seller.clone(), winner, auction.highest_bid, shares);
+            }
+            None => {
+                self.edition_states.insert(&idx, &EditionState::AVAILABLE);
+            }
+        }
+        self.auctions.remove(&idx);
+        logger::auction_settled(token_id, edition_id, &auction);
+    }
+
+    /// seller can call off an auction as long as nobody has bid yet
+    pub fn cancel_auction(&mut self, token_id: TokenId, edition_id: EditionNumber) {
+        self.not_migrating();
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        let auction = self.auctions.get(&idx).unwrap_or_else(|| env::panic(b"NO AUCTION FOR THIS EDITION"));
+        assert_eq!(auction.seller, env::predecessor_account_id(), "{}", ONLY_TOKEN_OWNER);
+        assert!(auction.highest_bidder.is_none(), "{}", "AUCTION ALREADY HAS BIDS");
+        self.auctions.remove(&idx);
+        self.edition_states.insert(&idx, &EditionState::AVAILABLE);
+        logger::auction_cancelled(token_id, edition_id, &auction);
+    }
+
+    /// the winner of an auction can't be approved up front, so tokens requiring resale approval are only auctioned
+    /// by their creator. the auction has to start and end within the collection's trade window.
+    fn _check_auction_allowed(&self, token_id: TokenId, end: u64) {
+        self._check_trade_window(token_id);
+        let md = self.metadata.get(&self.tokens.get(&token_id).unwrap().metadata).unwrap();
+        assert!(self.collections.get(&md.collection_id).is_none_or(|collection| collection.trade_end.is_none_or(|trade_end| end <= trade_end)), "{}", TRADE_WINDOW_CLOSED);
+        if self.resale_approval_tokens.contains(&token_id) {
+            assert_eq!(md.creator, env::predecessor_account_id(), "{}", "RESALES OF THIS TOKEN NEED APPROVAL AND CAN'T BE AUCTIONED");
+        }
+    }
+
+    pub fn get_auction(&self, token_id: TokenId, edition_id: EditionNumber) -> Option<Auction> {
+        self.auctions.get(&(self.tokens.get(&token_id).unwrap().edition_index + edition_id))
+    }
+
+    /// locks an available edition into a sealed-bid auction. bidders commit sha256("bidder:amount:salt") with a deposit
+    /// covering their bid during `commit_duration`, then reveal it during `reveal_duration`.
+    /// losing reveals are refunded right away, unrevealed deposits are forfeited to the fee receiver on settlement.
+    pub fn start_sealed_auction(&mut self, token_id: TokenId, edition_id: EditionNumber, reserve_price: String, commit_duration: u64, reveal_duration: u64) {
+        self.not_migrating();
+        assert!(!self.paused, "{}", PAUSED_ERR);
+        self.not_token_paused(token_id);
+        self.only_token_owner(token_id, edition_id);
+        assert!(commit_duration > 0 && reveal_duration > 0 && commit_duration + reveal_duration <= MAX_AUCTION_DURATION, "{}", "INVALID AUCTION DURATION");
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        match self.edition_states.get(&idx).unwrap() {
+            EditionState::AVAILABLE => {}
+            _ => env::panic(b"EDITION MUST BE AVAILABLE TO START AN AUCTION"),
+        }
+        self._check_auction_allowed(token_id, env::block_timestamp() + commit_duration + reveal_duration);
+        self._check_min_hold(token_id, idx, &env::predecessor_account_id());
+        let commit_end = env::block_timestamp() + commit_duration;
+        let auction = SealedAuction {
+            seller: env::predecessor_account_id(),
+            reserve: parse_balance(&reserve_price),
+            commit_end,
+            reveal_end: commit_end + reveal_duration,
+            highest_bid: 0,
+            highest_bidder: None,
+            unrevealed: 0,
+        };
+        self.edition_states.insert(&idx, &EditionState::LOCKED);
+        self.sealed_auctions.insert(&idx, &auction);
+        logger::sealed_auction(token_id, edition_id, "insert", "SealedAuctionStarted", &auction);
+    }
+
+    /// the attached deposit must cover the bid that is revealed later, overpaying hides the bid and is refunded on reveal
+    #[payable]
+    pub fn commit_bid(&mut self, token_id: TokenId, edition_id: EditionNumber, hash: Base64VecU8) {
+        self.not_migrating();
+        self.not_denied(&env::predecessor_account_id());
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        let mut auction = self.sealed_auctions.get(&idx).unwrap_or_else(|| env::panic(b"NO SEALED AUCTION FOR THIS EDITION"));
+        assert!(env::block_timestamp() < auction.commit_end, "{}", "COMMIT PHASE HAS ENDED");
+        self._check_trade_window(token_id);
+        assert!(auction.seller != env::predecessor_account_id(), "{}", "YOU CANNOT BID ON YOUR OWN TOKEN");
+        assert!(env::attached_deposit() >= auction.reserve && env::attached_deposit() > 0, "{}", "BID TOO LOW");
+        let key = self.gen_allowance_key(idx, &env::predecessor_account_id());
+        assert!(!self.sealed_commits.contains_key(&key), "{}", "BID ALREADY COMMITTED");
+        self.sealed_commits.insert(&key, &SealedCommit { hash: hash.0, deposit: env::attached_deposit() });
+        auction.unrevealed += env::attached_deposit();
+        self.sealed_auctions.insert(&idx, &auction);
+    }
+
+    pub fn reveal_bid(&mut self, token_id: TokenId, edition_id: EditionNumber, amount: String, salt: String) {
+        self.not_migrating();
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        let mut auction = self.sealed_auctions.get(&idx).unwrap_or_else(|| env::panic(b"NO SEALED AUCTION FOR THIS EDITION"));
+        assert!(env::block_timestamp() >= auction.commit_end && env::block_timestamp() < auction.reveal_end, "{}", "NOT IN REVEAL PHASE");
+        let bidder = env::predecessor_account_id();
+        let key = self.gen_allowance_key(idx, &bidder);
+        let commit = self.sealed_commits.remove(&key).unwrap_or_else(|| env::panic(b"NO COMMITTED BID"));
+        assert!(env::sha256(format!("{}:{}:{}", bidder, amount, salt).as_bytes()) == commit.hash, "{}", "BID DOES NOT MATCH COMMIT");
+        let amount = parse_balance(&amount);
+        assert!(amount <= commit.deposit, "{}", "BID EXCEEDS DEPOSIT");
+        auction.unrevealed -= commit.deposit;
+        if amount >= auction.reserve && amount > auction.highest_bid {
+            if let Some(outbid) = auction.highest_bidder.take() {
+                Promise::new(outbid).transfer(auction.highest_bid);
+            }
+            if commit.deposit > amount {
+                Promise::new(bidder.clone()).transfer(commit.deposit - amount);
+            }
+            auction.highest_bid = amount;
+            auction.highest_bidder = Some(bidder);
+        } else {
+            Promise::new(bidder).transfer(commit.deposit);
+        }
+        self.sealed_auctions.insert(&idx, &auction);
+        logger::sealed_auction(token_id, edition_id, "update", "SealedAuctionReveal", &auction);
+    }
+
+    /// closes a sealed auction after its reveal phase, callable by anyone. pays out like settle_auction.
+    pub fn settle_sealed_auction(&mut self, token_id: TokenId, edition_id: EditionNumber) {
+        self.not_migrating();
+        let token = self.tokens.get(&token_id).unwrap();
+        let idx = token.edition_index + edition_id;
+        let auction = self.sealed_auctions.get(&idx).unwrap_or_else(|| env::panic(b"NO SEALED AUCTION FOR THIS EDITION"));
+        assert!(env::block_timestamp() >= auction.reveal_end, "{}", "AUCTION HAS NOT ENDED");
+        if auction.unrevealed > 0 {
+            Promise::new(self.fee_receiver.clone()).transfer(auction.unrevealed);
+            logger::near_transfer(self.fee_receiver.clone(), auction.unrevealed, TransferReason::FEE, env::block_timestamp());
+        }
+        match auction.highest_bidder.clone() {
+            Some(winner) => {
+                let shares = self.edition_shares.remove(&idx);
+                // the auction is removed only after the transfer, which checks it to release the lock
+                self._internal_transfer(auction.seller.clone(), winner.clone(), token_id, edition_id, idx, REASON_AUCTION);
+                self.last_sales.insert(&idx, &auction.highest_bid);
+                self._add_collector_volume(winner.clone(), auction.highest_bid);
+                self._record_collection_activity(token_id, edition_id, EVENT_AUCTION_SETTLED, auction.highest_bid, auction.seller.clone(), winner.clone());
+                self._distribute_sale(token.metadata, edition_id, auction.seller.clone(), winner, auction.highest_bid, shares);
+            }
+            None => {
+                self.edition_states.insert(&idx, &EditionState::AVAILABLE);
+            }
+        }
+        self.sealed_auctions.remove(&idx);
+        logger::sealed_auction(token_id, edition_id, "delete", "SealedAuctionSettled", &auction);
+    }
+
+    /// emergency escape hatch for stuck LOCKED editions, only during an emergency stop. drops any auction or claim
+    /// reservation on the edition: the highest bidder is refunded, unrevealed sealed-bid deposits can't be enumerated
+    /// and are forfeited to the fee receiver like on settlement.
+    pub fn force_unlock_edition(&mut self, token_id: TokenId, edition_id: EditionNumber) {
+        self.not_migrating();
+        self.only_owner();
+        assert!(self.emergency_stop, "{}", "SET THE EMERGENCY STOP BEFORE FORCE UNLOCKING");
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        match self.edition_states.get(&idx).unwrap() {
+            EditionState::LOCKED => {}
+            _ => env::panic(b"EDITION IS NOT LOCKED"),
+        }
+        if let Some(auction) = self.auctions.remove(&idx) {
+            if let Some(bidder) = auction.highest_bidder.clone() {
+                Promise::new(bidder).transfer(auction.highest_bid);
+            }
+            logger::auction_cancelled(token_id, edition_id, &auction);
+        }
+        if let Some(auction) = self.sealed_auctions.remove(&idx) {
+            if let Some(bidder) = auction.highest_bidder.clone() {
+                Promise::new(bidder).transfer(auction.highest_bid);
+            }
+            if auction.unrevealed > 0 {
+                Promise::new(self.fee_receiver.clone()).transfer(auction.unrevealed);
+                logger::near_transfer(self.fee_receiver.clone(), auction.unrevealed, TransferReason::FEE, env::block_timestamp());
+            }
+            logger::sealed_auction(token_id, edition_id, "delete", "SealedAuctionCancelled", &auction);
+        }
+        self.claimables.remove(&idx);
+        if self.allowlist_claimables.remove(&idx).is_some() {
+            self._dequeue_allowlist_claimable(token_id, idx);
+        }
+        self.edition_states.insert(&idx, &EditionState::AVAILABLE);
+        logger::force_unlock(token_id, edition_id, idx, env::predecessor_account_id());
+    }
+
+    pub fn get_sealed_auction(&self, token_id: TokenId, edition_id: EditionNumber) -> Option<SealedAuction> {
+        self.sealed_auctions.get(&(self.tokens.get(&token_id).unwrap().edition_index + edition_id))
+    }
+
+    /// bids `amount`, which costs it plus the edition storage fee and refunds anything attached above. without `amount`
+    /// the attached deposit minus the storage fee is the bid, strict deposits need it. with `expires_at` (ns timestamp)
+    /// the bid can't be accepted after it and anyone can refund it to the bidder with reclaim_expired_offer.
+    #[payable]
+    pub fn offer(&mut self, token_id: TokenId, edition_id: EditionNumber, expires_at: Option<u64>, amount: Option<String>) {
+        self.not_migrating();
+        if let Some(expires_at) = expires_at {
+            assert!(expires_at > env::block_timestamp(), "{}", "EXPIRY MUST BE IN THE FUTURE");
+        }
+        let deposit = match amount {
+            Some(amount) => parse_balance(&amount) + self.edition_storage_fee,
+            None => {
+                assert!(!self.strict_deposits, "{}", "STRICT DEPOSITS NEED THE OFFERED AMOUNT");
+                env::attached_deposit()
+            }
+        };
+        self._check_deposit(deposit);
+        self._offer(token_id, edition_id, deposit, expires_at.unwrap_or(0));
+        if env::attached_deposit() > deposit {
+            Promise::new(env::predecessor_account_id()).transfer(env::attached_deposit() - deposit);
+        }
+    }
+
+    /// bids `percent` of the current listing price. the deposit must cover that bid plus the offer storage fee,
+    /// anything attached above it is refunded so the recorded bid is exactly the requested share of the ask.
+    #[payable]
+    pub fn offer_percent(&mut self, token_id: TokenId, edition_id: EditionNumber, percent: u16) {
+        self.not_migrating();
+        assert!(percent > 0 && percent <= 100, "{}", "PERCENT MUST BE BETWEEN 1 AND 100");
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        let price = self.marketplace.get(&idx).unwrap_or_else(|| env::panic(b"EDITION IS NOT LISTED"));
+        let required = price * u128::from(percent) / 100 + self.edition_storage_fee;
+        assert!(env::attached_deposit() >= required, "{} {}", "DEPOSIT NOT ENOUGH", required);
+        self._offer(token_id, edition_id, required, 0);
+        if env::attached_deposit() > required {
+            Promise::new(env::predecessor_account_id()).transfer(env::attached_deposit() - required);
+        }
+    }
+
+    /// records a bid for `deposit`. the edition storage fee is reserved out of it and the rest is the bid value.
+    fn _offer(&mut self, token_id: TokenId, edition_id: EditionNumber, deposit: Balance, expires_at: u64) {
+        assert!(!self.paused, "{}", PAUSED_ERR);
+        self.not_denied(&env::predecessor_account_id());
+        self.not_token_paused(token_id);
+        self._check_trade_window(token_id);
+        let token = self.tokens.get(&token_id).unwrap();
+        let edition = self.editions.get(&(token.edition_index + edition_id)).unwrap();
+        // only the edition storage fee is reserved out of the deposit, anything above it is the bid
+        assert!(deposit > self.edition_storage_fee, "{}", "NOTHING DEPOSITED");
+        assert!(edition.edition_owner != env::predecessor_account_id(), "YOU CANNOT BID ON YOUR OWN TOKEN");
+        let tok_x_edition: String = self.gen_token_x_edition(token_id, edition_id);
+        let bid: Bid = Bid {
+            bidder: env::predecessor_account_id(),
+            amount: deposit - self.edition_storage_fee,
+            storage_fee: self.edition_storage_fee,
+            date: env::block_timestamp().to_string(),
+            executed: false,
+            expires_at,
+            cancelled: false,
+        };
+        let mut current_offers = match self.offers.get(&tok_x_edition) {
+            Some(offers) => offers,
+            None => {
+                self.offer_lists += 1;
+                Vector::new(format!("ov{}", self.offer_lists).into_bytes())
+            }
+        };
+        // one active bid per bidder and edition: a new offer replaces the bidder's previous one, which is refunded
+        // like a cancelled offer, so raising or lowering a bid is just offering again
+        if let Some(previous) = self._active_bid_idx(&bid.bidder, token_id, edition_id, &current_offers) {
+            let old = current_offers.get(previous).unwrap();
+            self._refund_bid(token_id, edition_id, &mut current_offers, previous, old);
+        }
+        current_offers.push(&bid);
+        self._index_bid(&bid.bidder, (token_id, edition_id, current_offers.len() - 1), true);
+
+
+        logger::new_offer(bid.clone(), current_offers.len() - 1, token_id, edition_id);
+        self.offers.insert(&tok_x_edition, &current_offers);
+
+        logger::insert_activity(token_id, edition_id, EVENT_OFFER.to_string(), bid.amount.to_string(), edition.edition_owner);
+    }
+
+    pub fn accept_offer(&mut self, token_id: TokenId, edition_id: EditionNumber, idx: u64) {
+        self.not_migrating();
+        // accept, /remove other offers/, transfer money, transfer nft
+        self._check_trade_window(token_id);
+        let tokxedition = self.gen_token_x_edition(token_id, edition_id);
+        let token = self.tokens.get(&token_id).unwrap();
+        let edition_idx = token.edition_index + edition_id;
+        let edition = self.editions.get(&edition_idx).unwrap();
+        assert!(edition.edition_owner == env::predecessor_account_id(), "{}", ONLY_TOKEN_OWNER);
+        let old_owner = edition.edition_owner.clone();
+        let mut offers = self.offers.get(&tokxedition).unwrap();
+        let mut to_be_accepted = offers.get(idx).unwrap();
+        assert!(!to_be_accepted.executed, "{}", "OFFER IS CANCELLED OR ACCEPTED.");
+        assert!(!self._is_expired(&to_be_accepted), "{}", "OFFER HAS EXPIRED");
+        self._use_resale_approval(token.metadata, edition_idx, &old_owner, &to_be_accepted.bidder);
+        self._check_min_hold(token_id, edition_idx, &old_owner);
+        let shares = self.edition_shares.remove(&edition_idx);
+        // delist explicitly so an offer-based sale never leaves a listing behind
+        if self.marketplace.remove(&edition_idx).is_some() {
+            self.listings.remove(&edition_idx);
+            logger::marketplace_remove(edition.clone(), edition_idx);
+            logger::insert_activity(token_id, edition_id, EVENT_MARKET_DELETE.to_string(), "0".to_string(), old_owner.clone());
+        }
+        self._internal_transfer(env::predecessor_account_id(), to_be_accepted.bidder.clone(), token_id, edition_id, edition_idx, REASON_OFFER_ACCEPT);
+
+        self.edition_states.insert(&edition_idx, &EditionState::AVAILABLE);
+        // only the bid value is paid out, the storage reservation goes back to the bidder
+        let bid_value = to_be_accepted.amount;
+        self._refund_storage_fee(&mut to_be_accepted);
+        // send money to their owners
+        self._distribute_sale(token.metadata, edition_id, old_owner.clone(), to_be_accepted.bidder.clone(), bid_value, shares);
+        logger::accept_offer(bid_value, env::predecessor_account_id(), idx, token_id, edition_id, env::block_timestamp());
+        logger::transfer_edition(edition.clone(), edition_idx, to_be_accepted.bidder.clone());
+        logger::insert_activity(token_id, edition_id, EVENT_ACCEPT_OFFER.to_string(), bid_value.to_string(), to_be_accepted.bidder.clone());
+        self.last_sales.insert(&edition_idx, &bid_value);
+        self._add_collector_volume(to_be_accepted.bidder.clone(), bid_value);
+        self._index_bid(&to_be_accepted.bidder, (token_id, edition_id, idx), false);
+        self._record_collection_activity(token_id, edition_id, EVENT_ACCEPT_OFFER, bid_value, old_owner, to_be_accepted.bidder.clone());
+        to_be_accepted.executed = true;
+        offers.replace(idx, &to_be_accepted);
+        self.offers.insert(&tokxedition, &offers);
+    }
+
+    pub fn cancel_offer(&mut self, token_id: TokenId, edition_id: EditionNumber, idx: u64) {
+        self.not_migrating();
+        let tokxedition = self.gen_token_x_edition(token_id, edition_id);
+        let mut offer = self.offers.get(&tokxedition).unwrap();
+        let mut to_be_cancelled = offer.get(idx).unwrap();
+        assert!(!to_be_cancelled.executed, "{}", "OFFER IS CANCELLED OR ACCEPTED.");
+        assert!(to_be_cancelled.bidder == env::predecessor_account_id(), "{}", "ONLY OFFER OWNER CAN CANCEL");
+
+        // the bid and its storage reservation both go back
+        if to_be_cancelled.amount > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(to_be_cancelled.amount);
+        }
+        self._refund_storage_fee(&mut to_be_cancelled);
+        self._index_bid(&to_be_cancelled.bidder, (token_id, edition_id, idx), false);
+        logger::execute_offer(to_be_cancelled.clone(), idx, token_id, edition_id);
+        logger::insert_activity(token_id, edition_id, EVENT_CANCEL_OFFER.to_string(), to_be_cancelled.amount.to_string(), to_be_cancelled.bidder.clone());
+        to_be_cancelled.executed = true;
+        to_be_cancelled.cancelled = true;
         offer.replace(idx, &to_be_cancelled);
+        self.offers.insert(&tokxedition, &offer);
+    }
+
+    /// bids on any edition of a collection, any owner of one of its editions can accept it.
+    /// like an edition offer, the edition storage fee is reserved out of the deposit. at most MAX_COLLECTION_OFFERS
+    /// can be active per collection.
+    #[payable]
+    pub fn offer_collection(&mut self, collection_id: CollectionId) {
+        self.not_migrating();
+        assert!(!self.paused, "{}", PAUSED_ERR);
+        self.not_denied(&env::predecessor_account_id());
+        assert!(self.collections.get(&collection_id).is_some(), "{}", "COLLECTION DOES NOT EXIST");
+        let deposit = env::attached_deposit();
+        assert!(deposit > self.edition_storage_fee, "{}", "NOTHING DEPOSITED");
+        let active = self.active_collection_offers.get(&collection_id).unwrap_or(0);
+        assert!(active < MAX_COLLECTION_OFFERS, "{}", "TOO MANY ACTIVE COLLECTION OFFERS");
+        let bid = Bid {
+            bidder: env::predecessor_account_id(),
+            amount: deposit - self.edition_storage_fee,
+            storage_fee: self.edition_storage_fee,
+            date: env::block_timestamp().to_string(),
+            executed: false,
+            expires_at: 0,
+            cancelled: false,
+        };
+        let mut offers = self.collection_offers.get(&collection_id)
+            .unwrap_or_else(|| Vector::new(format!("cov{}", collection_id).into_bytes()));
+        offers.push(&bid);
+        self.collection_offers.insert(&collection_id, &offers);
+        self.active_collection_offers.insert(&collection_id, &(active + 1));
+        logger::collection_offer(collection_id, offers.len() - 1, bid, "insert");
+    }
+    /// sells an edition of the collection to a collection-wide bid, paid out like an accepted edition offer
+    pub fn accept_collection_offer(&mut self, collection_id: CollectionId, token_id: TokenId, edition_id: EditionNumber, idx: u64) {
+        self.not_migrating();
+        assert!(!self.paused, "{}", PAUSED_ERR);
+        self._check_trade_window(token_id);
+        let token = self.tokens.get(&token_id).unwrap();
+        assert_eq!(self.metadata.get(&token.metadata).unwrap().collection_id, collection_id, "{}", "TOKEN IS NOT PART OF THIS COLLECTION");
+        let edition_idx = token.edition_index + edition_id;
+        let edition = self.editions.get(&edition_idx).unwrap();
+        let seller = env::predecessor_account_id();
+        assert!(edition.edition_owner == seller, "{}", ONLY_TOKEN_OWNER);
+        self.not_denied(&seller);
+        let mut offers = self.collection_offers.get(&collection_id).unwrap();
+        let mut bid = offers.get(idx).unwrap();
+        assert!(!bid.executed, "{}", "OFFER IS CANCELLED OR ACCEPTED.");
+        assert!(!self._is_expired(&bid), "{}", "OFFER HAS EXPIRED");
+        assert!(bid.bidder != seller, "{}", "CANNOT BUY YOUR OWN TOKEN");
+        self.not_denied(&bid.bidder);
+        self._use_resale_approval(token.metadata, edition_idx, &seller, &bid.bidder);
+        self._check_min_hold(token_id, edition_idx, &seller);
+        let shares = self.edition_shares.remove(&edition_idx);
+        if self.marketplace.remove(&edition_idx).is_some() {
+            self.listings.remove(&edition_idx);
+            logger::marketplace_remove(edition, edition_idx);
+            logger::insert_activity(token_id, edition_id, EVENT_MARKET_DELETE.to_string(), "0".to_string(), seller.clone());
+        }
+        self._internal_transfer(seller.clone(), bid.bidder.clone(), token_id, edition_id, edition_idx, REASON_OFFER_ACCEPT);
+        self._distribute_sale(token.metadata, edition_id, seller.clone(), bid.bidder.clone(), bid.amount, shares);
+        self._refund_storage_fee(&mut bid);
+        logger::insert_activity(token_id, edition_id, EVENT_ACCEPT_OFFER.to_string(), bid.amount.to_string(), bid.bidder.clone());
+        self.last_sales.insert(&edition_idx, &bid.amount);
+        self._add_collector_volume(bid.bidder.clone(), bid.amount);
+        self._record_collection_activity(token_id, edition_id, EVENT_ACCEPT_OFFER, bid.amount, seller, bid.bidder.clone());
+        bid.executed = true;
+        logger::collection_offer(collection_id, idx, bid.clone(), "update");
+        offers.replace(idx, &bid);
+        self.collection_offers.insert(&collection_id, &offers);
+        self._collection_offer_closed(collection_id);
+    }
+    pub fn cancel_collection_offer(&mut self, collection_id: CollectionId, idx: u64) {
+        self.not_migrating();
+        let mut offers = self.collection_offers.get(&collection_id).unwrap();
+        let mut bid = offers.get(idx).unwrap();
+        assert!(!bid.executed, "{}", "OFFER IS CANCELLED OR ACCEPTED.");
+        assert!(bid.bidder == env::predecessor_account_id(), "{}", "ONLY OFFER OWNER CAN CANCEL");
+        // as with edition offers the bid and its storage reservation both go back
+        if bid.amount > 0 {
+            Promise::new(bid.bidder.clone()).transfer(bid.amount);
+        }
+        self._refund_storage_fee(&mut bid);
+        bid.executed = true;
+        bid.cancelled = true;
+        logger::collection_offer(collection_id, idx, bid.clone(), "update");
+        offers.replace(idx, &bid);
+        self.collection_offers.insert(&collection_id, &offers);
+        self._collection_offer_closed(collection_id);
+    }
+    pub fn get_collection_offers(&self, collection_id: CollectionId) -> Vec<Bid> {
+        self.collection_offers.get(&collection_id).map(|offers| offers.to_vec()).unwrap_or_default()
+    }
+    fn _collection_offer_closed(&mut self, collection_id: CollectionId) {
+        let active = self.active_collection_offers.get(&collection_id).unwrap_or(0);
+        self.active_collection_offers.insert(&collection_id, &active.saturating_sub(1));
+    }
+
+    /// transfers an edition and refunds up to MAX_BATCH of its active offers so the new owner does not inherit them.
+    /// returns how many active offers are left, clear_offers refunds the rest.
+    pub fn transfer_and_clear_offers(&mut self, to: AccountId, token_id: TokenId, edition_id: EditionNumber) -> u64 {
+        self.not_migrating();
+        self.transfer(to, token_id, edition_id);
+        self._refund_offers(token_id, edition_id)
+    }
+    /// refunds up to MAX_BATCH active offers on an edition, callable by its owner. returns how many are left.
+    pub fn clear_offers(&mut self, token_id: TokenId, edition_id: EditionNumber) -> u64 {
+        self.not_migrating();
+        self.only_token_owner(token_id, edition_id);
+        self._refund_offers(token_id, edition_id)
+    }
+    /// deletes an edition's offer list once every bid in it is accepted or cancelled, callable by anyone.
+    /// closed bids have had their storage fee refunded already, storage fees still held for older bid records
+    /// pay the caller up to MAX_PRUNE_REWARD and the rest goes to the collected fees. returns the amount paid.
+    pub fn prune_offers(&mut self, token_id: TokenId, edition_id: EditionNumber) -> String {
+        self.not_migrating();
+        let tokxedition = self.gen_token_x_edition(token_id, edition_id);
+        let mut offers = self.offers.get(&tokxedition).unwrap_or_else(|| env::panic(b"NO OFFERS TO PRUNE"));
+        assert!(offers.iter().all(|bid| bid.executed), "{}", "EDITION STILL HAS ACTIVE OFFERS");
+        let freed: Balance = offers.iter().map(|bid| bid.storage_fee).sum();
+        offers.clear();
+        self.offers.remove(&tokxedition);
+        let reward = freed.min(MAX_PRUNE_REWARD);
+        self.collected_fees += freed - reward;
+        if reward > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(reward);
+        }
+        reward.to_string()
+    }
+    /// refunds a bid whose expiry has passed to its bidder, callable by anyone
+    pub fn reclaim_expired_offer(&mut self, token_id: TokenId, edition_id: EditionNumber, idx: u64) {
+        self.not_migrating();
+        let tokxedition = self.gen_token_x_edition(token_id, edition_id);
+        let mut offers = self.offers.get(&tokxedition).unwrap();
+        let bid = offers.get(idx).unwrap();
+        assert!(!bid.executed, "{}", "OFFER IS CANCELLED OR ACCEPTED.");
+        assert!(self._is_expired(&bid), "{}", "OFFER HAS NOT EXPIRED");
+        self._refund_bid(token_id, edition_id, &mut offers, idx, bid);
+        self.offers.insert(&tokxedition, &offers);
+    }
+    fn _is_expired(&self, bid: &Bid) -> bool {
+        bid.expires_at > 0 && env::block_timestamp() > bid.expires_at
+    }
+    fn _refund_offers(&mut self, token_id: TokenId, edition_id: EditionNumber) -> u64 {
+        let tokxedition = self.gen_token_x_edition(token_id, edition_id);
+        let mut offers = match self.offers.get(&tokxedition) {
+            Some(offers) => offers,
+            None => return 0,
+        };
+        let mut refunded = 0;
+        let mut remaining = 0;
+        for idx in 0..offers.len() {
+            let bid = offers.get(idx).unwrap();
+            if bid.executed {
+                continue;
+            }
+            if refunded == MAX_BATCH {
+                remaining += 1;
+                continue;
+            }
+            self._refund_bid(token_id, edition_id, &mut offers, idx, bid);
+            refunded += 1;
+        }
+        self.offers.insert(&tokxedition, &offers);
+        remaining
+    }
+    /// refunds an active bid with its storage fee and marks it cancelled in its offer list
+    fn _refund_bid(&mut self, token_id: TokenId, edition_id: EditionNumber, offers: &mut Vector<Bid>, idx: u64, mut bid: Bid) {
+        if bid.amount > 0 {
+            Promise::new(bid.bidder.clone()).transfer(bid.amount);
+        }
+        self._refund_storage_fee(&mut bid);
+        logger::execute_offer(bid.clone(), idx, token_id, edition_id);
+        logger::insert_activity(token_id, edition_id, EVENT_CANCEL_OFFER.to_string(), bid.amount.to_string(), bid.bidder.clone());
+        self._index_bid(&bid.bidder, (token_id, edition_id, idx), false);
+        bid.executed = true;
+        bid.cancelled = true;
+        offers.replace(idx, &bid);
+    }
+    /// returns the storage fee reserved for a bid to its bidder, the record keeps 0 so it is never paid twice
+    fn _refund_storage_fee(&self, bid: &mut Bid) {
+        if bid.storage_fee > 0 {
+            Promise::new(bid.bidder.clone()).transfer(bid.storage_fee);
+            bid.storage_fee = 0;
+        }
+    }
+    fn _active_bid_idx(&self, bidder: &AccountId, token_id: TokenId, edition_id: EditionNumber, offers: &Vector<Bid>) -> Option<u64> {
+        self.bidder_offers.get(bidder)?
+            .iter()
+            .filter(|(token, edition, _)| *token == token_id && *edition == edition_id)
+            .map(|(_, _, idx)| idx)
+            .find(|idx| offers.get(*idx).is_some_and(|bid| !bid.executed && &bid.bidder == bidder))
+    }
+    fn _index_bid(&mut self, bidder: &AccountId, entry: (TokenId, EditionNumber, u64), active: bool) {
+        let mut bids = self.bidder_offers.get(bidder).unwrap_or_else(|| UnorderedSet::new(format!("bo{}", bidder).into_bytes()));
+        if active {
+            bids.insert(&entry);
+        } else {
+            bids.remove(&entry);
+        }
+        self.bidder_offers.insert(bidder, &bids);
+    }
+    /// everything the contract holds for `account`: its active offers and accrued royalties.
+    /// payouts are sent right away and never retried, so there are no failed payouts to hold.
+    pub fn claimable_of(&self, account: AccountId) -> ClaimableBreakdown {
+        let offers: Balance = self.bidder_offers.get(&account)
+            .map(|bids| bids.iter()
+                .filter_map(|(token_id, edition_id, idx)| self.offers.get(&self.gen_token_x_edition(token_id, edition_id))?.get(idx))
+                .filter(|bid| !bid.executed && bid.bidder == account)
+                .map(|bid| bid.amount + bid.storage_fee)
+                .sum())
+            .unwrap_or(0);
+        let earnings = self.creator_balance.get(&account).unwrap_or(0);
+        ClaimableBreakdown {
+            offers: offers.to_string(),
+            earnings: earnings.to_string(),
+            total: (offers + earnings).to_string(),
+        }
+    }
+    /// cancels and refunds up to MAX_BATCH of the caller's active offers and withdraws its accrued royalties.
+    /// returns how many active offers are left for the next call.
+    pub fn claim_all(&mut self) -> u64 {
+        self.not_migrating();
+        let account = env::predecessor_account_id();
+        let bids: Vec<(TokenId, EditionNumber, u64)> = self.bidder_offers.get(&account).map(|bids| bids.to_vec()).unwrap_or_default();
+        for (token_id, edition_id, idx) in bids.iter().take(MAX_BATCH).cloned() {
+            let tokxedition = self.gen_token_x_edition(token_id, edition_id);
+            let mut offers = self.offers.get(&tokxedition).unwrap();
+            match offers.get(idx) {
+                Some(bid) if !bid.executed && bid.bidder == account => self._refund_bid(token_id, edition_id, &mut offers, idx, bid),
+                _ => self._index_bid(&account, (token_id, edition_id, idx), false),
+            }
+            self.offers.insert(&tokxedition, &offers);
+        }
+        if self.creator_balance.get(&account).unwrap_or(0) > 0 {
+            self.withdraw_earnings();
+        }
+        bids.len().saturating_sub(MAX_BATCH) as u64
+    }
+
+    /// pays out a sale: platform fee to fee_receiver, royalty to the creator on secondary sales, the rest to the seller(s)
+    /// a single sale_settled event with the full breakdown is logged next to the per-transfer logs
+    fn _distribute_sale(&mut self, metadata_id: TokenId, edition_id: EditionNumber, seller: AccountId, buyer: AccountId, amount: Balance, shares: Option<Vec<(AccountId, u16)>>) {
+        let md = self.metadata.get(&metadata_id).unwrap();
+        let edition_idx = self.tokens.get(&metadata_id).unwrap().edition_index + edition_id;
+        let nearfolio_fee: u128 = self._platform_fee(self._is_primary_sale(&md, edition_idx, &seller), amount);
+        self.first_sale_done.insert(&edition_idx, &true);
+        let rest = self._checked_payout(amount, nearfolio_fee);
+        Promise::new(self.fee_receiver.clone()).transfer(nearfolio_fee);
+        logger::near_transfer(self.fee_receiver.clone(), nearfolio_fee, TransferReason::FEE, env::block_timestamp());
+        // a royalty prepaid by the seller is pooled with the proceeds, royalties come out of it first
+        // and whatever it doesn't cover is taken from the sale as usual
+        let mut sellers = rest + self._take_prepaid_royalty(metadata_id, edition_id, &seller);
+        let royalties = if md.creator != seller { self._royalty_split(metadata_id, &md, rest) } else { Vec::new() };
+        for (account, royalty_fee) in royalties.clone() {
+            sellers = self._checked_payout(sellers, royalty_fee);
+            if self.accrue_earnings.contains(&account) {
+                let balance = self.creator_balance.get(&account).unwrap_or(0) + royalty_fee;
+                self.creator_balance.insert(&account, &balance);
+                logger::earnings_update(account, balance);
+            } else {
+                Promise::new(account.clone()).transfer(royalty_fee);
+                logger::near_transfer(account, royalty_fee, TransferReason::ROYALTY, env::block_timestamp());
+            }
+        }
+        // what the seller and any co-owners are paid, a prepaid royalty included
+        logger::sale_settled(metadata_id, edition_id, seller.clone(), buyer, amount, nearfolio_fee, royalties, sellers);
+        self._pay_sellers(seller, sellers, shares);
+    }
+    /// escrows the royalty the listed price of an edition will owe, so the buyer of the listing pays only the price
+    /// and the creator's cut is already covered. refunded when the listing is cancelled or the edition burned.
+    #[payable]
+    pub fn prepay_royalty(&mut self, token_id: TokenId, edition_id: EditionNumber) {
+        self.not_migrating();
+        self.only_token_owner(token_id, edition_id);
+        let token = self.tokens.get(&token_id).unwrap();
+        let idx = token.edition_index + edition_id;
+        let price = self.marketplace.get(&idx).unwrap_or_else(|| env::panic(b"EDITION IS NOT LISTED"));
+        assert!(self.royalty_prepayments.get(&idx).is_none_or(|(payer, _)| payer != env::predecessor_account_id()), "{}", "ROYALTY ALREADY PREPAID");
+        // a prepayment left behind by a previous owner goes back to them
+        self._refund_prepaid_royalty(idx);
+        let md = self.metadata.get(&token.metadata).unwrap();
+        let rest = self._checked_payout(price, self._trade_fee_of(price));
+        let due = if md.creator != env::predecessor_account_id() { self._royalty_due(token.metadata, &md, rest) } else { 0 };
+        assert!(due > 0, "{}", "NO ROYALTY DUE");
+        assert!(env::attached_deposit() >= due, "{} {}", "DEPOSIT NOT ENOUGH", due);
+        self.royalty_prepayments.insert(&idx, &(env::predecessor_account_id(), due));
+        if env::attached_deposit() > due {
+            Promise::new(env::predecessor_account_id()).transfer(env::attached_deposit() - due);
+        }
+        logger::royalty_prepayment(token_id, edition_id, env::predecessor_account_id(), due);
+    }
+    pub fn prepaid_royalty(&self, token_id: TokenId, edition_id: EditionNumber) -> String {
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        self.royalty_prepayments.get(&idx).map_or(0, |(_, amount)| amount).to_string()
+    }
+    /// removes the prepaid royalty of a sold edition, only the seller's own prepayment counts towards the sale
+    fn _take_prepaid_royalty(&mut self, token_id: TokenId, edition_id: EditionNumber, seller: &AccountId) -> Balance {
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        match self.royalty_prepayments.get(&idx) {
+            Some((payer, amount)) if &payer == seller => {
+                self.royalty_prepayments.remove(&idx);
+                amount
+            }
+            _ => {
+                self._refund_prepaid_royalty(idx);
+                0
+            }
+        }
+    }
+    fn _refund_prepaid_royalty(&mut self, edition_idx: u64) {
+        if let Some((payer, amount)) = self.royalty_prepayments.remove(&edition_idx) {
+            Promise::new(payer.clone()).transfer(amount);
+            logger::near_transfer(payer, amount, TransferReason::REFUND, env::block_timestamp());
+        }
+    }
+    /// the first sale of an edition by its creator, charged primary_sale_fee_bps instead of the trade fee when that is set
+    fn _is_primary_sale(&self, md: &Metadata, edition_idx: u64, seller: &AccountId) -> bool {
+        &md.creator == seller && !self.first_sale_done.get(&edition_idx).unwrap_or(false)
+    }
+    fn _platform_fee(&self, primary: bool, amount: Balance) -> Balance {
+        if primary && self.primary_sale_fee_bps > 0 {
+            amount * u128::from(self.primary_sale_fee_bps) / 10_000
+        } else {
+            self._trade_fee_of(amount)
+        }
+    }
+    /// regular trade fee on `amount`, rounded down. state that hasn't run migrate_trade_fee still holds a divisor.
+    fn _trade_fee_of(&self, amount: Balance) -> Balance {
+        if self.trade_fee_in_bps {
+            amount * self.trade_fee / 10_000
+        } else {
+            amount.div(self.trade_fee)
+        }
+    }
+    /// whether selling the edition now would be its primary sale
+    pub fn is_primary_sale(&self, token_id: TokenId, edition_id: EditionNumber) -> bool {
+        let token = self.tokens.get(&token_id).unwrap();
+        let idx = token.edition_index + edition_id;
+        let edition = self.editions.get(&idx).unwrap_or_else(|| env::panic(TOKEN_LOCKED.as_bytes()));
+        self._is_primary_sale(&self.metadata.get(&token.metadata).unwrap(), idx, &edition.edition_owner)
+    }
+    /// opt-in curation for creators: once enabled, editions of the token can only be bought or have offers accepted on
+    /// the secondary market by buyers the creator approved for that edition with approve_resale.
+    /// buyers should ask the creator first, unapproved buys and offer acceptances are rejected.
+    pub fn set_require_resale_approval(&mut self, token_id: TokenId, value: bool) {
+        self.not_migrating();
+        let md = self.metadata.get(&self.tokens.get(&token_id).unwrap().metadata).unwrap();
+        assert_eq!(md.creator, env::predecessor_account_id(), "{}", "ONLY THE CREATOR CAN CHANGE RESALE APPROVAL");
+        if value {
+            self.resale_approval_tokens.insert(&token_id);
+        } else {
+            self.resale_approval_tokens.remove(&token_id);
+        }
+    }
+    pub fn requires_resale_approval(&self, token_id: TokenId) -> bool {
+        self.resale_approval_tokens.contains(&token_id)
+    }
+    /// opt-in anti-flip for creators: owners other than the creator can't list, sell or accept offers on an edition
+    /// until `hold_ns` has passed since they acquired it. 0 turns it off.
+    pub fn set_min_hold(&mut self, token_id: TokenId, hold_ns: u64) {
+        self.not_migrating();
+        let md = self.metadata.get(&self.tokens.get(&token_id).unwrap().metadata).unwrap();
+        assert_eq!(md.creator, env::predecessor_account_id(), "{}", "ONLY THE CREATOR CAN CHANGE THE HOLD PERIOD");
+        if hold_ns > 0 {
+            self.min_hold_ns.insert(&token_id, &hold_ns);
+        } else {
+            self.min_hold_ns.remove(&token_id);
+        }
+    }
+    pub fn min_hold_of(&self, token_id: TokenId) -> u64 {
+        self.min_hold_ns.get(&token_id).unwrap_or(0)
+    }
+    /// timestamp from which the current owner may resell the edition
+    pub fn hold_ends_at(&self, token_id: TokenId, edition_id: EditionNumber) -> u64 {
+        let token = self.tokens.get(&token_id).unwrap();
+        self._acquired_at(token.metadata, token.edition_index + edition_id) + self.min_hold_of(token_id)
+    }
+    fn _acquired_at(&self, metadata_id: TokenId, edition_idx: u64) -> u64 {
+        self.acquired_at.get(&edition_idx).unwrap_or_else(|| {
+            u64::from_str(&self.metadata.get(&metadata_id).unwrap().date).unwrap_or(0)
+        })
+    }
+    fn _check_min_hold(&self, token_id: TokenId, edition_idx: u64, owner: &AccountId) {
+        let hold = self.min_hold_of(token_id);
+        if hold == 0 {
+            return;
+        }
+        let metadata_id = self.tokens.get(&token_id).unwrap().metadata;
+        if &self.metadata.get(&metadata_id).unwrap().creator == owner {
+            return;
+        }
+        assert!(env::block_timestamp() >= self._acquired_at(metadata_id, edition_idx) + hold, "{}", HOLD_PERIOD);
+    }
+    /// approves `buyer` for one secondary purchase of an edition, the approval is used up by the sale
+    pub fn approve_resale(&mut self, token_id: TokenId, edition_id: EditionNumber, buyer: AccountId) {
+        self.not_migrating();
+        let token = self.tokens.get(&token_id).unwrap();
+        let md = self.metadata.get(&token.metadata).unwrap();
+        assert_eq!(md.creator, env::predecessor_account_id(), "{}", "ONLY THE CREATOR CAN APPROVE RESALES");
+        self.resale_approvals.insert(&self.gen_allowance_key(token.edition_index + edition_id, &buyer), &true);
+    }
+    pub fn revoke_resale_approval(&mut self, token_id: TokenId, edition_id: EditionNumber, buyer: AccountId) {
+        self.not_migrating();
+        let token = self.tokens.get(&token_id).unwrap();
+        let md = self.metadata.get(&token.metadata).unwrap();
+        assert_eq!(md.creator, env::predecessor_account_id(), "{}", "ONLY THE CREATOR CAN APPROVE RESALES");
+        self.resale_approvals.remove(&self.gen_allowance_key(token.edition_index + edition_id, &buyer));
+    }
+    pub fn is_resale_approved(&self, token_id: TokenId, edition_id: EditionNumber, buyer: AccountId) -> bool {
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        self.resale_approvals.contains_key(&self.gen_allowance_key(idx, &buyer))
+    }
+    fn _use_resale_approval(&mut self, token_id: TokenId, edition_idx: u64, seller: &AccountId, buyer: &AccountId) {
+        if !self.resale_approval_tokens.contains(&token_id) {
+            return;
+        }
+        let md = self.metadata.get(&token_id).unwrap();
+        if &md.creator == seller || &md.creator == buyer {
+            return;
+        }
+        assert!(self.resale_approvals.remove(&self.gen_allowance_key(edition_idx, buyer)).is_some(), "{}", "RESALE TO THIS BUYER IS NOT APPROVED BY THE CREATOR");
+    }
+    /// when enabled, the caller's royalties are credited to an internal balance instead of being sent at every sale
+    pub fn set_accrue_earnings(&mut self, value: bool) {
+        self.not_migrating();
+        if value {
+            self.accrue_earnings.insert(&env::predecessor_account_id());
+        } else {
+            self.accrue_earnings.remove(&env::predecessor_account_id());
+        }
+    }
+    pub fn is_accruing_earnings(&self, account: AccountId) -> bool {
+        self.accrue_earnings.contains(&account)
+    }
+    /// sends all accrued royalties of the caller in one transfer
+    pub fn withdraw_earnings(&mut self) {
+        self.not_migrating();
+        let account = env::predecessor_account_id();
+        let balance = self.creator_balance.remove(&account).unwrap_or(0);
+        assert!(balance > 0, "{}", "NOTHING TO WITHDRAW");
+        Promise::new(account.clone()).transfer(balance);
+        logger::earnings_update(account.clone(), 0);
+        logger::near_transfer(account, balance, TransferReason::ROYALTY, env::block_timestamp());
+    }
+    /// total an account has spent on buys, accepted offers and won auctions, for off-chain collector leaderboards
+    pub fn collector_volume_of(&self, account: AccountId) -> String {
+        self.collector_volume.get(&account).unwrap_or(0).to_string()
+    }
+    /// per-account figures for profile pages in one view call
+    pub fn account_summary(&self, account: AccountId) -> AccountSummary {
+        AccountSummary {
+            editions_owned: self.tokens_by_owner_count(account.clone()),
+            collector_volume: self.collector_volume_of(account.clone()),
+            earnings: self.earnings_of(account.clone()),
+            denied: self.is_denied(account),
+        }
+    }
+    pub fn earnings_of(&self, account: AccountId) -> String {
+        self.creator_balance.get(&account).unwrap_or(0).to_string()
+    }
+    /// total royalty owed on the seller's proceeds `amount`
+    fn _royalty_due(&self, metadata_id: TokenId, md: &Metadata, amount: Balance) -> Balance {
+        self._royalty_split(metadata_id, md, amount).iter().map(|(_, amount)| amount).sum()
+    }
+    /// royalty recipients and their non-zero amounts for the seller's proceeds `amount` on a secondary sale.
+    /// the collection's royalty split the token was minted with, otherwise the creator's royalty in bps, rounded down
+    fn _royalty_split(&self, metadata_id: TokenId, md: &Metadata, amount: Balance) -> Vec<(AccountId, Balance)> {
+        if let Some(royalties) = self.token_royalties.get(&metadata_id) {
+            return royalties
+                .into_iter()
+                .map(|(account, bps)| (account, amount * u128::from(bps) / 10_000))
+                .filter(|(_, amount)| *amount > 0)
+                .collect();
+        }
+        // tokens not covered by migrate_royalties yet still hold a divisor
+        let bps = self.royalty_bps.get(&metadata_id).unwrap_or_else(|| self._royalty_bps(md.royalty));
+        let royalty_fee = amount * u128::from(bps) / 10_000;
+        if royalty_fee > 0 { vec![(md.creator.clone(), royalty_fee)] } else { vec![] }
+    }
+    /// royalties a secondary sale at `price` would pay out, computed after the trade fee like an actual sale
+    pub fn royalty_for(&self, token_id: TokenId, price: String) -> Vec<(AccountId, String)> {
+        let price = parse_balance(&price);
+        let metadata_id = self.tokens.get(&token_id).unwrap().metadata;
+        let md = self.metadata.get(&metadata_id).unwrap();
+        let rest = self._checked_payout(price, self._trade_fee_of(price));
+        self._royalty_split(metadata_id, &md, rest)
+            .into_iter()
+            .map(|(account, amount)| (account, amount.to_string()))
+            .collect()
+    }
+
+    /// subtracts a fee from a payout, panicking instead of wrapping when a misconfigured fee exceeds it
+    fn _checked_payout(&self, amount: Balance, fee: Balance) -> Balance {
+        amount.checked_sub(fee).unwrap_or_else(|| env::panic(PAYOUT_UNDERFLOW.as_bytes()))
+    }
+
+    /// splits a sale between co-owners by their shares, rounding dust goes to the listing owner
+    fn _pay_sellers(&self, seller: AccountId, amount: Balance, shares: Option<Vec<(AccountId, u16)>>) {
+        let mut remaining = amount;
+        for (account, bps) in shares.unwrap_or_default() {
+            let part = amount * u128::from(bps) / 10_000;
+            if part > 0 {
+                remaining -= part;
+                Promise::new(account.clone()).transfer(part);
+                logger::near_transfer(account, part, TransferReason::SALE, env::block_timestamp());
+            }
+        }
+        if remaining > 0 {
+            Promise::new(seller.clone()).transfer(remaining);
+            logger::near_transfer(seller, remaining, TransferReason::SALE, env::block_timestamp());
+        }
+    }
+
+    /// splits a solely owned edition between co-owners. shares are basis points and must add up to 10000.
+    /// the caller stays the edition owner and manages listings, sale proceeds are split by share.
+    /// the edition can't be transferred or burned while fractionalized, a sale buys out all co-owners.
+    pub fn fractionalize(&mut self, token_id: TokenId, edition_id: EditionNumber, shares: Vec<(AccountId, u16)>) {
+        self.not_migrating();
+        assert!(!self.paused, "{}", PAUSED_ERR);
+        self.only_token_owner(token_id, edition_id);
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        assert!(!self.edition_shares.contains_key(&idx), "{}", "EDITION ALREADY FRACTIONALIZED");
+        match self.edition_states.get(&idx).unwrap() {
+            EditionState::LOCKED | EditionState::BURNED => env::panic(TOKEN_LOCKED.as_bytes()),
+            _ => {}
+        }
+        assert!(shares.len() > 1 && shares.len() <= MAX_SHAREHOLDERS, "{}", "INVALID NUMBER OF SHAREHOLDERS");
+        for (account, _) in shares.iter() {
+            assert!(env::is_valid_account_id(account.as_bytes()), "{}", ACC_NOT_VALID);
+        }
+        let total: u32 = shares.iter().map(|(_, bps)| u32::from(*bps)).sum();
+        assert_eq!(total, 10_000, "{}", "SHARES MUST ADD UP TO 10000 BPS");
+        self.edition_shares.insert(&idx, &shares);
+        logger::edition_shares(token_id, edition_id, idx, shares);
+    }
+
+    pub fn shares_of(&self, token_id: TokenId, edition_id: EditionNumber) -> Vec<(AccountId, u16)> {
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        self.edition_shares.get(&idx).unwrap_or_default()
+    }
+
+    /// likes an edition once per account. the small like fee guards against storage growth, it is booked with the
+    /// collected fees and not refunded by unlike. anything attached above it is sent back.
+    #[payable]
+    pub fn like(&mut self, token_id: TokenId, edition_id: EditionNumber) {
+        self.not_migrating();
+        assert!(!self.paused, "{}", PAUSED_ERR);
+        self.not_denied(&env::predecessor_account_id());
+        self._check_deposit(self.like_fee);
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        assert!(self.editions.contains_key(&idx), "{}", TOKEN_LOCKED);
+        let mut liked_by = self.likes.get(&idx).unwrap_or_else(|| UnorderedSet::new(format!("lk{}", idx).into_bytes()));
+        assert!(liked_by.insert(&env::predecessor_account_id()), "{}", "ALREADY LIKED");
+        self.likes.insert(&idx, &liked_by);
+        self.like_counts.insert(&idx, &(self.like_counts.get(&idx).unwrap_or(0) + 1));
+        self._settle_deposit(self.like_fee);
+    }
+
+    pub fn unlike(&mut self, token_id: TokenId, edition_id: EditionNumber) {
+        self.not_migrating();
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        let mut liked_by = self.likes.get(&idx).unwrap_or_else(|| env::panic(b"NOT LIKED"));
+        assert!(liked_by.remove(&env::predecessor_account_id()), "{}", "NOT LIKED");
+        self.likes.insert(&idx, &liked_by);
+        self.like_counts.insert(&idx, &(self.like_counts.get(&idx).unwrap_or(0) - 1));
+    }
+
+    pub fn likes_of(&self, token_id: TokenId, edition_id: EditionNumber) -> u64 {
+        self.like_counts.get(&(self.tokens.get(&token_id).unwrap().edition_index + edition_id)).unwrap_or(0)
+    }
+
+    pub fn has_liked(&self, account: AccountId, token_id: TokenId, edition_id: EditionNumber) -> bool {
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        self.likes.get(&idx).is_some_and(|liked_by| liked_by.contains(&account))
+    }
+
+    pub fn offer_status(&self, token_id: TokenId, edition_id: EditionNumber, idx: u64) -> OfferStatus {
+        self.offers.get(&self.gen_token_x_edition(token_id, edition_id))
+            .and_then(|list| list.get(idx))
+            .map_or(OfferStatus::NOTFOUND, |bid| self._offer_status(&bid))
+    }
+
+    /// executed bids were either cancelled (refunded) or accepted, the bidder and amount are kept either way.
+    /// a bid past its expiry stays EXPIRED until reclaim_expired_offer refunds it, then it reads as cancelled.
+    fn _offer_status(&self, bid: &Bid) -> OfferStatus {
+        if !bid.executed && self._is_expired(bid) {
+            OfferStatus::EXPIRED
+        } else if !bid.executed {
+            OfferStatus::ACTIVE
+        } else if bid.cancelled {
+            OfferStatus::CANCELLED
+        } else {
+            OfferStatus::ACCEPTED
+        }
     }
 
     pub fn gen_token_x_edition(&self, token_id: TokenId, edition_id: EditionNumber) -> String {
         token_id.to_string() + &*"::".to_string() + &*edition_id.to_string()
     }
 
+    pub fn allowances_granted_to(&self, account: AccountId) -> Vec<(TokenId, EditionNumber)> {
+        self._granted_allowances(&account)
+            .iter()
+            .filter(|idx| self._is_allowed(*idx, account.clone()))
+            .filter_map(|idx| self.editions.get(&idx))
+            .map(|edition| (edition.token_id, edition.edition_number))
+            .collect()
+    }
+
+    fn gen_nonce_key(&self, owner: &AccountId, escrow: &AccountId) -> String {
+        owner.to_string() + "::" + escrow
+    }
+
+    /// enforces the deadline and nonce `owner` granted `escrow` access with, a single-use grant is consumed
+    fn _use_access_grant(&mut self, owner: &AccountId, escrow: &AccountId) {
+        let key = self.gen_nonce_key(owner, escrow);
+        if let Some(deadline) = self.access_deadlines.get(&key) {
+            assert!(env::block_timestamp() <= deadline, "{}", "TRANSFER AUTHORIZATION EXPIRED");
+        }
+        if let Some(nonce) = self.access_nonces.get(&key) {
+            assert_eq!(self.transfer_nonces.get(&key).unwrap_or(0), nonce, "{}", "INVALID TRANSFER NONCE");
+            self.transfer_nonces.insert(&key, &(nonce + 1));
+            let mut acc = self.account_gives_access.get(owner).unwrap();
+            acc.remove(escrow);
+            self.account_gives_access.insert(owner, &acc);
+            self._clear_access_terms(owner, escrow);
+            logger::add_escrow(owner.clone(), acc.to_vec());
+        }
+    }
+
+    fn _clear_access_terms(&mut self, owner: &AccountId, escrow: &AccountId) {
+        let key = self.gen_nonce_key(owner, escrow);
+        self.access_deadlines.remove(&key);
+        self.access_nonces.remove(&key);
+    }
+
+    pub fn transfer_nonce(&self, owner: AccountId, escrow: AccountId) -> u64 {
+        self.transfer_nonces.get(&self.gen_nonce_key(&owner, &escrow)).unwrap_or(0)
+    }
+
     pub fn get_allowances(&self, token_id: TokenId, edition_id: EditionNumber) -> Vec<AccountId> {
-        self.edition_allowances.get(&(self.tokens.get(&token_id).unwrap().edition_index + edition_id)).unwrap().as_vector().to_vec()
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        self.edition_allowances.get(&idx).unwrap()
+            .iter()
+            .filter(|account| self._is_allowed(idx, account.clone()))
+            .collect()
     }
-    /// VIEWS FOR INDEXER
+    // VIEWS FOR INDEXER
 
     pub fn get_offers(&self, token_id: TokenId, edition_id: EditionNumber) -> Vec<Bid> {
         let tokxedition = self.gen_token_x_edition(token_id, edition_id);
-        let list = self.offers.get(&tokxedition).unwrap();
+        // editions that never received a bid have no list yet
+        let list = match self.offers.get(&tokxedition) {
+            Some(list) => list,
+            None => return Vec::new(),
+        };
         let mut result = Vec::new();
         for i in 0..list.len() {
             result.push(list.get(i).unwrap())
         };
         result
     }
+    /// the highest bid that can still be accepted, expired ones are skipped. the earliest bid wins a tie.
+    pub fn highest_offer(&self, token_id: TokenId, edition_id: EditionNumber) -> Option<Bid> {
+        self.offers.get(&self.gen_token_x_edition(token_id, edition_id))?
+            .iter()
+            .filter(|bid| !bid.executed && !self._is_expired(bid))
+            .fold(None, |best: Option<Bid>, bid| match best {
+                Some(best) if best.amount >= bid.amount => Some(best),
+                _ => Some(bid),
+            })
+    }
+    /// non-executed offers paired with their index for accept_offer/cancel_offer, paged over the active offers only
+    pub fn active_offers(&self, token_id: TokenId, edition_id: EditionNumber, from_index: u64, limit: u64) -> Vec<(u64, Bid)> {
+        match self.offers.get(&self.gen_token_x_edition(token_id, edition_id)) {
+            Some(list) => (0..list.len())
+                .filter_map(|idx| list.get(idx).filter(|bid| !bid.executed).map(|bid| (idx, bid)))
+                .skip(from_index as usize)
+                .take(limit as usize)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// every bid ever placed on an edition with its final status, accepted and cancelled ones included.
+    /// pages over offer positions, so the u64 is the idx used by accept_offer and offer_status.
+    pub fn offer_history(&self, token_id: TokenId, edition_id: EditionNumber, from_index: u64, limit: u64) -> Vec<(u64, Bid, OfferStatus)> {
+        match self.offers.get(&self.gen_token_x_edition(token_id, edition_id)) {
+            Some(list) => (from_index..list.len().min(from_index.saturating_add(limit)))
+                .filter_map(|idx| list.get(idx).map(|bid| (idx, bid.clone(), self._offer_status(&bid))))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// full snapshot of an edition for off-chain backups and migrations. only the first active offers are included.
+    pub fn export_edition(&self, token_id: TokenId, edition_id: EditionNumber) -> EditionExport {
+        let token = self.tokens.get(&token_id).unwrap();
+        let idx = token.edition_index + edition_id;
+        let creator = self.metadata.get(&token.metadata).unwrap().creator;
+        let offers = self.offers.get(&self.gen_token_x_edition(token_id, edition_id))
+            .map(|list| list.iter().filter(|bid| !bid.executed).take(MAX_EXPORT_OFFERS).collect())
+            .unwrap_or_default();
+        EditionExport {
+            token_id,
+            edition_number: edition_id,
+            edition_index: idx,
+            edition: self.editions.get(&idx),
+            state: self.edition_states.get(&idx).unwrap(),
+            price: self.marketplace.get(&idx).map(|price| price.to_string()),
+            offers,
+            allowances: self.edition_allowances.get(&idx)
+                .map(|allowed| allowed.iter().filter(|account| self._is_allowed(idx, account.clone())).collect())
+                .unwrap_or_default(),
+            shares: self.edition_shares.get(&idx).unwrap_or_default(),
+            last_sale: self.last_sales.get(&idx).map(|price| price.to_string()),
+            creator_verified: self.verified_creators.contains(&creator),
+        }
+    }
+
+    /// recent mints, sales and transfers in a collection, oldest first. only the last MAX_COLLECTION_ACTIVITY are kept.
+    pub fn collection_activity(&self, collection_id: CollectionId, from_index: u64, limit: u64) -> Vec<ActivityRecord> {
+        let feed = match self.collection_activity.get(&collection_id) {
+            Some(feed) => feed,
+            None => return Vec::new(),
+        };
+        // once the buffer wrapped around, the oldest record sits at the next write position
+        let start = self.collection_activity_count.get(&collection_id).unwrap_or(0) % MAX_COLLECTION_ACTIVITY;
+        let start = if feed.len() < MAX_COLLECTION_ACTIVITY { 0 } else { start };
+        (from_index..feed.len().min(from_index.saturating_add(limit)))
+            .filter_map(|i| feed.get((start + i) % feed.len()))
+            .collect()
+    }
+
+    /// tokens minted by `creator`. the creator is always the minting account, tokens in the genesis collection
+    /// are listed under their minter and not under the collection's default creator.
+    pub fn tokens_by_creator(&self, creator: AccountId, from_index: u64, limit: u64) -> Vec<TokenId> {
+        match self.creator_tokens.get(&creator) {
+            Some(minted) => (from_index..minted.len().min(from_index.saturating_add(limit))).filter_map(|i| minted.get(i)).collect(),
+            None => Vec::new(),
+        }
+    }
+    pub fn tokens_by_creator_count(&self, creator: AccountId) -> u64 {
+        self.creator_tokens.get(&creator).map_or(0, |minted| minted.len())
+    }
 
     pub fn get_token(&self, token_id: TokenId) -> Token {
         self.tokens.get(&token_id).unwrap()
@@ -636,7 +2777,7 @@ impl NonFungibleToken {
 
     pub fn get_edition(&self, token_id: TokenId, edition_id: EditionNumber) -> Edition {
         let index = self.tokens.get(&token_id).unwrap();
-        self.editions.get(&u64::from(index.edition_index + edition_id as u64)).unwrap()
+        self.editions.get(&(index.edition_index + edition_id)).unwrap()
     }
 
     pub fn get_collection(&self, collection_id: CollectionId) -> Collection {
@@ -645,21 +2786,98 @@ impl NonFungibleToken {
     pub fn get_metadata(&self, token_id: TokenId) -> Metadata {
         self.metadata.get(&token_id).unwrap()
     }
+    pub fn token_exists(&self, token_id: TokenId) -> bool {
+        self.tokens.contains_key(&token_id)
+    }
+    pub fn collection_exists(&self, collection_id: CollectionId) -> bool {
+        self.collections.contains_key(&collection_id)
+    }
+    /// false for unknown tokens, out of range edition numbers and burned editions
+    pub fn edition_exists(&self, token_id: TokenId, edition_id: EditionNumber) -> bool {
+        match self.tokens.get(&token_id) {
+            Some(token) => edition_id > 0 && edition_id <= token.editions && self.editions.contains_key(&(token.edition_index + edition_id)),
+            None => false,
+        }
+    }
     pub fn owner_of(&self, token_id: TokenId, edition_id: EditionNumber) -> AccountId {
         let index = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
         self.editions.get(&(index)).unwrap().edition_owner
     }
+    /// true when the absolute edition index was burned. burned indices are retired for good and never reassigned.
+    pub fn is_index_burned(&self, index: u64) -> bool {
+        self.edition_states.get(&index) == Some(EditionState::BURNED)
+    }
+    pub fn next_token_id(&self) -> TokenId {
+        self.current_supply
+    }
+    /// edition_index the next minted token gets, its editions live at next_edition_index + edition_number
+    pub fn next_edition_index(&self) -> u64 {
+        self.total_editions
+    }
+    /// 1-based rank of an edition within its token: its absolute index counted from the token's edition_index,
+    /// so the first edition ranks 1 whatever offset the token was minted at.
+    pub fn edition_rank(&self, token_id: TokenId, edition_id: EditionNumber) -> u64 {
+        let idx = self._edition_abs_index(token_id, edition_id).unwrap_or_else(|| env::panic(b"EDITION DOES NOT EXIST"));
+        idx - self.tokens.get(&token_id).unwrap().edition_index
+    }
+    /// (edition_number, absolute index) for every edition of a token. the absolute index is edition_index + edition_number
+    /// and is the key of editions, edition_states and marketplace.
+    pub fn edition_indices(&self, token_id: TokenId) -> Vec<(EditionNumber, u64)> {
+        let token = self.tokens.get(&token_id).unwrap();
+        (1..=token.editions)
+            .filter_map(|edition_id| self._edition_abs_index(token_id, edition_id).map(|idx| (edition_id, idx)))
+            .collect()
+    }
+    pub fn is_first_edition(&self, token_id: TokenId, edition_id: EditionNumber) -> bool {
+        self.edition_rank(token_id, edition_id) == 1
+    }
+    /// current owners of arbitrary editions in input order, None for burned or missing ones. at most MAX_BATCH items.
+    pub fn owners_of_many(&self, items: Vec<(TokenId, EditionNumber)>) -> Vec<Option<AccountId>> {
+        assert!(items.len() <= MAX_BATCH, "{}", "TOO MANY ITEMS");
+        items.into_iter()
+            .map(|(token_id, edition_id)| {
+                let idx = self._edition_abs_index(token_id, edition_id)?;
+                self.editions.get(&idx).map(|edition| edition.edition_owner)
+            })
+            .collect()
+    }
+    /// everything a marketplace tile needs in one read, None for missing or burned editions
+    pub fn edition_card(&self, token_id: TokenId, edition_id: EditionNumber) -> Option<EditionCard> {
+        let idx = self._edition_abs_index(token_id, edition_id)?;
+        let edition = self.editions.get(&idx)?;
+        let price = self.marketplace.get(&idx);
+        Some(EditionCard {
+            token_id,
+            edition_number: edition_id,
+            owner: edition.edition_owner,
+            state: self.edition_states.get(&idx).unwrap(),
+            listed: price.is_some(),
+            price: price.map(|price| price.to_string()),
+        })
+    }
+    /// absolute edition index, None when the token doesn't exist or has no such edition number
+    fn _edition_abs_index(&self, token_id: TokenId, edition_id: EditionNumber) -> Option<u64> {
+        let token = self.tokens.get(&token_id)?;
+        if edition_id == 0 || edition_id > token.editions {
+            return None;
+        }
+        Some(token.edition_index + edition_id)
+    }
     // admin stuff
     pub fn generate_genesis_collection(&mut self, thumbnail: String) {
+        self.not_migrating();
         self.only_owner();
-        assert_eq!(self.collections.get(&(0 as u64)).is_none(), true, "GENESIS COLLECTION ALREADY CREATED");
-        self.collections.insert(&(0 as u64), &Collection {
+        assert!(self.collections.get(&0_u64).is_none(), "GENESIS COLLECTION ALREADY CREATED");
+        self.collections.insert(&0_u64, &Collection {
             name: "Nearfolio".to_string(),
             date: env::block_timestamp().to_string(),
             thumbnail: thumbnail.clone(),
             creator: "nearfolio.near".to_string(),
             minters: Vec::new(),
             description: "Nearfolio default collection.".to_string(),
+            trade_start: None,
+            trade_end: None,
+            royalties: Vec::new(),
         });
         self.paused = false;
         logger::log_collection(Collection {
@@ -669,18 +2887,46 @@ impl NonFungibleToken {
             creator: "nearfolio.near".to_string(),
             minters: Vec::new(),
             description: "Nearfolio default collection.".to_string(),
+            trade_start: None,
+            trade_end: None,
+            royalties: Vec::new(),
         }, 0);
     }
     pub fn pause(&mut self) {
+        self.not_migrating();
         self.only_owner();
         self.paused = true;
     }
     pub fn unpause(&mut self) {
+        self.not_migrating();
         self.only_owner();
         self.paused = false
     }
+    /// freezes listing, bidding, sales and transfers of the given tokens, e.g. for a compromised collection
+    pub fn pause_tokens(&mut self, token_ids: Vec<TokenId>) {
+        self.not_migrating();
+        self.only_owner();
+        assert!(!token_ids.is_empty() && token_ids.len() <= MAX_BATCH, "{}", "INVALID BATCH SIZE");
+        for token_id in token_ids {
+            assert!(self.tokens.contains_key(&token_id), "{}", "TOKEN DOES NOT EXIST");
+            self.paused_tokens.insert(&token_id);
+            logger::token_pause(token_id, true);
+        }
+    }
+    pub fn unpause_tokens(&mut self, token_ids: Vec<TokenId>) {
+        self.not_migrating();
+        self.only_owner();
+        assert!(!token_ids.is_empty() && token_ids.len() <= MAX_BATCH, "{}", "INVALID BATCH SIZE");
+        for token_id in token_ids {
+            self.paused_tokens.remove(&token_id);
+            logger::token_pause(token_id, false);
+        }
+    }
+    pub fn is_token_paused(&self, token_id: TokenId) -> bool {
+        self.paused_tokens.contains(&token_id)
+    }
     pub fn is_paused(&self) -> bool {
-        self.paused.clone()
+        self.paused
     }
     pub fn is_escrow(&self, account_id: AccountId, escrow: AccountId) -> bool {
         self.account_gives_access.get(&account_id).unwrap().contains(&escrow)
@@ -688,9 +2934,45 @@ impl NonFungibleToken {
     pub fn get_escrows(&self, account_id: AccountId) -> Vec<AccountId> {
         self.account_gives_access.get(&account_id).unwrap().to_vec()
     }
-    /* pub fn owned_editions(&self, account: AccountId) -> Vec<EditionNumber> {
-        self.account_to_editions.get(&account).unwrap().as_vector().to_vec()
-    } */
+    /// editions `account` currently owns, paged in no particular order
+    pub fn tokens_by_owner(&self, account: AccountId, from_index: u64, limit: u64) -> Vec<Edition> {
+        match self.account_to_editions.get(&account) {
+            Some(owned) => {
+                let owned = owned.as_vector();
+                (from_index..owned.len().min(from_index.saturating_add(limit)))
+                    .filter_map(|i| self.editions.get(&owned.get(i)?))
+                    .collect()
+            }
+            None => Vec::new(),
+        }
+    }
+    pub fn tokens_by_owner_count(&self, account: AccountId) -> u64 {
+        self.account_to_editions.get(&account).map_or(0, |owned| owned.len())
+    }
+    /// cheap consistency probe for operators. supply and edition totals are checked against the latest token,
+    /// listing states against the marketplace for one page of editions only: absolute indexes from `from_index`,
+    /// at most `limit` capped at MAX_INVARIANT_SCAN. pass the returned next_index back in to check the next page.
+    pub fn invariants(&self, from_index: u64, limit: u64) -> InvariantReport {
+        let latest = self.current_supply.checked_sub(1).and_then(|token_id| self.tokens.get(&token_id));
+        let supply_consistent = !self.tokens.contains_key(&self.current_supply) && (self.current_supply == 0 || latest.is_some());
+        let editions_consistent = match latest {
+            Some(token) => token.edition_index + token.editions == self.total_editions,
+            None => self.total_editions == 0,
+        };
+        let first = std::cmp::max(from_index, 1);
+        let end = std::cmp::min(first.saturating_add(std::cmp::min(limit, MAX_INVARIANT_SCAN)), self.total_editions + 1);
+        let listings_consistent = (first..end).all(|idx| {
+            let listed = self.edition_states.get(&idx) == Some(EditionState::LISTED);
+            listed == self.marketplace.contains_key(&idx)
+        });
+        InvariantReport {
+            supply_consistent,
+            editions_consistent,
+            listings_consistent,
+            scanned_editions: end.saturating_sub(first),
+            next_index: std::cmp::max(first, end),
+        }
+    }
     pub fn edition_by_index(&self, index: u64) -> Edition {
         self.editions.get(&index).unwrap()
     }
@@ -698,32 +2980,127 @@ impl NonFungibleToken {
     fn only_owner(&self) {
         assert_eq!(env::predecessor_account_id(), self.owner_id, "{}", ONLY_OWNER);
     }
+    /// minters are managed by the owner or the delegated minter admin, e.g. a DAO contract
+    fn only_minter_admin(&self) {
+        let caller = env::predecessor_account_id();
+        assert!(caller == self.owner_id || self.minter_admin.as_ref() == Some(&caller), "{}", ONLY_MINTER_ADMIN);
+    }
     fn only_whitelisted(&self) {
         assert!(self.minters.contains(&env::predecessor_account_id()), "{}", ONLY_MINTER)
     }
+    fn not_migrating(&self) {
+        assert!(!self.migration_in_progress, "{}", MIGRATING)
+    }
+    fn legacy_api(&self) {
+        assert!(self.legacy_api_enabled, "{}", LEGACY_API_DISABLED)
+    }
+    fn not_token_paused(&self, token_id: TokenId) {
+        assert!(!self.paused_tokens.contains(&token_id), "{}", TOKEN_PAUSED)
+    }
+    fn not_denied(&self, account: &AccountId) {
+        assert!(!self.denylist.contains(account), "{}", ACC_DENIED)
+    }
     fn only_token_owner(&self, token_id: TokenId, edition_id: EditionNumber) {
         let token = self.tokens.get(&token_id).unwrap();
-        let edition = self.editions.get(&u64::from(edition_id as u64 + token.edition_index)).unwrap();
+        // burned editions have no entry in editions anymore
+        let edition = self.editions.get(&(edition_id + token.edition_index)).unwrap_or_else(|| env::panic(TOKEN_LOCKED.as_bytes()));
         assert_eq!(edition.edition_owner, env::predecessor_account_id(), "{}", ONLY_TOKEN_OWNER)
     }
     fn check_valid_account(&self, account: AccountId) {
-        let acc_hash = env::sha256(account.as_bytes());
-        assert!(env::is_valid_account_id(&acc_hash), "{}", ACC_NOT_VALID);
+        assert!(env::is_valid_account_id(account.as_bytes()), "{}", ACC_NOT_VALID);
     }
     fn _is_allowed(&self, idx: u64, account: AccountId) -> bool {
-        let allowances = self.edition_allowances.get(&idx).unwrap();
-        allowances.contains(&account)
+        let expiry = self.allowance_expiry.get(&self.gen_allowance_key(idx, &account));
+        self._has_allowance(idx, &account) && expiry.is_none_or(|expires_at| env::block_timestamp() <= expires_at)
+    }
+    /// granted on this very edition, expired or not. an allowance set shared with sibling editions by older mints
+    /// also holds accounts approved on the siblings, only the approval id tells them apart.
+    fn _has_allowance(&self, idx: u64, account: &AccountId) -> bool {
+        self.edition_allowances.get(&idx).unwrap().contains(account)
+            && self.approval_ids.contains_key(&self.gen_allowance_key(idx, account))
+    }
+    fn gen_allowance_key(&self, idx: u64, account: &AccountId) -> String {
+        idx.to_string() + "::" + account
+    }
+    /// adds `account` to the edition's allowances under a fresh approval id, re-approving replaces the old id
+    fn _approve(&mut self, token_id: TokenId, edition_id: EditionNumber, edition_idx: u64, account: &AccountId) -> u64 {
+        let mut allowances = self.edition_allowances.get(&edition_idx).unwrap();
+        allowances.insert(account);
+        self.edition_allowances.insert(&edition_idx, &allowances);
+        let mut granted = self._granted_allowances(account);
+        granted.insert(&edition_idx);
+        self.allowances_granted.insert(account, &granted);
+        let approval_id = self.next_approval_id.get(&edition_idx).unwrap_or(0) + 1;
+        self.next_approval_id.insert(&edition_idx, &approval_id);
+        self.approval_ids.insert(&self.gen_allowance_key(edition_idx, account), &approval_id);
+        logger::edition_allowance(token_id, edition_id, edition_idx, allowances.as_vector().to_vec());
+        approval_id
+    }
+    fn _revoke(&mut self, token_id: TokenId, edition_id: EditionNumber, edition_idx: u64, account: &AccountId) {
+        let mut allowances = self.edition_allowances.get(&edition_idx).unwrap();
+        allowances.remove(account);
+        self.edition_allowances.insert(&edition_idx, &allowances);
+        self.allowance_expiry.remove(&self.gen_allowance_key(edition_idx, account));
+        self.approval_ids.remove(&self.gen_allowance_key(edition_idx, account));
+        let mut granted = self._granted_allowances(account);
+        granted.remove(&edition_idx);
+        self.allowances_granted.insert(account, &granted);
+        logger::edition_allowance(token_id, edition_id, edition_idx, allowances.as_vector().to_vec())
     }
     fn _clear_allowance(&mut self, edition_idx: u64) {
         let mut allowances = self.edition_allowances.get(&edition_idx).unwrap();
+        for account in allowances.iter() {
+            self.allowance_expiry.remove(&self.gen_allowance_key(edition_idx, &account));
+            self.approval_ids.remove(&self.gen_allowance_key(edition_idx, &account));
+            let mut granted = self._granted_allowances(&account);
+            granted.remove(&edition_idx);
+            self.allowances_granted.insert(&account, &granted);
+        }
         allowances.clear();
         self.edition_allowances.insert(&edition_idx, &allowances);
+        if let Some(mut burners) = self.burn_allowances.get(&edition_idx) {
+            burners.clear();
+            self.burn_allowances.insert(&edition_idx, &burners);
+        }
+    }
+    fn _burn_allowances(&self, edition_idx: u64) -> UnorderedSet<AccountId> {
+        self.burn_allowances.get(&edition_idx).unwrap_or_else(|| UnorderedSet::new(format!("ba{}", edition_idx).into_bytes()))
+    }
+    fn _record_collection_activity(&mut self, token_id: TokenId, edition_id: EditionNumber, event_name: &str, amount: Balance, from: AccountId, to: AccountId) {
+        let collection_id = self.metadata.get(&self.tokens.get(&token_id).unwrap().metadata).unwrap().collection_id;
+        let mut feed = self.collection_activity.get(&collection_id).unwrap_or_else(|| Vector::new(format!("ca{}", collection_id).into_bytes()));
+        let count = self.collection_activity_count.get(&collection_id).unwrap_or(0);
+        let record = ActivityRecord {
+            token_id,
+            edition_id,
+            event_name: event_name.to_string(),
+            from,
+            to,
+            amount,
+            date: env::block_timestamp().to_string(),
+        };
+        if feed.len() < MAX_COLLECTION_ACTIVITY {
+            feed.push(&record);
+        } else {
+            feed.replace(count % MAX_COLLECTION_ACTIVITY, &record);
+        }
+        self.collection_activity.insert(&collection_id, &feed);
+        self.collection_activity_count.insert(&collection_id, &(count + 1));
+    }
+    fn _add_collector_volume(&mut self, account: AccountId, amount: Balance) {
+        let volume = self.collector_volume.get(&account).unwrap_or(0) + amount;
+        self.collector_volume.insert(&account, &volume);
     }
-    fn _internal_transfer(&mut self, from: AccountId, to: AccountId, token_id: u64, edition_number: u64, edition_idx: u64) {
-        //self.check_valid_account(to.clone());
+    fn _granted_allowances(&self, account: &AccountId) -> UnorderedSet<u64> {
+        self.allowances_granted.get(account).unwrap_or_else(|| UnorderedSet::new(format!("ag{}", account).into_bytes()))
+    }
+    fn _internal_transfer(&mut self, from: AccountId, to: AccountId, token_id: u64, edition_number: u64, edition_idx: u64, reason: &str) {
+        self.check_valid_account(to.clone());
         let mut edition = self.editions.get(&edition_idx).unwrap();
-        assert_eq!(self.is_paused(), false, "{}", PAUSED_ERR);
-        assert_eq!(edition.edition_owner == from && edition.edition_number == edition_number, true, "{} {}", ONLY_TOKEN_OWNER, "ERROR2".to_string());
+        assert!(!self.is_paused(), "{}", PAUSED_ERR);
+        self.not_token_paused(token_id);
+        assert!(edition.edition_owner == from && edition.edition_number == edition_number, "{} {}", ONLY_TOKEN_OWNER, "ERROR2");
+        assert!(!self.edition_shares.contains_key(&edition_idx), "{}", FRACTIONALIZED);
         // ensure token is available
         let state = self.edition_states.get(&edition_idx).unwrap();
         match state {
@@ -731,10 +3108,17 @@ impl NonFungibleToken {
                 env::panic(TOKEN_LOCKED.as_bytes());
             }
             EditionState::LOCKED => {
-                env::panic(TOKEN_LOCKED.as_bytes());
+                // only settling the auction holding the lock hands a locked edition over
+                let settling = reason == REASON_AUCTION && (self.auctions.contains_key(&edition_idx) || self.sealed_auctions.contains_key(&edition_idx));
+                assert!(settling, "{}", TOKEN_LOCKED);
             }
             EditionState::LISTED => {
                 self.marketplace.remove(&edition_idx);
+                self.listings.remove(&edition_idx);
+                // sales pool a prepaid royalty with the proceeds in _distribute_sale, anything else hands it back
+                if reason != REASON_SALE && reason != REASON_OFFER_ACCEPT {
+                    self._refund_prepaid_royalty(edition_idx);
+                }
             }
             _ => {}
         }
@@ -742,32 +3126,161 @@ impl NonFungibleToken {
         edition.edition_owner = to.clone();
 
         self.editions.insert(&edition_idx, &edition);
+        self._remove_owned(&from, edition_idx);
+        self._add_owned(&to, edition_idx);
+        self.acquired_at.insert(&edition_idx, &env::block_timestamp());
         self.edition_states.insert(&edition_idx, &EditionState::AVAILABLE);
-        self._clear_allowance(edition_idx.clone());
+        self._clear_allowance(edition_idx);
         logger::transfer_edition(edition, edition_idx, env::predecessor_account_id());
-        logger::insert_activity(token_id, edition_number, "Transfer".to_string(), to, from)
+        logger::nep171_transfer(from.clone(), to.clone(), token_id, edition_number);
+        logger::transfer_activity(token_id, edition_number, reason, to, from)
+    }
+    /// owner, fee receiver and every fee and limit, the same data ContractInitialized logged at deploy
+    pub fn config_snapshot(&self) -> ConfigSnapshot {
+        ConfigSnapshot {
+            owner_id: self.owner_id.clone(),
+            fee_receiver: self.fee_receiver.clone(),
+            mint_storage_fee: self.mint_storage_fee.to_string(),
+            edition_storage_fee: self.edition_storage_fee.to_string(),
+            create_collection_fee: self.create_collection_fee.to_string(),
+            trade_fee: self.trade_fee.to_string(),
+            like_fee: self.like_fee.to_string(),
+            min_royalty_bps: self.min_royalty_bps,
+            outbid_fee_bps: self.outbid_fee_bps,
+            max_name_length: self.MAX_NAME_LENGTH,
+            max_description_length: self.MAX_DESCRIPTION_LENGTH,
+            max_editions: self.MAX_EDITIONS,
+            max_external_link: self.MAX_EXTERNAL_LINK,
+            strict_deposits: self.strict_deposits,
+            emergency_stop: self.emergency_stop,
+            otc_royalty_required: self.otc_royalty_required,
+            listing_grace_ns: self.listing_grace_ns,
+            listing_fee: self.listing_fee.to_string(),
+            mint_rate_limit: self.mint_rate_limit,
+            mint_rate_window: self.mint_rate_window,
+            primary_sale_fee_bps: self.primary_sale_fee_bps,
+            legacy_api_enabled: self.legacy_api_enabled,
+            min_listing_price: self.min_listing_price.to_string(),
+            max_listing_price: self.max_listing_price.to_string(),
+            minter_admin: self.minter_admin.clone(),
+            paused: self.paused,
+        }
     }
     pub fn owner(&self) -> AccountId {
         self.owner_id.clone()
     }
     pub fn is_minter(&self, account: AccountId) -> bool {
-        self.minters.contains(&account).clone()
+        self.minters.contains(&account)
     }
     pub fn mint_fee(&self) -> Balance {
-        self.mint_storage_fee.clone()
+        self.mint_storage_fee
     }
     pub fn edition_fee(&self) -> Balance {
-        self.edition_storage_fee.clone()
+        self.edition_storage_fee
+    }
+    /// exact deposit mint_token requires for the given edition count, in yoctoNEAR
+    pub fn mint_cost(&self, editions: EditionNumber) -> String {
+        self._mint_cost(editions).to_string()
+    }
+    pub fn collection_cost(&self) -> String {
+        self.create_collection_fee.to_string()
+    }
+    /// sends collected mint and collection fees to fee_receiver. everything else the contract holds, offer and auction
+    /// deposits, prepaid royalties and creator earnings, is never counted as fees, and the withdrawal can't dip into
+    /// the balance staked for the contract's current storage (storage_usage bytes at 1 NEAR per 100kb).
+    pub fn withdraw_fees(&mut self, amount: String) {
+        self.not_migrating();
+        self.only_owner();
+        let amount = parse_balance(&amount);
+        assert!(amount <= self.collected_fees, "{} {}", "AMOUNT EXCEEDS COLLECTED FEES", self.collected_fees);
+        let staked = Balance::from(env::storage_usage()) * STORAGE_PRICE_PER_BYTE;
+        assert!(env::account_balance().saturating_sub(staked) >= amount, "{}", "AMOUNT WOULD TOUCH THE STORAGE STAKING RESERVE");
+        self.collected_fees -= amount;
+        Promise::new(self.fee_receiver.clone()).transfer(amount);
+        logger::near_transfer(self.fee_receiver.clone(), amount, TransferReason::FEE, env::block_timestamp());
+    }
+    pub fn collected_fees(&self) -> String {
+        self.collected_fees.to_string()
     }
     pub fn set_mint_fee(&mut self, fee: String) {
+        self.not_migrating();
         self.only_owner();
-        self.mint_storage_fee = u128::from_str(&fee).unwrap();
+        self.mint_storage_fee = parse_balance(&fee);
     }
     pub fn set_edition_fee(&mut self, fee: String) {
+        self.not_migrating();
         self.only_owner();
-        self.edition_storage_fee = u128::from_str(&fee).unwrap();
+        self.edition_storage_fee = parse_balance(&fee);
+    }
+    /// when enabled, overpaying mint_token or create_collection panics instead of leaving the excess on the contract,
+    /// and an offer must name its amount and attach exactly that plus the edition storage fee.
+    pub fn set_strict_deposits(&mut self, value: bool) {
+        self.not_migrating();
+        self.only_owner();
+        self.strict_deposits = value;
+    }
+    pub fn is_strict_deposits(&self) -> bool {
+        self.strict_deposits
+    }
+    /// turns the legacy NEP-4 transfer, transfer_from and grant_access off to move integrators to the standard API
+    pub fn set_legacy_api_enabled(&mut self, value: bool) {
+        self.not_migrating();
+        self.only_owner();
+        self.legacy_api_enabled = value;
+        logger::config_update("legacy_api_enabled", value.to_string());
+    }
+    pub fn is_legacy_api_enabled(&self) -> bool {
+        self.legacy_api_enabled
+    }
+    pub fn set_emergency_stop(&mut self, value: bool) {
+        self.not_migrating();
+        self.only_owner();
+        self.emergency_stop = value;
+        logger::config_update("emergency_stop", value.to_string());
+    }
+    pub fn is_emergency_stop(&self) -> bool {
+        self.emergency_stop
+    }
+    pub fn set_like_fee(&mut self, fee: String) {
+        self.not_migrating();
+        self.only_owner();
+        self.like_fee = parse_balance(&fee);
+    }
+    pub fn like_fee(&self) -> String {
+        self.like_fee.to_string()
+    }
+    /// minimum secondary sale royalty in basis points a new token must carry
+    pub fn set_min_royalty_bps(&mut self, value: u32) {
+        self.not_migrating();
+        self.only_owner();
+        assert!(value <= 10_000, "{}", "ROYALTY FLOOR CANNOT EXCEED 10000 BPS");
+        self.min_royalty_bps = value;
+    }
+    pub fn min_royalty_bps(&self) -> u32 {
+        self.min_royalty_bps
+    }
+    /// when enabled, record_otc_sale requires the creator's royalty on the agreed price to be attached
+    pub fn set_otc_royalty_required(&mut self, value: bool) {
+        self.not_migrating();
+        self.only_owner();
+        self.otc_royalty_required = value;
+    }
+    pub fn is_otc_royalty_required(&self) -> bool {
+        self.otc_royalty_required
+    }
+    /// share of an outbid auction bid kept as fee when it is refunded, in basis points.
+    /// makes rapid outbid churn cost something, 0 refunds outbid bidders in full.
+    pub fn set_outbid_fee_bps(&mut self, value: u32) {
+        self.not_migrating();
+        self.only_owner();
+        assert!(value <= 10_000, "{}", "OUTBID FEE CANNOT EXCEED 10000 BPS");
+        self.outbid_fee_bps = value;
+    }
+    pub fn outbid_fee_bps(&self) -> u32 {
+        self.outbid_fee_bps
     }
     pub fn set_max_edition(&mut self, value: u8) {
+        self.not_migrating();
         self.only_owner();
         self.MAX_EDITIONS = value;
     }
@@ -777,14 +3290,220 @@ impl NonFungibleToken {
     pub fn state_of(&self, token_id: TokenId, edition_id: EditionNumber) -> EditionState {
         self.edition_states.get(&(self.tokens.get(&token_id).unwrap().edition_index + edition_id)).unwrap()
     }
+    pub fn editions_by_state(&self, token_id: TokenId, state: EditionState) -> Vec<EditionNumber> {
+        let token = self.tokens.get(&token_id).unwrap();
+        (1..=token.editions)
+            .filter(|edition_id| self.edition_states.get(&(token.edition_index + edition_id)).as_ref() == Some(&state))
+            .collect()
+    }
     pub fn fee_receiver(&self) -> AccountId {
         self.fee_receiver.clone()
     }
     pub fn all_minters(&self) -> Vec<AccountId> {
         self.minters.as_vector().to_vec()
     }
+    /// recovery tool for the indexer: logs the current metadata, editions and listings of up to MAX_REEMIT tokens
+    /// starting at `from_token` again. burned editions are skipped, returns the token id to continue from.
+    pub fn reemit_tokens(&mut self, from_token: TokenId, limit: u64) -> TokenId {
+        self.only_owner();
+        let end = std::cmp::min(from_token.saturating_add(std::cmp::min(limit, MAX_REEMIT)), self.current_supply);
+        for token_id in from_token..end {
+            let token = match self.tokens.get(&token_id) {
+                Some(token) => token,
+                None => continue,
+            };
+            let md = self.metadata.get(&token.metadata).unwrap();
+            logger::log_mint(md.clone(), token_id);
+            for edition_id in 1..=token.editions {
+                let idx = token.edition_index + edition_id;
+                if let Some(edition) = self.editions.get(&idx) {
+                    logger::log_mint_editions(edition.clone(), idx);
+                    if let Some(price) = self.marketplace.get(&idx) {
+                        logger::marketplace_insert(edition, idx, price);
+                    }
+                }
+            }
+        }
+        std::cmp::max(from_token, end)
+    }
+    pub fn finish_migration(&mut self) {
+        self.only_owner();
+        assert!(self.migration_in_progress, "{}", "NO MIGRATION IN PROGRESS");
+        self.migration_in_progress = false;
+    }
+    pub fn is_migration_in_progress(&self) -> bool {
+        self.migration_in_progress
+    }
+    /// rewrites metadata stored before the media fields existed into the current layout, the new fields become None.
+    /// run it before anything else that reads metadata.
+    pub fn migrate_metadata(&mut self, from_token: TokenId, limit: u64) -> TokenId {
+        self.only_owner();
+        let end = std::cmp::min(from_token.saturating_add(limit), self.current_supply);
+        for token_id in from_token..end {
+            let mut key = b"md".to_vec();
+            key.extend(token_id.try_to_vec().unwrap());
+            if let Some(raw) = env::storage_read(&key) {
+                if Metadata::try_from_slice(&raw).is_err() {
+                    let legacy = LegacyMetadata::try_from_slice(&raw).unwrap_or_else(|_| env::panic(b"UNKNOWN METADATA LAYOUT"));
+                    rewrite_raw(&key, &Metadata::from(legacy));
+                }
+            }
+        }
+        std::cmp::max(from_token, end)
+    }
+    /// rewrites collections stored before trade windows and collection royalties existed into the current layout,
+    /// with no window and no royalty split. ids start at 0 for the genesis collection.
+    pub fn migrate_collections(&mut self, from_id: CollectionId, limit: u64) -> CollectionId {
+        self.only_owner();
+        let end = std::cmp::min(from_id.saturating_add(limit), self.total_collections + 1);
+        for collection_id in from_id..end {
+            let mut key = b"c".to_vec();
+            key.extend(collection_id.try_to_vec().unwrap());
+            if let Some(raw) = env::storage_read(&key) {
+                if Collection::try_from_slice(&raw).is_err() {
+                    let legacy = LegacyCollection::try_from_slice(&raw).unwrap_or_else(|_| env::panic(b"UNKNOWN COLLECTION LAYOUT"));
+                    rewrite_raw(&key, &Collection::from(legacy));
+                }
+            }
+        }
+        std::cmp::max(from_id, end)
+    }
+    /// rewrites the edition offers of up to `limit` tokens starting at `from_token` that were stored before bids had a
+    /// storage fee and expiry. an open legacy bid holds the whole deposit, the current edition storage fee is split
+    /// off it as its reservation and it is indexed for its bidder.
+    pub fn migrate_offers(&mut self, from_token: TokenId, limit: u64) -> TokenId {
+        self.only_owner();
+        let end = std::cmp::min(from_token.saturating_add(limit), self.current_supply);
+        for token_id in from_token..end {
+            let editions = match self.tokens.get(&token_id) {
+                Some(token) => token.editions,
+                None => continue,
+            };
+            for edition_id in 1..=editions {
+                let mut key = b"O".to_vec();
+                key.extend(self.gen_token_x_edition(token_id, edition_id).try_to_vec().unwrap());
+                let stored = match env::storage_read(&key) {
+                    Some(raw) => StoredVector::try_from_slice(&raw).unwrap(),
+                    None => continue,
+                };
+                for idx in 0..stored.len {
+                    let mut element_key = stored.prefix.clone();
+                    element_key.extend(&idx.to_le_bytes());
+                    let raw = env::storage_read(&element_key).unwrap();
+                    if Bid::try_from_slice(&raw).is_err() {
+                        let legacy = LegacyBid::try_from_slice(&raw).unwrap_or_else(|_| env::panic(b"UNKNOWN BID LAYOUT"));
+                        let bid = legacy.into_bid(self.edition_storage_fee);
+                        if !bid.executed {
+                            self._index_bid(&bid.bidder, (token_id, edition_id, idx), true);
+                        }
+                        rewrite_raw(&element_key, &bid);
+                    }
+                }
+            }
+        }
+        std::cmp::max(from_token, end)
+    }
+    /// indexes the owners of up to `limit` editions starting at absolute index `from_index` for tokens_by_owner,
+    /// for editions minted before ownership was tracked, and counts their burned editions for nft_total_supply.
+    /// burns are only counted in one pass from index 1 on.
+    pub fn migrate_owned_editions(&mut self, from_index: u64, limit: u64) -> u64 {
+        self.only_owner();
+        let end = std::cmp::min(from_index.saturating_add(limit), self.total_editions + 1);
+        for idx in from_index..end {
+            if let Some(edition) = self.editions.get(&idx) {
+                self._add_owned(&edition.edition_owner, idx);
+            }
+            if self.burned_scan_index.checked_add(1) == Some(idx) {
+                if self.edition_states.get(&idx) == Some(EditionState::BURNED) {
+                    self.burned_editions += 1;
+                }
+                self.burned_scan_index = idx;
+            }
+        }
+        std::cmp::max(from_index, end)
+    }
+    /// gives up to `limit` editions starting at absolute index `from_index` their own allowance set. editions minted
+    /// by the first deployment shared one set per token, so an allowance on one edition leaked to its siblings.
+    /// their allowances are reset, the shared sets are left in storage.
+    pub fn migrate_allowances(&mut self, from_index: u64, limit: u64) -> u64 {
+        self.only_owner();
+        let end = std::cmp::min(from_index.saturating_add(limit), self.total_editions + 1);
+        for idx in from_index..end {
+            let mut key = b"ea".to_vec();
+            key.extend(idx.try_to_vec().unwrap());
+            let stored = match env::storage_read(&key) {
+                Some(bytes) => StoredSet::deserialize(&mut bytes.as_slice()).unwrap(),
+                None => continue,
+            };
+            let prefix = self.allowances_prefix(idx);
+            if stored.index_prefix != [prefix.as_slice(), b"i"].concat() {
+                self.edition_allowances.insert(&idx, &UnorderedSet::new(prefix));
+            }
+        }
+        std::cmp::max(from_index, end)
+    }
+    /// converts the legacy divisor royalty of up to `limit` tokens starting at `from_token` into bps, both in
+    /// royalty_bps and in the token's metadata.
+    pub fn migrate_royalties(&mut self, from_token: TokenId, limit: u64) -> TokenId {
+        self.only_owner();
+        let end = std::cmp::min(from_token.saturating_add(limit), self.current_supply);
+        for token_id in from_token..end {
+            if let Some(mut md) = self.metadata.get(&token_id) {
+                let bps = self.royalty_bps.get(&token_id).unwrap_or_else(|| self._royalty_bps(md.royalty));
+                self.royalty_bps.insert(&token_id, &bps);
+                if md.royalty != bps {
+                    md.royalty = bps;
+                    self.metadata.insert(&token_id, &md);
+                }
+            }
+        }
+        std::cmp::max(from_token, end)
+    }
+    pub fn royalty_bps_of(&self, token_id: TokenId) -> Option<u32> {
+        self.royalty_bps.get(&token_id)
+    }
+    /// trade fee in basis points of the sale amount, e.g. 250 takes 2.5%. 0 disables it.
     pub fn set_trade_fee(&mut self, fee: u128) {
+        self.not_migrating();
         self.only_owner();
+        assert!(self.trade_fee_in_bps, "{}", "RUN migrate_trade_fee FIRST");
+        assert!(fee <= 10_000, "{}", "TRADE FEE CANNOT EXCEED 10000 BPS");
         self.trade_fee = fee;
     }
+    /// converts a trade fee stored as a divisor by older code into basis points, rounded down (13 becomes 769).
+    /// runs once, callable while a migration is in progress.
+    pub fn migrate_trade_fee(&mut self) {
+        self.only_owner();
+        assert!(!self.trade_fee_in_bps, "{}", "TRADE FEE IS ALREADY IN BPS");
+        self.trade_fee = 10_000u128.checked_div(self.trade_fee).unwrap_or(0);
+        self.trade_fee_in_bps = true;
+        logger::config_update("trade_fee", self.trade_fee.to_string());
+    }
+    /// platform fee in basis points a sale of the edition would pay right now: primary_sale_fee_bps for a creator's
+    /// first sale when set, otherwise the trade fee. there are no per-collection overrides
+    /// or per-account exemptions yet. UIs should read the fee from here rather than recomputing it.
+    pub fn effective_trade_fee(&self, token_id: TokenId, edition_id: EditionNumber) -> u16 {
+        self._edition_abs_index(token_id, edition_id).unwrap_or_else(|| env::panic(b"EDITION DOES NOT EXIST"));
+        let bps = if self.primary_sale_fee_bps > 0 && self.is_primary_sale(token_id, edition_id) {
+            u128::from(self.primary_sale_fee_bps)
+        } else if self.trade_fee_in_bps {
+            self.trade_fee
+        } else {
+            // a zero divisor in unmigrated state charges nothing, like migrate_trade_fee converts it
+            10_000u128.checked_div(self.trade_fee).unwrap_or(0)
+        };
+        // both setters cap the fee at 10000 bps
+        u16::try_from(bps).unwrap_or_else(|_| env::panic(b"FEE EXCEEDS 10000 BPS"))
+    }
+    /// platform cut of a creator's first sale of each edition in basis points, 0 charges the regular trade fee
+    pub fn set_primary_sale_fee_bps(&mut self, value: u32) {
+        self.not_migrating();
+        self.only_owner();
+        assert!(value <= 10_000, "{}", "PRIMARY SALE FEE CANNOT EXCEED 10000 BPS");
+        self.primary_sale_fee_bps = value;
+        logger::config_update("primary_sale_fee_bps", value.to_string());
+    }
+    pub fn primary_sale_fee_bps(&self) -> u32 {
+        self.primary_sale_fee_bps
+    }
 }
@@ -1,10 +1,10 @@
-use near_sdk::{env, AccountId, serde_json::json, Balance};
-use crate::types::{TokenId, AccountIdHash, EditionNumber, TokenPrice, CollectionId};
-use crate::model::{Metadata, Token, Edition, Collection, Bid};
+use near_sdk::{env, AccountId, serde_json::json, serde_json::Value, Balance};
+use crate::types::{TokenId, EditionNumber, CollectionId};
+use crate::model::{Metadata, Edition, Collection, Bid, Auction, SealedAuction, ConfigSnapshot};
 use crate::TransferReason;
 
 // new token
-pub(crate) fn log_mint(metadata: Metadata, token_id: TokenId, owner: AccountId) {
+pub(crate) fn log_mint(metadata: Metadata, token_id: TokenId) {
     env::log(
         json!({
             "type": "Metadata".to_string(),
@@ -24,6 +24,10 @@ pub(crate) fn log_mint(metadata: Metadata, token_id: TokenId, owner: AccountId)
                 "editions": metadata.editions,
                 "date": metadata.date,
                 "tags": metadata.tags,
+                "media_type": metadata.media_type,
+                "width": metadata.width,
+                "height": metadata.height,
+                "duration_ms": metadata.duration_ms,
                 "token_id": token_id
 			}
 		})
@@ -62,6 +66,8 @@ pub(crate) fn log_collection(collection: Collection, collection_id: CollectionId
                     "thumbnail": collection.thumbnail,
                     "creator": collection.creator,
                     "minters": collection.minters,
+                    "trade_start": collection.trade_start.map(|start| start.to_string()),
+                    "trade_end": collection.trade_end.map(|end| end.to_string()),
                     "collection_id": collection_id as i32
 			}
 		})
@@ -208,6 +214,45 @@ pub(crate) fn execute_offer(bid: Bid, idx: u64, token_id: TokenId, edition_id: u
     );
 }
 
+pub(crate) fn collection_offer(collection_id: CollectionId, idx: u64, bid: Bid, action: &str) {
+    env::log(
+        json!({
+            "type": "CollectionOffer".to_string(),
+            "action": action,
+            "cap_id": format!("cof_{}_{}", collection_id, idx),
+			"params": {
+                    "bidder": bid.bidder,
+                    "amount": bid.amount.to_string(),
+                    "collection_id": collection_id,
+                    "date": bid.date,
+                    "executed": bid.executed,
+                    "idx": idx
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+pub(crate) fn nft_transfer(token_id: &str, old_owner_id: AccountId, new_owner_id: AccountId, memo: Option<String>) {
+    env::log(
+        json!({
+            "type": "NftTransfer".to_string(),
+            "action": "insert",
+            "cap_id": format!("nft_{}", token_id),
+			"params": {
+                    "token_id": token_id,
+                    "old_owner_id": old_owner_id,
+                    "new_owner_id": new_owner_id,
+                    "memo": memo,
+                    "date": env::block_timestamp().to_string()
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
 pub(crate) fn minter_added(minter: AccountId) {
     env::log(
         json!({
@@ -240,6 +285,197 @@ pub(crate) fn minter_removed(minter: AccountId) {
     );
 }
 
+pub(crate) fn token_pause(token_id: TokenId, paused: bool) {
+    env::log(
+        json!({
+            "type": "TokenPause".to_string(),
+            "action": "update",
+            "cap_id": format!("tp_{}", token_id),
+			"params": {
+                    "token_id": token_id,
+                    "paused": paused
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+pub(crate) fn denylist_update(account: AccountId, denied: bool) {
+    env::log(
+        json!({
+            "type": "Denylist".to_string(),
+            "action": "update",
+            "cap_id": format!("deny_{}", account),
+			"params": {
+                    "account": account,
+                    "denied": denied
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+pub(crate) fn force_unlock(token_id: TokenId, edition_id: u64, idx: u64, by: AccountId) {
+    env::log(
+        json!({
+            "type": "ForceUnlock".to_string(),
+            "action": "insert",
+            "cap_id": format!("unlock_{}_{}", idx, env::block_timestamp()),
+			"params": {
+                    "token_id": token_id,
+                    "edition_id": edition_id,
+                    "edition_index": idx,
+                    "by": by,
+                    "date": env::block_timestamp().to_string()
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+pub(crate) fn contract_initialized(config: &ConfigSnapshot) {
+    env::log(
+        json!({
+            "type": "ContractInitialized".to_string(),
+            "action": "insert",
+            "cap_id": "contract".to_string(),
+			"params": {
+                    "config": config,
+                    "date": env::block_timestamp().to_string()
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+pub(crate) fn config_update(key: &str, value: String) {
+    env::log(
+        json!({
+            "type": "Config".to_string(),
+            "action": "update",
+            "cap_id": format!("cfg_{}", key),
+			"params": {
+                    "key": key,
+                    "value": value,
+                    "date": env::block_timestamp().to_string()
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn sale_settled(token_id: TokenId, edition_id: u64, seller: AccountId, buyer: AccountId, price: Balance, platform_fee: Balance, royalties: Vec<(AccountId, Balance)>, seller_proceeds: Balance) {
+    let royalties: Vec<(AccountId, String)> = royalties.into_iter().map(|(account, amount)| (account, amount.to_string())).collect();
+    env::log(
+        json!({
+            "type": "SaleSettled".to_string(),
+            "action": "insert",
+            "cap_id": format!("sale_{}_{}_{}", token_id, edition_id, env::block_timestamp()),
+			"params": {
+                    "token_id": token_id,
+                    "edition_id": edition_id,
+                    "seller": seller,
+                    "buyer": buyer,
+                    "price": price.to_string(),
+                    "platform_fee": platform_fee.to_string(),
+                    "royalties": royalties,
+                    "seller_proceeds": seller_proceeds.to_string(),
+                    "date": env::block_timestamp().to_string()
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+pub(crate) fn earnings_update(account: AccountId, balance: Balance) {
+    env::log(
+        json!({
+            "type": "Earnings".to_string(),
+            "action": "update",
+            "cap_id": format!("earn_{}", account),
+			"params": {
+                    "account": account,
+                    "balance": balance.to_string()
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+pub(crate) fn royalty_prepayment(token_id: TokenId, edition_id: EditionNumber, payer: AccountId, amount: Balance) {
+    env::log(
+        json!({
+            "type": "RoyaltyPrepayment".to_string(),
+            "action": "insert",
+            "cap_id": format!("{}:{}", token_id, edition_id),
+			"params": {
+                    "token_id": token_id.to_string(),
+                    "edition_id": edition_id.to_string(),
+                    "payer": payer,
+                    "amount": amount.to_string()
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+pub(crate) fn token_set(set_id: u64, token_ids: Vec<TokenId>) {
+    env::log(
+        json!({
+            "type": "TokenSet".to_string(),
+            "action": "insert",
+            "cap_id": format!("set_{}", set_id),
+			"params": {
+                    "set_id": set_id,
+                    "token_ids": token_ids
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+pub(crate) fn creator_verification(account: AccountId, verified: bool) {
+    env::log(
+        json!({
+            "type": "VerifiedCreator".to_string(),
+            "action": "update",
+            "cap_id": format!("vc_{}", account),
+			"params": {
+                    "account": account,
+                    "verified": verified
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+pub(crate) fn accepted_ft_update(ft: AccountId, decimals: Option<u8>) {
+    env::log(
+        json!({
+            "type": "AcceptedFt".to_string(),
+            "action": if decimals.is_some() { "write" } else { "delete" },
+            "cap_id": format!("ft_{}", ft),
+			"params": {
+                    "ft": ft,
+                    "decimals": decimals
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
 pub(crate) fn insert_activity(token_id: TokenId, edition_id: u64, event_name: String, target: String, related: AccountId) {
     env::log(
         json!({
@@ -261,7 +497,71 @@ pub(crate) fn insert_activity(token_id: TokenId, edition_id: u64, event_name: St
     );
 }
 
-pub(crate) fn burn(token_id: TokenId, edition_id: u64, to_burn_idx: u64, burner: AccountId) {
+/// insert_activity for a transfer, `reason` tells sales, accepted offers and plain transfers apart
+pub(crate) fn transfer_activity(token_id: TokenId, edition_id: u64, reason: &str, target: String, related: AccountId) {
+    env::log(
+        json!({
+            "type": "Activity".to_string(),
+            "action": "insert",
+            "cap_id": format!("act_{}_{}", token_id, edition_id),
+			"params": {
+			    "token_id":token_id,
+			    "edition_id": edition_id,
+                "event_name": "Transfer",
+                "reason": reason,
+                "from": env::predecessor_account_id(),
+                "target": target,
+                "related" : related,
+                "date": env::block_timestamp()
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+pub(crate) fn marketplace_batch_update(token_id: TokenId, event_name: String, listings: Vec<(u64, Balance)>, related: AccountId) {
+    let listings: Vec<(u64, String)> = listings.into_iter().map(|(edition_id, price)| (edition_id, price.to_string())).collect();
+    env::log(
+        json!({
+            "type": "Activity".to_string(),
+            "action": "insert",
+            "cap_id": format!("act_{}", token_id),
+			"params": {
+			    "token_id": token_id,
+			    "listings": listings,
+                "event_name": event_name,
+                "from": env::predecessor_account_id(),
+                "related" : related,
+                "date": env::block_timestamp()
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+pub(crate) fn transfer_batch(token_id: TokenId, event_name: String, edition_numbers: Vec<EditionNumber>, related: AccountId) {
+    env::log(
+        json!({
+            "type": "Activity".to_string(),
+            "action": "insert",
+            "cap_id": format!("act_{}", token_id),
+			"params": {
+			    "token_id": token_id,
+			    "edition_numbers": edition_numbers,
+                "event_name": event_name,
+                "from": env::predecessor_account_id(),
+                "related" : related,
+                "date": env::block_timestamp()
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+pub(crate) fn burn(token_id: TokenId, edition_id: u64, to_burn_idx: u64, owner: AccountId, burner: AccountId) {
     env::log(
         json!({
             "type": "Edition".to_string(),
@@ -285,8 +585,8 @@ pub(crate) fn burn(token_id: TokenId, edition_id: u64, to_burn_idx: u64, burner:
 			    "token_id":token_id,
 			    "edition_id": edition_id,
                 "event_name": "Burn",
-                "target": env::predecessor_account_id(),
-                "related" : env::predecessor_account_id(),
+                "target": owner,
+                "related" : burner,
                 "date": env::block_timestamp()
 			}
 		})
@@ -345,3 +645,211 @@ pub(crate) fn edition_allowance(token_id:TokenId, edition_number: u64, idx:u64,
     );
 }
 
+pub(crate) fn edition_shares(token_id: TokenId, edition_number: u64, idx: u64, shares: Vec<(AccountId, u16)>){
+    env::log(
+        json!({
+            "type": "Shares".to_string(),
+            "action": "update",
+            "cap_id": format!("sh_{}", idx),
+			"params": {
+                    "token_id": token_id,
+                    "edition_number": edition_number,
+                    "shares": shares
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+pub(crate) fn auction_started(token_id: TokenId, edition_id: u64, auction: &Auction){
+    env::log(
+        json!({
+            "type": "Auction".to_string(),
+            "action": "insert",
+            "cap_id": format!("auc_{}_{}", token_id, edition_id),
+			"params": {
+                    "event_name": "AuctionStarted",
+                    "token_id": token_id,
+                    "edition_id": edition_id,
+                    "seller": auction.seller,
+                    "reserve": auction.reserve.to_string(),
+                    "end": auction.end.to_string(),
+                    "highest_bid": auction.highest_bid.to_string(),
+                    "bidder": auction.highest_bidder,
+                    "date": env::block_timestamp().to_string()
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+pub(crate) fn auction_bid(token_id: TokenId, edition_id: u64, auction: &Auction){
+    env::log(
+        json!({
+            "type": "Auction".to_string(),
+            "action": "update",
+            "cap_id": format!("auc_{}_{}", token_id, edition_id),
+			"params": {
+                    "event_name": "AuctionBid",
+                    "token_id": token_id,
+                    "edition_id": edition_id,
+                    "seller": auction.seller,
+                    "reserve": auction.reserve.to_string(),
+                    "end": auction.end.to_string(),
+                    "highest_bid": auction.highest_bid.to_string(),
+                    "bidder": auction.highest_bidder,
+                    "date": env::block_timestamp().to_string()
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+pub(crate) fn auction_settled(token_id: TokenId, edition_id: u64, auction: &Auction){
+    env::log(
+        json!({
+            "type": "Auction".to_string(),
+            "action": "update",
+            "cap_id": format!("auc_{}_{}", token_id, edition_id),
+			"params": {
+                    "event_name": "AuctionSettled",
+                    "token_id": token_id,
+                    "edition_id": edition_id,
+                    "seller": auction.seller,
+                    "reserve": auction.reserve.to_string(),
+                    "end": auction.end.to_string(),
+                    "highest_bid": auction.highest_bid.to_string(),
+                    "bidder": auction.highest_bidder,
+                    "date": env::block_timestamp().to_string()
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+pub(crate) fn auction_cancelled(token_id: TokenId, edition_id: u64, auction: &Auction){
+    env::log(
+        json!({
+            "type": "Auction".to_string(),
+            "action": "update",
+            "cap_id": format!("auc_{}_{}", token_id, edition_id),
+			"params": {
+                    "event_name": "AuctionCancelled",
+                    "token_id": token_id,
+                    "edition_id": edition_id,
+                    "seller": auction.seller,
+                    "reserve": auction.reserve.to_string(),
+                    "end": auction.end.to_string(),
+                    "highest_bid": auction.highest_bid.to_string(),
+                    "bidder": auction.highest_bidder,
+                    "date": env::block_timestamp().to_string()
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+pub(crate) fn burn_allowance(token_id:TokenId, edition_number: u64, idx:u64, allowed: Vec<AccountId>){
+    env::log(
+        json!({
+            "type": "BurnAllowance".to_string(),
+            "action": "update",
+            "cap_id": format!("ballow_{}", idx),
+			"params": {
+                    "token_id": token_id,
+                    "edition_number": edition_number,
+                    "allowed": allowed
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+pub(crate) fn sealed_auction(token_id: TokenId, edition_id: u64, action: &str, event_name: &str, auction: &SealedAuction){
+    env::log(
+        json!({
+            "type": "SealedAuction".to_string(),
+            "action": action,
+            "cap_id": format!("sauc_{}_{}", token_id, edition_id),
+			"params": {
+                    "event_name": event_name,
+                    "token_id": token_id,
+                    "edition_id": edition_id,
+                    "seller": auction.seller,
+                    "reserve": auction.reserve.to_string(),
+                    "commit_end": auction.commit_end.to_string(),
+                    "reveal_end": auction.reveal_end.to_string(),
+                    "highest_bid": auction.highest_bid.to_string(),
+                    "bidder": auction.highest_bidder,
+                    "date": env::block_timestamp().to_string()
+			}
+		})
+            .to_string()
+            .as_bytes()
+    );
+}
+
+/// NEP-297 event line for standard tooling, logged next to the custom logs above which stay as they are.
+/// every nep171_* log is built here so the envelope is in one place.
+fn nep171_event(event: &str, data: Value) -> String {
+    format!("EVENT_JSON:{}", json!({
+        "standard": "nep171",
+        "version": "1.0.0",
+        "event": event,
+        "data": [data]
+    }))
+}
+
+// the "{token_id}::{edition_number}" id nft_token takes
+fn standard_token_id(token_id: TokenId, edition_number: EditionNumber) -> String {
+    format!("{}::{}", token_id, edition_number)
+}
+
+// set when someone other than the owner moved or burned the edition
+fn authorized_id(owner_id: &AccountId) -> Option<AccountId> {
+    let sender = env::predecessor_account_id();
+    if &sender != owner_id { Some(sender) } else { None }
+}
+
+pub(crate) fn nep171_mint(owner_id: AccountId, token_id: TokenId, editions: EditionNumber) {
+    env::log(nft_mint_event(owner_id, token_id, editions).as_bytes());
+}
+
+pub(crate) fn nep171_transfer(old_owner_id: AccountId, new_owner_id: AccountId, token_id: TokenId, edition_number: EditionNumber) {
+    env::log(nft_transfer_event(old_owner_id, new_owner_id, token_id, edition_number).as_bytes());
+}
+
+pub(crate) fn nep171_burn(owner_id: AccountId, token_id: TokenId, edition_number: EditionNumber) {
+    env::log(nft_burn_event(owner_id, token_id, edition_number).as_bytes());
+}
+
+// the event lines the nep171_* functions log, kept apart so tests can read them
+pub(crate) fn nft_mint_event(owner_id: AccountId, token_id: TokenId, editions: EditionNumber) -> String {
+    let token_ids: Vec<String> = (1..=editions).map(|edition| standard_token_id(token_id, edition)).collect();
+    nep171_event("nft_mint", json!({
+        "owner_id": owner_id,
+        "token_ids": token_ids
+    }))
+}
+
+pub(crate) fn nft_transfer_event(old_owner_id: AccountId, new_owner_id: AccountId, token_id: TokenId, edition_number: EditionNumber) -> String {
+    nep171_event("nft_transfer", json!({
+        "authorized_id": authorized_id(&old_owner_id),
+        "old_owner_id": old_owner_id,
+        "new_owner_id": new_owner_id,
+        "token_ids": [standard_token_id(token_id, edition_number)]
+    }))
+}
+
+pub(crate) fn nft_burn_event(owner_id: AccountId, token_id: TokenId, edition_number: EditionNumber) -> String {
+    nep171_event("nft_burn", json!({
+        "authorized_id": authorized_id(&owner_id),
+        "owner_id": owner_id,
+        "token_ids": [standard_token_id(token_id, edition_number)]
+    }))
+}
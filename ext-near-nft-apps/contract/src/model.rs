@@ -1,10 +1,8 @@
-#[path = "./types.rs"]
-mod types;
-
-use types::{TokenId, TokenPrice, CollectionId, AccountIdHash, Allow, Fee, EditionNumber};
+use crate::types::{TokenId, CollectionId, EditionNumber};
 use serde::{Deserialize, Serialize};
 use borsh::{BorshDeserialize, BorshSerialize};
-use near_sdk::{env, AccountId, near_bindgen, Balance};
+use near_sdk::{AccountId, near_bindgen, Balance};
+use crate::EditionState;
 
 
 #[near_bindgen]
@@ -16,15 +14,100 @@ pub struct Collection {
     pub thumbnail: String,
     pub creator: AccountId,
     pub minters: Vec<AccountId>,
+    // optional trading window, buys and offers on the collection's editions are rejected outside of it
+    #[serde(default)]
+    pub trade_start: Option<u64>,
+    #[serde(default)]
+    pub trade_end: Option<u64>,
+    // royalty recipients in basis points of the seller's proceeds, copied to each token at mint.
+    // empty falls back to the token's creator royalty
+    #[serde(default)]
+    pub royalties: Vec<(AccountId, u32)>,
+}
+
+// stored layout of Collection before trade windows and royalties, only read by migrate_collections
+#[derive(BorshDeserialize)]
+pub struct LegacyCollection {
+    pub name: String,
+    pub description: String,
+    pub date: String,
+    pub thumbnail: String,
+    pub creator: AccountId,
+    pub minters: Vec<AccountId>,
+}
+
+impl From<LegacyCollection> for Collection {
+    fn from(legacy: LegacyCollection) -> Self {
+        Collection {
+            name: legacy.name,
+            description: legacy.description,
+            date: legacy.date,
+            thumbnail: legacy.thumbnail,
+            creator: legacy.creator,
+            minters: legacy.minters,
+            trade_start: None,
+            trade_end: None,
+            royalties: Vec::new(),
+        }
+    }
 }
 
 
 #[derive(BorshDeserialize, BorshSerialize, Clone, Serialize, Deserialize)]
 pub struct Bid {
+    pub bidder: AccountId,
+    pub amount: Balance,
+    pub storage_fee: Balance,
+    pub date: String,
+    pub executed: bool,
+    // ns timestamp after which the bid can't be accepted anymore, 0 never expires
+    #[serde(default)]
+    pub expires_at: u64,
+    // set with executed when the bid was cancelled or refunded instead of accepted
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+// stored layout of Bid before the storage fee, expiry and cancelled flag, only read by migrate_offers.
+// amount is the whole deposit, the storage reservation was kept out of it on cancel.
+// closed bids had their bidder overwritten, "::" for a cancelled bid and "" with amount 0 for an accepted one.
+#[derive(BorshDeserialize)]
+pub struct LegacyBid {
     pub bidder: AccountId,
     pub amount: Balance,
     pub date: String,
-    pub executed: bool
+    pub executed: bool,
+}
+
+impl LegacyBid {
+    /// splits `storage_fee` off an open bid's deposit, closed bids have nothing left to refund
+    pub fn into_bid(self, storage_fee: Balance) -> Bid {
+        let storage_fee = if self.executed { 0 } else { std::cmp::min(storage_fee, self.amount) };
+        let cancelled = self.executed && self.bidder == "::";
+        Bid {
+            bidder: self.bidder,
+            amount: self.amount - storage_fee,
+            storage_fee,
+            date: self.date,
+            executed: self.executed,
+            expires_at: 0,
+            cancelled,
+        }
+    }
+}
+
+// borsh layout of a near_sdk Vector handle, lets migrations get at elements that no longer deserialize
+#[derive(BorshDeserialize)]
+pub struct StoredVector {
+    pub len: u64,
+    pub prefix: Vec<u8>,
+}
+
+// leading field of a near_sdk UnorderedSet handle, tells migrations which storage prefix a set lives under.
+// read with BorshDeserialize::deserialize, the elements vector handle after it is left unread
+#[derive(BorshDeserialize)]
+pub struct StoredSet {
+    pub index_prefix: Vec<u8>,
 }
 
 
@@ -48,6 +131,36 @@ pub struct Edition {
 
 #[derive(BorshDeserialize, BorshSerialize, Clone, Serialize, Deserialize)]
 pub struct Metadata {
+    pub name: String,
+    pub collection_id: CollectionId,
+    pub creator: String,
+    pub description: String,
+    pub thumbnail: String,
+    pub main: String,
+    pub nft_type: String,
+    pub file: String,
+    pub external_link: String,
+    // basis points of the seller's proceeds, a divisor for tokens migrate_royalties hasn't converted yet
+    pub royalty: u32,
+    pub editions: EditionNumber,
+    pub date: String,
+    pub tags: Vec<String>,
+    // optional rendering hints, None for tokens minted before they existed
+    #[serde(default)]
+    pub media_type: Option<String>,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+}
+
+
+
+// stored layout of Metadata before the media fields, only read by migrate_metadata
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct LegacyMetadata {
     pub name: String,
     pub collection_id: CollectionId,
     pub creator: String,
@@ -62,3 +175,215 @@ pub struct Metadata {
     pub date: String,
     pub tags: Vec<String>
 }
+
+impl From<LegacyMetadata> for Metadata {
+    fn from(legacy: LegacyMetadata) -> Self {
+        Metadata {
+            name: legacy.name,
+            collection_id: legacy.collection_id,
+            creator: legacy.creator,
+            description: legacy.description,
+            thumbnail: legacy.thumbnail,
+            main: legacy.main,
+            nft_type: legacy.nft_type,
+            file: legacy.file,
+            external_link: legacy.external_link,
+            royalty: legacy.royalty,
+            editions: legacy.editions,
+            date: legacy.date,
+            tags: legacy.tags,
+            media_type: None,
+            width: None,
+            height: None,
+            duration_ms: None,
+        }
+    }
+}
+
+
+
+#[derive(Serialize, Deserialize)]
+pub struct EditionExport {
+    pub token_id: TokenId,
+    pub edition_number: EditionNumber,
+    pub edition_index: u64,
+    pub edition: Option<Edition>,
+    pub state: EditionState,
+    pub price: Option<String>,
+    pub offers: Vec<Bid>,
+    pub allowances: Vec<AccountId>,
+    pub shares: Vec<(AccountId, u16)>,
+    pub last_sale: Option<String>,
+    pub creator_verified: bool,
+}
+
+
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Serialize, Deserialize)]
+pub struct Auction {
+    pub seller: AccountId,
+    pub reserve: Balance,
+    pub end: u64,
+    pub highest_bid: Balance,
+    pub highest_bidder: Option<AccountId>,
+}
+
+
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Serialize, Deserialize)]
+pub struct SealedAuction {
+    pub seller: AccountId,
+    pub reserve: Balance,
+    pub commit_end: u64,
+    pub reveal_end: u64,
+    pub highest_bid: Balance,
+    pub highest_bidder: Option<AccountId>,
+    // deposits of commits that have not been revealed yet, forfeited to the fee receiver on settlement
+    pub unrevealed: Balance,
+}
+
+
+
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct SealedCommit {
+    pub hash: Vec<u8>,
+    pub deposit: Balance,
+}
+
+
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Serialize, Deserialize)]
+pub struct ActivityRecord {
+    pub token_id: TokenId,
+    pub edition_id: EditionNumber,
+    pub event_name: String,
+    pub from: AccountId,
+    pub to: AccountId,
+    pub amount: Balance,
+    pub date: String,
+}
+
+
+
+#[derive(Serialize, Deserialize)]
+pub struct AllowanceInfo {
+    pub account: AccountId,
+    pub expires_at: Option<u64>,
+    pub can_transfer: bool,
+    pub can_burn: bool,
+}
+
+
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Serialize, Deserialize)]
+pub struct FtOffer {
+    pub bidder: AccountId,
+    pub ft: AccountId,
+    pub amount: Balance,
+}
+
+// msg of an ft_transfer_call to this contract, buys the listed edition or bids on it when `offer` is set
+#[derive(Serialize, Deserialize)]
+pub struct FtTransferMsg {
+    pub token_id: TokenId,
+    pub edition_id: EditionNumber,
+    #[serde(default)]
+    pub offer: bool,
+}
+
+
+
+#[derive(Serialize, Deserialize)]
+pub struct InvariantReport {
+    pub supply_consistent: bool,
+    pub editions_consistent: bool,
+    pub listings_consistent: bool,
+    pub scanned_editions: u64,
+    // first absolute edition index the next page starts at
+    pub next_index: u64,
+}
+
+
+
+#[derive(Serialize, Deserialize)]
+pub struct EditionCard {
+    pub token_id: TokenId,
+    pub edition_number: EditionNumber,
+    pub owner: AccountId,
+    pub state: EditionState,
+    pub listed: bool,
+    pub price: Option<String>,
+}
+
+
+
+#[derive(Serialize, Deserialize)]
+pub struct ClaimableBreakdown {
+    pub offers: String,
+    pub earnings: String,
+    pub total: String,
+}
+
+
+
+#[derive(Serialize, Deserialize)]
+pub struct AccountSummary {
+    pub editions_owned: u64,
+    // spent on buys, accepted offers and won auctions
+    pub collector_volume: String,
+    pub earnings: String,
+    pub denied: bool,
+}
+
+
+
+#[derive(Serialize, Deserialize)]
+pub struct ConfigSnapshot {
+    pub owner_id: AccountId,
+    pub fee_receiver: AccountId,
+    pub mint_storage_fee: String,
+    pub edition_storage_fee: String,
+    pub create_collection_fee: String,
+    pub trade_fee: String,
+    pub like_fee: String,
+    pub min_royalty_bps: u32,
+    pub outbid_fee_bps: u32,
+    pub max_name_length: u8,
+    pub max_description_length: u8,
+    pub max_editions: u8,
+    pub max_external_link: u8,
+    pub strict_deposits: bool,
+    pub emergency_stop: bool,
+    pub otc_royalty_required: bool,
+    pub listing_grace_ns: u64,
+    pub listing_fee: String,
+    pub mint_rate_limit: u64,
+    pub mint_rate_window: u64,
+    pub primary_sale_fee_bps: u32,
+    pub minter_admin: Option<AccountId>,
+    pub paused: bool,
+    pub legacy_api_enabled: bool,
+    pub min_listing_price: String,
+    pub max_listing_price: String,
+}
+
+
+
+// NEP-177 shaped metadata of a single edition, built from the token's Metadata for standard wallets and explorers
+#[derive(Serialize, Deserialize)]
+pub struct TokenMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub media: Option<String>,
+    pub copies: Option<u64>,
+    pub issued_at: Option<String>,
+}
+
+
+
+#[derive(Serialize, Deserialize)]
+pub struct JsonToken {
+    pub token_id: String,
+    pub owner_id: AccountId,
+    pub metadata: TokenMetadata,
+}
@@ -0,0 +1,156 @@
+use near_sdk::{env, near_bindgen, AccountId, Balance, Promise};
+use near_sdk::json_types::U128;
+use serde_json::json;
+use crate::types::{TokenId, EditionNumber};
+use crate::model::{FtOffer, FtTransferMsg};
+use crate::{logger, NonFungibleToken, PAUSED_ERR, FRACTIONALIZED, EVENT_OFFER, EVENT_CANCEL_OFFER, EVENT_ACCEPT_OFFER, REASON_OFFER_ACCEPT};
+
+// gas handed to each ft_transfer paying out an FT sale or refunding an FT offer
+const GAS_FOR_FT_TRANSFER: u64 = 10_000_000_000_000;
+static MAX_FT_OFFERS: usize = 50;
+
+/// payments in the fungible tokens registered with add_accepted_ft. listings priced in an FT are bought, and offers
+/// in an FT are escrowed, by ft_transfer_call-ing this contract. payouts go out as ft_transfer calls.
+/// https://nomicon.io/Standards/Tokens/FungibleToken/Core
+#[near_bindgen]
+impl NonFungibleToken {
+    /// lists an edition priced in `ft`, which has to be an accepted FT. pays the listing fee in NEAR like set_price.
+    #[payable]
+    pub fn set_price_in_ft(&mut self, token_id: TokenId, edition_id: EditionNumber, ft: AccountId, price: String) {
+        self.not_migrating();
+        self.only_token_owner(token_id, edition_id);
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        assert!(!self.edition_shares.contains_key(&idx), "{}", FRACTIONALIZED);
+        let price = crate::parse_balance(&price);
+        assert!(env::attached_deposit() >= self.listing_fee, "{} {}", "DEPOSIT NOT ENOUGH", self.listing_fee);
+        self._set_price(token_id, edition_id, price, Some(ft));
+        self._settle_deposit(self.listing_fee);
+    }
+    pub fn listing_ft(&self, token_id: TokenId, edition_id: EditionNumber) -> Option<AccountId> {
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        self.marketplace.get(&idx).and(self.listing_fts.get(&idx))
+    }
+
+    /// NEP-141 receiver. buys the edition in `msg` when it's listed in the sending FT, or escrows the amount as an
+    /// offer on it with "offer": true. returns the part of `amount` the FT contract refunds to the sender.
+    pub fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> U128 {
+        self.not_migrating();
+        let ft = env::predecessor_account_id();
+        self._only_accepted_ft(&ft);
+        self.not_denied(&sender_id);
+        let request: FtTransferMsg = serde_json::from_str(&msg).unwrap_or_else(|_| env::panic(b"INVALID FT TRANSFER MSG"));
+        let idx = self._edition_abs_index(request.token_id, request.edition_id).unwrap_or_else(|| env::panic(b"EDITION DOES NOT EXIST"));
+        if request.offer {
+            self._ft_offer(ft, sender_id, request.token_id, request.edition_id, idx, amount.0);
+            return U128(0);
+        }
+        let price = self.marketplace.get(&idx).unwrap_or_else(|| env::panic(b"EDITION IS NOT LISTED"));
+        assert!(self.listing_fts.get(&idx) == Some(ft.clone()), "{}", "LISTING IS NOT PRICED IN THIS FT");
+        assert!(amount.0 >= price, "{}", "DEPOSIT NOT ENOUGH");
+        self._buy(request.token_id, request.edition_id, sender_id, price, Some(ft));
+        U128(amount.0 - price)
+    }
+
+    fn _ft_offer(&mut self, ft: AccountId, bidder: AccountId, token_id: TokenId, edition_id: EditionNumber, idx: u64, amount: Balance) {
+        assert!(!self.paused, "{}", PAUSED_ERR);
+        self.not_token_paused(token_id);
+        self._check_trade_window(token_id);
+        let edition = self.editions.get(&idx).unwrap_or_else(|| env::panic(b"EDITION DOES NOT EXIST"));
+        assert!(edition.edition_owner != bidder, "{}", "CANNOT OFFER ON YOUR OWN TOKEN");
+        assert!(amount > 0, "{}", "OFFER MUST BE POSITIVE");
+        let mut offers = self.ft_offers.get(&idx).unwrap_or_default();
+        assert!(offers.len() < MAX_FT_OFFERS, "{}", "TOO MANY FT OFFERS ON THIS EDITION");
+        offers.push(FtOffer { bidder: bidder.clone(), ft, amount });
+        self.ft_offers.insert(&idx, &offers);
+        logger::insert_activity(token_id, edition_id, EVENT_OFFER.to_string(), amount.to_string(), bidder);
+    }
+    pub fn ft_offers(&self, token_id: TokenId, edition_id: EditionNumber) -> Vec<FtOffer> {
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        self.ft_offers.get(&idx).unwrap_or_default()
+    }
+
+    /// sells the edition to the FT offer at `offer_index`, paid out like a sale of a listing in that FT
+    pub fn accept_ft_offer(&mut self, token_id: TokenId, edition_id: EditionNumber, offer_index: u64) {
+        self.not_migrating();
+        assert!(!self.paused, "{}", PAUSED_ERR);
+        self.not_token_paused(token_id);
+        self._check_trade_window(token_id);
+        self.only_token_owner(token_id, edition_id);
+        let seller = env::predecessor_account_id();
+        self.not_denied(&seller);
+        let token = self.tokens.get(&token_id).unwrap();
+        let idx = token.edition_index + edition_id;
+        let mut offers = self.ft_offers.get(&idx).unwrap_or_default();
+        assert!((offer_index as usize) < offers.len(), "{}", "OFFER DOES NOT EXIST");
+        let offer = offers.remove(offer_index as usize);
+        self.not_denied(&offer.bidder);
+        self._only_accepted_ft(&offer.ft);
+        self.ft_offers.insert(&idx, &offers);
+        self._use_resale_approval(token.metadata, idx, &seller, &offer.bidder);
+        self._check_min_hold(token_id, idx, &seller);
+        let shares = self.edition_shares.remove(&idx);
+        self._internal_transfer(seller.clone(), offer.bidder.clone(), token_id, edition_id, idx, REASON_OFFER_ACCEPT);
+        logger::insert_activity(token_id, edition_id, EVENT_ACCEPT_OFFER.to_string(), offer.amount.to_string(), offer.bidder.clone());
+        self._record_collection_activity(token_id, edition_id, EVENT_ACCEPT_OFFER, offer.amount, seller.clone(), offer.bidder.clone());
+        self._distribute_ft_sale(offer.ft, token.metadata, edition_id, seller, offer.bidder, offer.amount, shares);
+    }
+
+    /// the bidder takes back their FT offer, refunded even when the FT was removed from the accepted ones since
+    pub fn cancel_ft_offer(&mut self, token_id: TokenId, edition_id: EditionNumber, offer_index: u64) {
+        self.not_migrating();
+        let idx = self.tokens.get(&token_id).unwrap().edition_index + edition_id;
+        let mut offers = self.ft_offers.get(&idx).unwrap_or_default();
+        assert!((offer_index as usize) < offers.len(), "{}", "OFFER DOES NOT EXIST");
+        assert_eq!(offers[offer_index as usize].bidder, env::predecessor_account_id(), "{}", "ONLY THE BIDDER CAN CANCEL THE OFFER");
+        let offer = offers.remove(offer_index as usize);
+        self.ft_offers.insert(&idx, &offers);
+        self._ft_transfer(&offer.ft, offer.bidder.clone(), offer.amount);
+        logger::insert_activity(token_id, edition_id, EVENT_CANCEL_OFFER.to_string(), offer.amount.to_string(), offer.bidder);
+    }
+
+    /// pays out an edition sold for `amount` of `ft` the way _distribute_sale does in NEAR: the platform fee first,
+    /// royalties on the rest, then the seller and any co-owners. royalties are always paid out directly in the FT,
+    /// accrued earnings and prepaid royalties only hold NEAR.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn _distribute_ft_sale(&mut self, ft: AccountId, metadata_id: TokenId, edition_id: EditionNumber, seller: AccountId, buyer: AccountId, amount: Balance, shares: Option<Vec<(AccountId, u16)>>) {
+        let md = self.metadata.get(&metadata_id).unwrap();
+        let edition_idx = self.tokens.get(&metadata_id).unwrap().edition_index + edition_id;
+        let platform_fee = self._platform_fee(self._is_primary_sale(&md, edition_idx, &seller), amount);
+        self.first_sale_done.insert(&edition_idx, &true);
+        let rest = self._checked_payout(amount, platform_fee);
+        self._ft_transfer(&ft, self.fee_receiver.clone(), platform_fee);
+        self._refund_prepaid_royalty(edition_idx);
+        let mut sellers = rest;
+        let royalties = if md.creator != seller { self._royalty_split(metadata_id, &md, rest) } else { Vec::new() };
+        for (account, royalty_fee) in royalties.clone() {
+            sellers = self._checked_payout(sellers, royalty_fee);
+            self._ft_transfer(&ft, account, royalty_fee);
+        }
+        logger::sale_settled(metadata_id, edition_id, seller.clone(), buyer, amount, platform_fee, royalties, sellers);
+        let mut remaining = sellers;
+        for (account, bps) in shares.unwrap_or_default() {
+            let part = sellers * u128::from(bps) / 10_000;
+            remaining -= part;
+            self._ft_transfer(&ft, account, part);
+        }
+        self._ft_transfer(&ft, seller, remaining);
+    }
+
+    pub(crate) fn _only_accepted_ft(&self, ft: &AccountId) {
+        assert!(self.accepted_fts.get(ft).is_some(), "{}", "FT NOT ACCEPTED");
+    }
+
+    fn _ft_transfer(&self, ft: &AccountId, receiver_id: AccountId, amount: Balance) {
+        if amount > 0 {
+            Promise::new(ft.clone()).function_call(
+                b"ft_transfer".to_vec(),
+                json!({
+                    "receiver_id": receiver_id,
+                    "amount": U128(amount),
+                }).to_string().into_bytes(),
+                1,
+                GAS_FOR_FT_TRANSFER,
+            );
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use near_sdk::{env, near_bindgen, AccountId};
+use crate::types::{TokenId, EditionNumber};
+use crate::model::{JsonToken, TokenMetadata};
+use crate::{logger, NonFungibleToken, ONLY_ESCROW, EVENT_TRANSFER, REASON_TRANSFER};
+
+/// NEP-171 view of the contract. a standard token is a single edition, its token_id is the
+/// "{token_id}::{edition_number}" string gen_token_x_edition produces.
+/// https://nomicon.io/Standards/Tokens/NonFungibleToken/Core
+#[near_bindgen]
+impl NonFungibleToken {
+    pub fn nft_token(&self, token_id: String) -> Option<JsonToken> {
+        let (id, edition_number) = parse_token_id(&token_id)?;
+        self._json_token(self._edition_abs_index(id, edition_number)?)
+    }
+
+    /// transfers an edition by its standard token id. callable by the owner or an account holding an edition
+    /// allowance, whose approval id has to match when one is given. requires exactly 1 yoctoNEAR attached.
+    #[payable]
+    pub fn nft_transfer(&mut self, receiver_id: AccountId, token_id: String, approval_id: Option<u64>, memo: Option<String>) {
+        self.not_migrating();
+        assert_eq!(env::attached_deposit(), 1, "{}", "REQUIRES ATTACHED DEPOSIT OF EXACTLY 1 YOCTONEAR");
+        let (id, edition_number, index, owner) = self._standard_edition(&token_id);
+        let sender = env::predecessor_account_id();
+        self.not_denied(&sender);
+        self.not_denied(&receiver_id);
+        assert!(sender == owner || self._approval_matches(index, &sender, approval_id), "{}", ONLY_ESCROW);
+        assert!(owner != receiver_id, "{}", "CURRENT AND NEXT OWNER MUST DIFFER");
+        self._internal_transfer(owner.clone(), receiver_id.clone(), id, edition_number, index, REASON_TRANSFER);
+        self._record_collection_activity(id, edition_number, EVENT_TRANSFER, 0, owner.clone(), receiver_id.clone());
+        logger::nft_transfer(&token_id, owner, receiver_id, memo);
+    }
+}
+
+impl NonFungibleToken {
+    /// standard view of the edition at absolute index `index`, None if it doesn't exist or was burned
+    pub(crate) fn _json_token(&self, index: u64) -> Option<JsonToken> {
+        let edition = self.editions.get(&index)?;
+        let token = self.tokens.get(&edition.token_id)?;
+        let md = self.metadata.get(&token.metadata)?;
+        Some(JsonToken {
+            token_id: self.gen_token_x_edition(edition.token_id, edition.edition_number),
+            owner_id: edition.edition_owner,
+            metadata: TokenMetadata {
+                title: Some(format!("{} #{}", md.name, edition.edition_number)),
+                description: Some(md.description),
+                media: Some(md.main),
+                copies: Some(md.editions),
+                issued_at: Some(md.date),
+            },
+        })
+    }
+}
+
+/// splits a "{token_id}::{edition_number}" id, None if it isn't one
+pub(crate) fn parse_token_id(token_id: &str) -> Option<(TokenId, EditionNumber)> {
+    let mut parts = token_id.split("::");
+    let id = parts.next()?.parse().ok()?;
+    let edition_number = parts.next()?.parse().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((id, edition_number))
+}
@@ -0,0 +1,90 @@
+use near_sdk::{env, near_bindgen, AccountId, Promise};
+use serde_json::json;
+use crate::nep171::parse_token_id;
+use crate::types::{TokenId, EditionNumber};
+use crate::{NonFungibleToken, ONLY_TOKEN_OWNER};
+
+// gas handed to the approved account's nft_on_approve
+const GAS_FOR_NFT_ON_APPROVE: u64 = 10_000_000_000_000;
+
+/// NEP-178 approvals on top of the edition allowances, token ids are the NEP-171 "{token_id}::{edition_number}" strings.
+/// every approval gets an id from a per edition counter, so an id that was revoked or replaced never matches again.
+/// https://nomicon.io/Standards/Tokens/NonFungibleToken/ApprovalManagement
+#[near_bindgen]
+impl NonFungibleToken {
+    /// grants `account_id` an allowance on the edition, re-approving an account issues it a new approval id.
+    /// with a msg the approved account's nft_on_approve is called with it.
+    #[payable]
+    pub fn nft_approve(&mut self, token_id: String, account_id: AccountId, msg: Option<String>) -> Option<Promise> {
+        self.not_migrating();
+        assert!(env::attached_deposit() >= 1, "{}", "REQUIRES ATTACHED DEPOSIT OF AT LEAST 1 YOCTONEAR");
+        self.check_valid_account(account_id.clone());
+        self.not_denied(&account_id);
+        let (id, edition_number, index, owner) = self._standard_edition(&token_id);
+        assert_eq!(owner, env::predecessor_account_id(), "{}", ONLY_TOKEN_OWNER);
+        let approval_id = self._approve(id, edition_number, index, &account_id);
+        msg.map(|msg| {
+            Promise::new(account_id).function_call(
+                b"nft_on_approve".to_vec(),
+                json!({
+                    "token_id": token_id,
+                    "owner_id": owner,
+                    "approval_id": approval_id,
+                    "msg": msg,
+                }).to_string().into_bytes(),
+                0,
+                GAS_FOR_NFT_ON_APPROVE,
+            )
+        })
+    }
+
+    #[payable]
+    pub fn nft_revoke(&mut self, token_id: String, account_id: AccountId) {
+        self.not_migrating();
+        assert_eq!(env::attached_deposit(), 1, "{}", "REQUIRES ATTACHED DEPOSIT OF EXACTLY 1 YOCTONEAR");
+        let (id, edition_number, index, owner) = self._standard_edition(&token_id);
+        assert_eq!(owner, env::predecessor_account_id(), "{}", ONLY_TOKEN_OWNER);
+        if self._has_allowance(index, &account_id) {
+            self._revoke(id, edition_number, index, &account_id);
+        }
+    }
+
+    /// revokes every transfer allowance of the edition, burn allowances are left alone
+    #[payable]
+    pub fn nft_revoke_all(&mut self, token_id: String) {
+        self.not_migrating();
+        assert_eq!(env::attached_deposit(), 1, "{}", "REQUIRES ATTACHED DEPOSIT OF EXACTLY 1 YOCTONEAR");
+        let (id, edition_number, index, owner) = self._standard_edition(&token_id);
+        assert_eq!(owner, env::predecessor_account_id(), "{}", ONLY_TOKEN_OWNER);
+        for account in self.edition_allowances.get(&index).unwrap().to_vec() {
+            if self._has_allowance(index, &account) {
+                self._revoke(id, edition_number, index, &account);
+            }
+        }
+    }
+
+    /// true if the account holds a live allowance on the edition, and if given, under `approval_id`
+    pub fn nft_is_approved(&self, token_id: String, approved_account_id: AccountId, approval_id: Option<u64>) -> bool {
+        let index = match parse_token_id(&token_id).and_then(|(id, edition_number)| self._edition_abs_index(id, edition_number)) {
+            Some(index) => index,
+            None => return false,
+        };
+        self._approval_matches(index, &approved_account_id, approval_id)
+    }
+}
+
+impl NonFungibleToken {
+    pub(crate) fn _approval_matches(&self, index: u64, account: &AccountId, approval_id: Option<u64>) -> bool {
+        self._is_allowed(index, account.clone()) && approval_id.is_none_or(|approval_id| {
+            self.approval_ids.get(&self.gen_allowance_key(index, account)) == Some(approval_id)
+        })
+    }
+
+    /// resolves a standard token id to (token_id, edition_number, absolute index, owner), panics if it doesn't exist
+    pub(crate) fn _standard_edition(&self, token_id: &str) -> (TokenId, EditionNumber, u64, AccountId) {
+        let (id, edition_number) = parse_token_id(token_id).unwrap_or_else(|| env::panic(b"INVALID TOKEN ID"));
+        let index = self._edition_abs_index(id, edition_number).unwrap_or_else(|| env::panic(b"TOKEN DOES NOT EXIST"));
+        let owner = self.editions.get(&index).unwrap_or_else(|| env::panic(b"TOKEN DOES NOT EXIST")).edition_owner;
+        (id, edition_number, index, owner)
+    }
+}
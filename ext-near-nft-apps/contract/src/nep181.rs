@@ -0,0 +1,44 @@
+use near_sdk::json_types::U128;
+use near_sdk::{near_bindgen, AccountId};
+use crate::model::JsonToken;
+use crate::NonFungibleToken;
+
+// page size when the caller doesn't pass a limit
+const DEFAULT_NFT_PAGE: u64 = 50;
+
+/// NEP-181 enumeration over editions. from_index is the 0 based position in the global edition index
+/// for nft_tokens and in the owner's edition set for nft_tokens_for_owner.
+/// https://nomicon.io/Standards/Tokens/NonFungibleToken/Enumeration
+#[near_bindgen]
+impl NonFungibleToken {
+    /// every edition minted and not burned
+    pub fn nft_total_supply(&self) -> U128 {
+        U128(u128::from(self.total_editions - self.burned_editions))
+    }
+
+    /// the editions among the `limit` positions starting at `from_index`. burned editions are skipped, so a page
+    /// can come back short, the next one starts at from_index + limit.
+    pub fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<JsonToken> {
+        let start = from_index.map_or(0, |index| index.0 as u64);
+        let end = self.total_editions.min(start.saturating_add(limit.unwrap_or(DEFAULT_NFT_PAGE)));
+        (start.saturating_add(1)..=end)
+            .filter_map(|index| self._json_token(index))
+            .collect()
+    }
+
+    pub fn nft_supply_for_owner(&self, account_id: AccountId) -> U128 {
+        U128(u128::from(self.tokens_by_owner_count(account_id)))
+    }
+
+    pub fn nft_tokens_for_owner(&self, account_id: AccountId, from_index: Option<U128>, limit: Option<u64>) -> Vec<JsonToken> {
+        let owned = match self.account_to_editions.get(&account_id) {
+            Some(owned) => owned,
+            None => return Vec::new(),
+        };
+        let owned = owned.as_vector();
+        let start = from_index.map_or(0, |index| index.0 as u64);
+        (start..owned.len().min(start.saturating_add(limit.unwrap_or(DEFAULT_NFT_PAGE))))
+            .filter_map(|i| self._json_token(owned.get(i)?))
+            .collect()
+    }
+}
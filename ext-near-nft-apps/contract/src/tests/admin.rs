@@ -0,0 +1,445 @@
+use crate::{EditionState, NEP4};
+use super::*;
+
+#[test]
+fn accepted_ft_registry() {
+    let mut contract = setup();
+    contract.add_accepted_ft("usdc.near".to_string(), 6);
+    assert_eq!(contract.accepted_fts(), vec!["usdc.near".to_string()]);
+    assert_eq!(contract.ft_decimals("usdc.near".to_string()), Some(6));
+    assert!(!contract.is_accepted_ft("dai.near".to_string()));
+    contract.remove_accepted_ft("usdc.near".to_string());
+    assert!(contract.accepted_fts().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "Only contract owner can call this method.")]
+fn only_owner_registers_fts() {
+    let mut contract = setup();
+    call_as(ALICE, 0);
+    contract.add_accepted_ft("usdc.near".to_string(), 6);
+}
+
+#[test]
+fn primary_sale_fee_overrides_the_trade_fee_until_the_first_sale() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(OWNER, 0);
+    contract.set_trade_fee(250);
+    contract.set_primary_sale_fee_bps(1_500);
+    assert_eq!(contract.effective_trade_fee(token_id, 1), 1_500);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 1, contract.min_listing_price.to_string());
+    call_as(BOB, contract.min_listing_price);
+    contract.buy(token_id, 1);
+    assert_eq!(contract.effective_trade_fee(token_id, 1), 250);
+}
+
+#[test]
+fn primary_sale_fee_takes_precedence_over_a_higher_trade_fee() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(OWNER, 0);
+    contract.set_trade_fee(250);
+    contract.set_primary_sale_fee_bps(100);
+    assert_eq!(contract.effective_trade_fee(token_id, 1), 100);
+}
+
+#[test]
+fn effective_trade_fee_of_a_zero_legacy_divisor_is_zero() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    contract.trade_fee = 0;
+    contract.trade_fee_in_bps = false;
+    assert_eq!(contract.effective_trade_fee(token_id, 1), 0);
+    contract.trade_fee = 40;
+    assert_eq!(contract.effective_trade_fee(token_id, 1), 250);
+}
+
+const NEAR: Balance = 1_000_000_000_000_000_000_000_000;
+
+/// ALICE sells edition 1 of a fresh token to BOB for `price` with the trade fee set to `bps`
+fn sale_at(contract: &mut NonFungibleToken, bps: u128, price: Balance) {
+    let token_id = mint(contract, 1);
+    call_as(OWNER, 0);
+    contract.set_trade_fee(bps);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 1, price.to_string());
+    call_as(BOB, price);
+    contract.buy(token_id, 1);
+}
+
+#[test]
+fn zero_bps_trade_fee_pays_the_seller_everything() {
+    let mut contract = setup();
+    sale_at(&mut contract, 0, NEAR);
+    assert!(transfers().contains(&(ALICE.to_string(), NEAR)));
+    assert!(!transfers().iter().any(|(account, amount)| account == FEES && *amount > 0));
+}
+
+#[test]
+fn trade_fee_of_250_bps_takes_2_5_percent() {
+    let mut contract = setup();
+    sale_at(&mut contract, 250, NEAR);
+    assert!(transfers().contains(&(FEES.to_string(), NEAR / 40)));
+    assert!(transfers().contains(&(ALICE.to_string(), NEAR - NEAR / 40)));
+}
+
+#[test]
+fn trade_fee_rounds_down_on_odd_amounts() {
+    let mut contract = setup();
+    call_as(OWNER, 0);
+    contract.set_trade_fee(250);
+    // 399 * 250 / 10000 = 9.975
+    assert_eq!(contract._trade_fee_of(399), 9);
+    assert_eq!(contract._trade_fee_of(NEAR + 1), NEAR / 40);
+}
+
+#[test]
+fn migrate_trade_fee_converts_the_divisor_to_bps() {
+    let mut contract = setup();
+    contract.trade_fee = 13;
+    contract.trade_fee_in_bps = false;
+    assert_eq!(contract._trade_fee_of(1_300), 100);
+    call_as(OWNER, 0);
+    contract.migrate_trade_fee();
+    assert_eq!(contract.trade_fee, 769);
+    assert_eq!(contract._trade_fee_of(10_000), 769);
+}
+
+#[test]
+#[should_panic(expected = "TRADE FEE IS ALREADY IN BPS")]
+fn migrate_trade_fee_runs_once() {
+    let mut contract = setup();
+    call_as(OWNER, 0);
+    contract.migrate_trade_fee();
+}
+
+#[test]
+#[should_panic(expected = "RUN migrate_trade_fee FIRST")]
+fn trade_fee_cannot_be_set_before_the_divisor_is_migrated() {
+    let mut contract = setup();
+    // state that still holds the trade fee as a divisor, where 0 would make every sale panic
+    contract.trade_fee_in_bps = false;
+    call_as(OWNER, 0);
+    contract.set_trade_fee(0);
+}
+
+#[test]
+fn trade_fee_accepts_0_to_10000_bps() {
+    let mut contract = setup();
+    call_as(OWNER, 0);
+    contract.set_trade_fee(0);
+    assert_eq!(contract._trade_fee_of(1_000), 0);
+    contract.set_trade_fee(10_000);
+    assert_eq!(contract._trade_fee_of(1_000), 1_000);
+}
+
+#[test]
+#[should_panic(expected = "TRADE FEE CANNOT EXCEED 10000 BPS")]
+fn trade_fee_above_10000_bps_is_rejected() {
+    let mut contract = setup();
+    call_as(OWNER, 0);
+    contract.set_trade_fee(10_001);
+}
+
+#[test]
+fn invariants_hold_after_minting_and_listing() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 1, contract.min_listing_price.to_string());
+    let report = contract.invariants(1, 10);
+    assert!(report.supply_consistent && report.editions_consistent && report.listings_consistent);
+    assert_eq!((report.scanned_editions, report.next_index), (2, 3));
+}
+
+#[test]
+fn invariants_report_a_listed_state_without_a_listing_on_its_page_only() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    let idx = contract.tokens.get(&token_id).unwrap().edition_index + 2;
+    contract.edition_states.insert(&idx, &EditionState::LISTED);
+    let report = contract.invariants(1, 1);
+    assert!(report.listings_consistent);
+    assert_eq!((report.scanned_editions, report.next_index), (1, 2));
+    let report = contract.invariants(report.next_index, 1);
+    assert!(!report.listings_consistent);
+    assert!(report.supply_consistent && report.editions_consistent);
+}
+
+fn deny(contract: &mut NonFungibleToken, account: &str) {
+    call_as(OWNER, 0);
+    contract.deny(account.to_string());
+}
+
+#[test]
+#[should_panic(expected = "This account is blocked from using the contract.")]
+fn denied_account_cannot_mint() {
+    let mut contract = setup();
+    deny(&mut contract, ALICE);
+    mint(&mut contract, 1);
+}
+
+#[test]
+#[should_panic(expected = "This account is blocked from using the contract.")]
+fn denied_account_cannot_buy() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 1, NEAR.to_string());
+    deny(&mut contract, BOB);
+    call_as(BOB, NEAR);
+    contract.buy(token_id, 1);
+}
+
+#[test]
+#[should_panic(expected = "This account is blocked from using the contract.")]
+fn denied_account_cannot_offer() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    deny(&mut contract, BOB);
+    call_as(BOB, NEAR);
+    contract.offer(token_id, 1, None, None);
+}
+
+#[test]
+#[should_panic(expected = "This account is blocked from using the contract.")]
+fn transfer_to_a_denied_account_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    deny(&mut contract, BOB);
+    call_as(ALICE, 0);
+    contract.transfer(BOB.to_string(), token_id, 1);
+}
+
+#[test]
+#[should_panic(expected = "This account is blocked from using the contract.")]
+fn denied_escrow_cannot_transfer_from() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.grant_access(BOB.to_string(), None, None);
+    deny(&mut contract, BOB);
+    call_as(BOB, 0);
+    contract.transfer_from(ALICE.to_string(), CAROL.to_string(), token_id, 1);
+}
+
+#[test]
+fn denied_account_can_still_cancel_its_offer() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    let storage_fee = contract.edition_storage_fee;
+    call_as(BOB, NEAR + storage_fee);
+    contract.offer(token_id, 1, None, None);
+    deny(&mut contract, BOB);
+    assert!(contract.is_denied(BOB.to_string()));
+    call_as(BOB, 0);
+    contract.cancel_offer(token_id, 1, 0);
+    assert_eq!(transfers(), vec![(BOB.to_string(), NEAR), (BOB.to_string(), storage_fee)]);
+}
+
+/// three tokens of ALICE's, the second one listed, all paused in one call
+fn paused_tokens(contract: &mut NonFungibleToken) -> Vec<TokenId> {
+    let tokens = vec![mint(contract, 1), mint(contract, 1), mint(contract, 1)];
+    call_as(ALICE, 0);
+    contract.set_price(tokens[1], 1, NEAR.to_string());
+    call_as(OWNER, 0);
+    contract.pause_tokens(tokens.clone());
+    tokens
+}
+
+#[test]
+fn pause_tokens_freezes_every_token_of_the_batch() {
+    let mut contract = setup();
+    let tokens = paused_tokens(&mut contract);
+    assert!(tokens.iter().all(|token_id| contract.is_token_paused(*token_id)));
+    contract.unpause_tokens(vec![tokens[0]]);
+    call_as(ALICE, 0);
+    contract.set_price(tokens[0], 1, NEAR.to_string());
+    assert!(contract.is_token_paused(tokens[2]));
+}
+
+#[test]
+#[should_panic(expected = "Trading of this token is temporarily frozen.")]
+fn paused_token_cannot_be_bought() {
+    let mut contract = setup();
+    let tokens = paused_tokens(&mut contract);
+    call_as(BOB, NEAR);
+    contract.buy(tokens[1], 1);
+}
+
+#[test]
+#[should_panic(expected = "Trading of this token is temporarily frozen.")]
+fn paused_token_cannot_be_listed() {
+    let mut contract = setup();
+    let tokens = paused_tokens(&mut contract);
+    call_as(ALICE, 0);
+    contract.set_price(tokens[2], 1, NEAR.to_string());
+}
+
+#[test]
+#[should_panic(expected = "Trading of this token is temporarily frozen.")]
+fn paused_token_cannot_receive_offers() {
+    let mut contract = setup();
+    let tokens = paused_tokens(&mut contract);
+    call_as(BOB, NEAR);
+    contract.offer(tokens[0], 1, None, None);
+}
+
+#[test]
+fn owner_verifies_and_unverifies_a_creator() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    assert!(!contract.is_verified_creator(ALICE.to_string()));
+    call_as(OWNER, 0);
+    contract.verify_creator(ALICE.to_string());
+    assert!(contract.is_verified_creator(ALICE.to_string()));
+    assert!(contract.export_edition(token_id, 1).creator_verified);
+    contract.unverify_creator(ALICE.to_string());
+    assert!(!contract.is_verified_creator(ALICE.to_string()));
+    assert!(!contract.export_edition(token_id, 1).creator_verified);
+}
+
+#[test]
+fn delegated_minter_admin_onboards_and_removes_minters() {
+    let mut contract = setup();
+    call_as(OWNER, 0);
+    contract.set_minter_admin(Some(CAROL.to_string()));
+    call_as(CAROL, 0);
+    contract.add_minter(BOB.to_string());
+    assert!(contract.is_minter(BOB.to_string()));
+    contract.remove_minter(ALICE.to_string());
+    assert!(!contract.is_minter(ALICE.to_string()));
+    // the owner keeps managing minters alongside the admin
+    call_as(OWNER, 0);
+    contract.add_minter(ALICE.to_string());
+    assert!(contract.is_minter(ALICE.to_string()));
+}
+
+#[test]
+#[should_panic(expected = "Only contract owner or minter admin can call this method.")]
+fn minter_admin_loses_access_when_unset() {
+    let mut contract = setup();
+    call_as(OWNER, 0);
+    contract.set_minter_admin(Some(CAROL.to_string()));
+    contract.set_minter_admin(None);
+    call_as(CAROL, 0);
+    contract.add_minter(BOB.to_string());
+}
+
+#[test]
+fn reemit_tokens_pages_through_tokens_without_touching_them() {
+    let mut contract = setup();
+    let first = mint(&mut contract, 2);
+    call_as(ALICE, 0);
+    contract.set_price(first, 1, NEAR.to_string());
+    contract.burn_edition(first, 2);
+    let last = mint(&mut contract, 1);
+
+    call_as(OWNER, 0);
+    assert_eq!(contract.reemit_tokens(first, 1), first + 1);
+    assert_eq!(contract.reemit_tokens(first + 1, 10), last + 1);
+    // past the last token the cursor stays put
+    assert_eq!(contract.reemit_tokens(last + 1, 10), last + 1);
+    assert_eq!(contract.get_price(first, 1), NEAR);
+    assert!(contract.state_of(first, 2) == EditionState::BURNED);
+    assert_eq!(contract.nft_total_supply().0, 2);
+}
+
+#[test]
+fn reemit_tokens_caps_the_batch() {
+    let mut contract = setup();
+    let first = contract.current_supply;
+    for _ in 0..21 {
+        mint(&mut contract, 1);
+    }
+    call_as(OWNER, 0);
+    assert_eq!(contract.reemit_tokens(first, 100), first + 20);
+}
+
+#[test]
+#[should_panic(expected = "Only contract owner can call this method.")]
+fn only_owner_reemits_tokens() {
+    let mut contract = setup();
+    mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.reemit_tokens(0, 10);
+}
+
+#[test]
+fn config_snapshot_starts_from_the_deploy_parameters() {
+    call_as(OWNER, 0);
+    let mut contract = NonFungibleToken::new(OWNER.to_string(), FEES.to_string());
+    let initial = contract.config_snapshot();
+    assert_eq!((initial.owner_id, initial.fee_receiver), (OWNER.to_string(), FEES.to_string()));
+    assert_eq!(initial.mint_storage_fee, contract.mint_storage_fee.to_string());
+    assert_eq!(initial.edition_storage_fee, contract.edition_storage_fee.to_string());
+    assert_eq!(initial.trade_fee, "769");
+    assert_eq!((initial.max_name_length, initial.max_editions), (30, 25));
+    assert!(initial.paused && !initial.strict_deposits && !initial.emergency_stop && initial.legacy_api_enabled);
+    assert_eq!(initial.minter_admin, None);
+
+    contract.set_outbid_fee_bps(150);
+    contract.set_strict_deposits(true);
+    let current = contract.config_snapshot();
+    assert_eq!(current.outbid_fee_bps, 150);
+    assert!(current.strict_deposits);
+}
+
+#[test]
+#[should_panic(expected = "Invalid amount, expected a whole number of yoctoNEAR.")]
+fn set_mint_fee_rejects_a_negative_fee() {
+    let mut contract = setup();
+    call_as(OWNER, 0);
+    contract.set_mint_fee("-1".to_string());
+}
+
+/// ALICE's single edition token with the legacy NEP-4 methods switched off
+fn legacy_api_off(contract: &mut NonFungibleToken) -> TokenId {
+    let token_id = mint(contract, 1);
+    call_as(OWNER, 0);
+    contract.set_legacy_api_enabled(false);
+    assert!(!contract.is_legacy_api_enabled());
+    token_id
+}
+
+#[test]
+#[should_panic(expected = "Legacy NEP-4 transfer and access methods are disabled, use the NEP-171 nft_* methods instead.")]
+fn legacy_transfer_fails_when_disabled() {
+    let mut contract = setup();
+    let token_id = legacy_api_off(&mut contract);
+    call_as(ALICE, 0);
+    contract.transfer(BOB.to_string(), token_id, 1);
+}
+
+#[test]
+#[should_panic(expected = "Legacy NEP-4 transfer and access methods are disabled, use the NEP-171 nft_* methods instead.")]
+fn legacy_transfer_from_fails_when_disabled() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.grant_access(BOB.to_string(), None, None);
+    call_as(OWNER, 0);
+    contract.set_legacy_api_enabled(false);
+    call_as(BOB, 0);
+    contract.transfer_from(ALICE.to_string(), CAROL.to_string(), token_id, 1);
+}
+
+#[test]
+#[should_panic(expected = "Legacy NEP-4 transfer and access methods are disabled, use the NEP-171 nft_* methods instead.")]
+fn legacy_grant_access_fails_when_disabled() {
+    let mut contract = setup();
+    legacy_api_off(&mut contract);
+    call_as(ALICE, 0);
+    contract.grant_access(BOB.to_string(), None, None);
+}
+
+#[test]
+fn nft_transfer_keeps_working_with_the_legacy_api_disabled() {
+    let mut contract = setup();
+    let token_id = legacy_api_off(&mut contract);
+    call_as(ALICE, 1);
+    contract.nft_transfer(BOB.to_string(), format!("{}::1", token_id), None, None);
+    assert_eq!(contract.owner_of(token_id, 1), BOB.to_string());
+}
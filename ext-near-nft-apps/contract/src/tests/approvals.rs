@@ -0,0 +1,165 @@
+use near_sdk::collections::UnorderedSet;
+use crate::{EditionState, NEP4};
+use super::*;
+
+fn approve(contract: &mut NonFungibleToken, token_id: TokenId, edition_id: EditionNumber, account: &str) {
+    call_as(ALICE, 1);
+    contract.nft_approve(format!("{}::{}", token_id, edition_id), account.to_string(), None);
+}
+
+#[test]
+fn approving_an_edition_does_not_authorize_its_siblings() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    approve(&mut contract, token_id, 1, BOB);
+    assert!(contract.check_allowance(token_id, 1, BOB.to_string()));
+    assert!(!contract.check_allowance(token_id, 2, BOB.to_string()));
+    assert!(!contract.nft_is_approved(format!("{}::2", token_id), BOB.to_string(), None));
+    assert!(contract.get_allowances(token_id, 2).is_empty());
+    assert_eq!(contract.allowances_granted_to(BOB.to_string()), vec![(token_id, 1)]);
+}
+
+#[test]
+fn migrate_allowances_splits_sets_shared_by_older_mints() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    let first = contract.tokens.get(&token_id).unwrap().edition_index + 1;
+    // the single per token set the first deployment gave every edition
+    let shared: UnorderedSet<String> = UnorderedSet::new(format!("o{}", first).into_bytes());
+    contract.edition_allowances.insert(&first, &shared);
+    contract.edition_allowances.insert(&(first + 1), &shared);
+    approve(&mut contract, token_id, 1, BOB);
+    // the approval lands in the shared set but still only counts for edition 1
+    assert!(!contract.check_allowance(token_id, 2, BOB.to_string()));
+    assert!(contract.get_allowances(token_id, 2).is_empty());
+
+    call_as(OWNER, 0);
+    assert_eq!(contract.migrate_allowances(first, 10), first + 2);
+    assert!(contract.get_allowances(token_id, 1).is_empty());
+    approve(&mut contract, token_id, 1, CAROL);
+    assert_eq!(contract.edition_allowances.get(&(first + 1)).unwrap().len(), 0);
+    // editions that already have their own set are left alone
+    call_as(OWNER, 0);
+    contract.migrate_allowances(first, 10);
+    assert_eq!(contract.get_allowances(token_id, 1), vec![CAROL.to_string()]);
+}
+
+#[test]
+fn re_approving_issues_a_new_approval_id() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    let standard_id = format!("{}::1", token_id);
+    approve(&mut contract, token_id, 1, BOB);
+    assert!(contract.nft_is_approved(standard_id.clone(), BOB.to_string(), Some(1)));
+    approve(&mut contract, token_id, 1, BOB);
+    assert!(!contract.nft_is_approved(standard_id.clone(), BOB.to_string(), Some(1)));
+    assert!(contract.nft_is_approved(standard_id, BOB.to_string(), Some(2)));
+}
+
+#[test]
+fn revoked_approval_no_longer_matches() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    approve(&mut contract, token_id, 1, BOB);
+    approve(&mut contract, token_id, 1, CAROL);
+    call_as(ALICE, 1);
+    contract.nft_revoke(format!("{}::1", token_id), BOB.to_string());
+    assert!(!contract.nft_is_approved(format!("{}::1", token_id), BOB.to_string(), None));
+    assert!(contract.nft_is_approved(format!("{}::1", token_id), CAROL.to_string(), None));
+}
+
+#[test]
+fn revoke_all_clears_every_approval_of_the_edition() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    approve(&mut contract, token_id, 1, BOB);
+    approve(&mut contract, token_id, 1, CAROL);
+    approve(&mut contract, token_id, 2, BOB);
+    call_as(ALICE, 1);
+    contract.nft_revoke_all(format!("{}::1", token_id));
+    assert!(!contract.nft_is_approved(format!("{}::1", token_id), BOB.to_string(), None));
+    assert!(!contract.nft_is_approved(format!("{}::1", token_id), CAROL.to_string(), None));
+    assert!(contract.nft_is_approved(format!("{}::2", token_id), BOB.to_string(), None));
+}
+
+#[test]
+#[should_panic(expected = "You don't have rights to access this account's funds.")]
+fn transfer_with_a_stale_approval_id_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    approve(&mut contract, token_id, 1, BOB);
+    approve(&mut contract, token_id, 1, BOB);
+    call_as(BOB, 1);
+    contract.nft_transfer(CAROL.to_string(), format!("{}::1", token_id), Some(1), None);
+}
+
+#[test]
+fn transfer_with_the_current_approval_id() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    approve(&mut contract, token_id, 1, BOB);
+    call_as(BOB, 1);
+    contract.nft_transfer(CAROL.to_string(), format!("{}::1", token_id), Some(1), None);
+    assert_eq!(contract.owner_of(token_id, 1), CAROL.to_string());
+}
+
+#[test]
+fn allowances_granted_to_follows_grants_removals_and_transfers() {
+    let mut contract = setup();
+    let first = mint(&mut contract, 2);
+    let second = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.grant_edition_allowance(first, 1, BOB.to_string());
+    contract.grant_edition_allowance(first, 2, BOB.to_string());
+    contract.grant_edition_allowance(second, 1, BOB.to_string());
+    assert_eq!(contract.allowances_granted_to(BOB.to_string()), vec![(first, 1), (first, 2), (second, 1)]);
+    contract.remove_edition_allowance(first, 2, BOB.to_string());
+    // a transfer clears the allowances of the edition
+    contract.transfer(CAROL.to_string(), second, 1);
+    assert_eq!(contract.allowances_granted_to(BOB.to_string()), vec![(first, 1)]);
+    assert!(contract.allowances_granted_to(CAROL.to_string()).is_empty());
+}
+
+#[test]
+fn custodian_with_a_burn_allowance_burns_the_owners_edition() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    call_as(ALICE, 0);
+    contract.grant_burn_allowance(token_id, 1, BOB.to_string());
+    call_as(BOB, 0);
+    contract.burn_edition_from(ALICE.to_string(), token_id, 1);
+    assert!(contract.state_of(token_id, 1) == EditionState::BURNED);
+    assert!(contract.state_of(token_id, 2) == EditionState::AVAILABLE);
+}
+
+#[test]
+#[should_panic(expected = "You don't have rights to access this account's funds.")]
+fn transfer_allowance_does_not_allow_burning() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    approve(&mut contract, token_id, 1, BOB);
+    call_as(BOB, 0);
+    contract.burn_edition_from(ALICE.to_string(), token_id, 1);
+}
+
+#[test]
+fn allowance_details_list_what_each_delegate_can_do() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    approve(&mut contract, token_id, 1, BOB);
+    approve(&mut contract, token_id, 1, OWNER);
+    call_as(ALICE, 0);
+    contract.set_allowance_expiry(token_id, 1, BOB.to_string(), NOW + 100);
+    contract.grant_burn_allowance(token_id, 1, CAROL.to_string());
+    contract.grant_burn_allowance(token_id, 1, OWNER.to_string());
+    let details: Vec<(String, Option<u64>, bool, bool)> = contract.get_allowance_details(token_id, 1)
+        .into_iter()
+        .map(|info| (info.account, info.expires_at, info.can_transfer, info.can_burn))
+        .collect();
+    assert_eq!(details, vec![
+        (BOB.to_string(), Some(NOW + 100), true, false),
+        (OWNER.to_string(), None, true, true),
+        (CAROL.to_string(), None, false, true),
+    ]);
+    assert_eq!(contract.get_allowances(token_id, 1), vec![BOB.to_string(), OWNER.to_string()]);
+}
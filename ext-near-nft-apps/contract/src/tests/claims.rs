@@ -0,0 +1,93 @@
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+use near_sdk::json_types::{Base58PublicKey, Base64VecU8};
+use crate::EditionState;
+use super::*;
+
+fn keypair(seed: u8) -> Keypair {
+    let secret = SecretKey::from_bytes(&[seed; 32]).unwrap();
+    let public = PublicKey::from(&secret);
+    Keypair { secret, public }
+}
+
+fn sign(keypair: &Keypair, account: &str) -> Base64VecU8 {
+    Base64VecU8(keypair.sign(account.as_bytes()).to_bytes().to_vec())
+}
+
+/// edition 1 of a new token in a collection whose claim key is `keypair`, reserved for its allowlist
+fn allowlisted_edition(contract: &mut NonFungibleToken, keypair: &Keypair) -> (CollectionId, TokenId) {
+    let collection_id = create_collection(contract);
+    let token_id = mint_in(contract, collection_id, 2);
+    call_as(ALICE, 0);
+    contract.set_claim_key(collection_id, Some(Base58PublicKey([vec![0], keypair.public.to_bytes().to_vec()].concat())));
+    contract.make_claimable_by_allowlist(token_id, 1);
+    (collection_id, token_id)
+}
+
+#[test]
+fn claim_with_valid_signature() {
+    let mut contract = setup();
+    let creator = keypair(1);
+    let (collection_id, token_id) = allowlisted_edition(&mut contract, &creator);
+    call_as(BOB, 0);
+    contract.claim_with_proof(collection_id, sign(&creator, BOB));
+    assert_eq!(contract.owner_of(token_id, 1), BOB.to_string());
+    assert!(contract.has_claimed_from_allowlist(collection_id, BOB.to_string()));
+}
+
+#[test]
+#[should_panic(expected = "INVALID ALLOWLIST SIGNATURE")]
+fn claim_with_forged_signature_fails() {
+    let mut contract = setup();
+    let (collection_id, _) = allowlisted_edition(&mut contract, &keypair(1));
+    // signed by a key other than the collection's claim key
+    call_as(BOB, 0);
+    contract.claim_with_proof(collection_id, sign(&keypair(2), BOB));
+}
+
+#[test]
+#[should_panic(expected = "INVALID ALLOWLIST SIGNATURE")]
+fn claim_with_another_accounts_signature_fails() {
+    let mut contract = setup();
+    let creator = keypair(1);
+    let (collection_id, _) = allowlisted_edition(&mut contract, &creator);
+    call_as(CAROL, 0);
+    contract.claim_with_proof(collection_id, sign(&creator, BOB));
+}
+
+#[test]
+fn designated_claimant_claims_the_edition() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.make_claimable(token_id, 1, BOB.to_string());
+    assert!(contract.state_of(token_id, 1) == EditionState::LOCKED);
+    call_as(BOB, 0);
+    contract.claim(token_id, 1);
+    assert_eq!(contract.owner_of(token_id, 1), BOB.to_string());
+    assert!(contract.state_of(token_id, 1) == EditionState::AVAILABLE);
+    assert!(transfers().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "ONLY THE DESIGNATED CLAIMANT CAN CLAIM")]
+fn claim_by_anyone_but_the_claimant_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.make_claimable(token_id, 1, BOB.to_string());
+    call_as(CAROL, 0);
+    contract.claim(token_id, 1);
+}
+
+#[test]
+#[should_panic(expected = "EDITION IS NOT CLAIMABLE")]
+fn revoked_claimable_cannot_be_claimed() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.make_claimable(token_id, 1, BOB.to_string());
+    contract.revoke_claimable(token_id, 1);
+    assert!(contract.state_of(token_id, 1) == EditionState::AVAILABLE);
+    call_as(BOB, 0);
+    contract.claim(token_id, 1);
+}
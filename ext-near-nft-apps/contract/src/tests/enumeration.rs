@@ -0,0 +1,241 @@
+use crate::EditionState;
+use super::*;
+
+#[test]
+fn burned_editions_leave_the_total_supply() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 3);
+    assert_eq!(contract.nft_total_supply().0, 3);
+    call_as(ALICE, 0);
+    contract.burn_edition(token_id, 2);
+    assert_eq!(contract.nft_total_supply().0, 2);
+}
+
+#[test]
+fn nft_tokens_pages_over_positions_and_skips_burned_editions() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 5);
+    call_as(ALICE, 0);
+    contract.burn_edition(token_id, 2);
+    let ids = |page: Vec<crate::model::JsonToken>| page.into_iter().map(|token| token.token_id).collect::<Vec<_>>();
+    // the first page only scans positions 1 and 2, the burned edition isn't made up for from later positions
+    assert_eq!(ids(contract.nft_tokens(None, Some(2))), vec![format!("{}::1", token_id)]);
+    assert_eq!(ids(contract.nft_tokens(Some(2.into()), Some(2))), vec![format!("{}::3", token_id), format!("{}::4", token_id)]);
+    assert_eq!(ids(contract.nft_tokens(Some(4.into()), Some(2))), vec![format!("{}::5", token_id)]);
+    assert!(contract.nft_tokens(Some(5.into()), Some(2)).is_empty());
+}
+
+#[test]
+fn first_edition_ranks_1_whatever_the_edition_index_offset() {
+    let mut contract = setup();
+    mint(&mut contract, 3);
+    let token_id = mint(&mut contract, 2);
+    assert_eq!(contract.tokens.get(&token_id).unwrap().edition_index, 3);
+    assert_eq!(contract.edition_rank(token_id, 1), 1);
+    assert!(contract.is_first_edition(token_id, 1));
+    assert_eq!(contract.edition_rank(token_id, 2), 2);
+    assert!(!contract.is_first_edition(token_id, 2));
+}
+
+#[test]
+#[should_panic(expected = "EDITION DOES NOT EXIST")]
+fn edition_rank_of_a_missing_edition_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    contract.edition_rank(token_id, 3);
+}
+
+#[test]
+fn editions_are_grouped_by_state() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 4);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 2, "1000000000000000000000000".to_string());
+    contract.set_price(token_id, 3, "2000000000000000000000000".to_string());
+    contract.burn_edition(token_id, 4);
+    assert_eq!(contract.editions_by_state(token_id, EditionState::AVAILABLE), vec![1]);
+    assert_eq!(contract.editions_by_state(token_id, EditionState::LISTED), vec![2, 3]);
+    assert_eq!(contract.editions_by_state(token_id, EditionState::BURNED), vec![4]);
+    assert!(contract.editions_by_state(token_id, EditionState::LOCKED).is_empty());
+}
+
+#[test]
+fn existence_checks_for_present_missing_and_burned_ids() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    let collection_id = create_collection(&mut contract);
+    call_as(ALICE, 0);
+    contract.burn_edition(token_id, 2);
+    assert!(contract.token_exists(token_id));
+    assert!(!contract.token_exists(token_id + 1));
+    assert!(contract.collection_exists(collection_id));
+    assert!(!contract.collection_exists(collection_id + 1));
+    assert!(contract.edition_exists(token_id, 1));
+    assert!(!contract.edition_exists(token_id, 2));
+    assert!(!contract.edition_exists(token_id, 0));
+    assert!(!contract.edition_exists(token_id, 3));
+    assert!(!contract.edition_exists(token_id + 1, 1));
+}
+
+#[test]
+fn collection_activity_is_kept_per_collection() {
+    let mut contract = setup();
+    let first = create_collection(&mut contract);
+    let second = create_collection(&mut contract);
+    let sold = mint_in(&mut contract, first, 1);
+    let given = mint_in(&mut contract, second, 1);
+    call_as(ALICE, 0);
+    contract.set_price(sold, 1, "1000000000000000000000000".to_string());
+    call_as(BOB, 1_000_000_000_000_000_000_000_000);
+    contract.buy(sold, 1);
+    call_as(ALICE, 1);
+    contract.nft_transfer(CAROL.to_string(), format!("{}::1", given), None, None);
+    let events = |collection_id| -> Vec<(TokenId, String, String)> {
+        contract.collection_activity(collection_id, 0, 10).into_iter().map(|record| (record.token_id, record.event_name, record.to)).collect()
+    };
+    assert_eq!(events(first), vec![(sold, "Mint".to_string(), ALICE.to_string()), (sold, "MarketBuy".to_string(), BOB.to_string())]);
+    assert_eq!(events(second), vec![(given, "Mint".to_string(), ALICE.to_string()), (given, "Transfer".to_string(), CAROL.to_string())]);
+}
+
+#[test]
+fn tokens_by_creator_keeps_each_minters_tokens_apart() {
+    let mut contract = setup();
+    call_as(OWNER, 0);
+    contract.add_minter(BOB.to_string());
+    let first = mint(&mut contract, 1);
+    let bobs = contract.current_supply;
+    call_as(BOB, contract.mint_cost(1).parse().unwrap());
+    contract.mint_token(metadata(1));
+    let second = mint(&mut contract, 2);
+
+    assert_eq!(contract.tokens_by_creator(ALICE.to_string(), 0, 10), vec![first, second]);
+    assert_eq!(contract.tokens_by_creator(ALICE.to_string(), 1, 10), vec![second]);
+    assert_eq!(contract.tokens_by_creator_count(ALICE.to_string()), 2);
+    assert_eq!(contract.tokens_by_creator(BOB.to_string(), 0, 10), vec![bobs]);
+    // genesis collection tokens are listed under their minter, not the collection's creator
+    assert!(contract.tokens_by_creator(OWNER.to_string(), 0, 10).is_empty());
+    assert_eq!(contract.tokens_by_creator_count(CAROL.to_string()), 0);
+}
+
+#[test]
+fn owners_of_many_answers_in_input_order_with_none_for_burned_editions() {
+    let mut contract = setup();
+    let first = mint(&mut contract, 2);
+    let second = mint(&mut contract, 1);
+    call_as(ALICE, 1);
+    contract.nft_transfer(BOB.to_string(), format!("{}::1", second), None, None);
+    contract.burn_edition(first, 2);
+    let owners = contract.owners_of_many(vec![(second, 1), (first, 2), (first, 1), (first, 3), (second + 1, 1)]);
+    assert_eq!(owners, vec![Some(BOB.to_string()), None, Some(ALICE.to_string()), None, None]);
+}
+
+#[test]
+fn next_indices_predict_the_next_mint() {
+    let mut contract = setup();
+    mint(&mut contract, 3);
+    let (token_id, edition_index) = (contract.next_token_id(), contract.next_edition_index());
+    assert_eq!(mint(&mut contract, 2), token_id);
+    assert_eq!(contract.tokens.get(&token_id).unwrap().edition_index, edition_index);
+    assert_eq!(contract.next_token_id(), token_id + 1);
+    assert_eq!(contract.next_edition_index(), edition_index + 2);
+}
+
+#[test]
+fn edition_card_reflects_a_listed_edition() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 2, "1000000000000000000000000".to_string());
+    let card = contract.edition_card(token_id, 2).unwrap();
+    assert_eq!((card.token_id, card.edition_number, card.owner), (token_id, 2, ALICE.to_string()));
+    assert!(card.state == EditionState::LISTED && card.listed);
+    assert_eq!(card.price, Some("1000000000000000000000000".to_string()));
+    let unlisted = contract.edition_card(token_id, 1).unwrap();
+    assert!(unlisted.state == EditionState::AVAILABLE && !unlisted.listed && unlisted.price.is_none());
+    contract.burn_edition(token_id, 1);
+    assert!(contract.edition_card(token_id, 1).is_none());
+    assert!(contract.edition_card(token_id, 3).is_none());
+}
+
+#[test]
+fn burned_edition_index_is_never_handed_out_again() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    let burned = contract.tokens.get(&token_id).unwrap().edition_index + 2;
+    call_as(ALICE, 0);
+    contract.burn_edition(token_id, 2);
+    assert!(contract.is_index_burned(burned));
+    assert!(!contract.is_index_burned(burned - 1));
+
+    let later = mint(&mut contract, 3);
+    let token = contract.tokens.get(&later).unwrap();
+    assert!((token.edition_index + 1..=token.edition_index + 3).all(|idx| idx > burned && !contract.is_index_burned(idx)));
+    assert!(contract.is_index_burned(burned));
+    assert!(contract.editions.get(&burned).is_none());
+}
+
+#[test]
+fn edition_indices_key_into_the_edition_maps() {
+    let mut contract = setup();
+    mint(&mut contract, 2);
+    let token_id = mint(&mut contract, 3);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 2, "1000000000000000000000000".to_string());
+    let indices = contract.edition_indices(token_id);
+    assert_eq!(indices.iter().map(|(edition_id, _)| *edition_id).collect::<Vec<_>>(), vec![1, 2, 3]);
+    for (edition_id, idx) in indices {
+        assert_eq!(idx, contract.tokens.get(&token_id).unwrap().edition_index + edition_id);
+        assert_eq!(contract.editions.get(&idx).unwrap().edition_number, edition_id);
+        assert!(contract.edition_states.get(&idx) == Some(contract.state_of(token_id, edition_id)));
+        assert_eq!(contract.marketplace.get(&idx).is_some(), edition_id == 2);
+    }
+}
+
+#[test]
+fn nft_token_maps_an_edition_onto_the_standard_shape() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    let standard_id = format!("{}::2", token_id);
+    let token = contract.nft_token(standard_id.clone()).unwrap();
+    assert_eq!((token.token_id, token.owner_id), (standard_id, ALICE.to_string()));
+    let md = contract.get_metadata(token_id);
+    assert_eq!(token.metadata.title, Some(format!("{} #2", md.name)));
+    assert_eq!(token.metadata.media, Some(md.main));
+    assert_eq!(token.metadata.copies, Some(2));
+    assert_eq!(token.metadata.issued_at, Some(md.date));
+}
+
+#[test]
+fn nft_token_of_a_malformed_missing_or_burned_id_is_none() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    call_as(ALICE, 0);
+    contract.burn_edition(token_id, 2);
+    for id in &[format!("{}", token_id), format!("{}::1::1", token_id), format!("{}::x", token_id), format!("{}::3", token_id), format!("{}::1", token_id + 1), format!("{}::2", token_id)] {
+        assert!(contract.nft_token(id.clone()).is_none(), "{}", id);
+    }
+}
+
+#[test]
+fn tokens_by_owner_follows_mints_transfers_and_burns() {
+    let mut contract = setup();
+    let first = mint(&mut contract, 3);
+    let second = mint(&mut contract, 1);
+    call_as(ALICE, 1);
+    contract.nft_transfer(BOB.to_string(), format!("{}::2", first), None, None);
+    call_as(ALICE, 0);
+    contract.burn_edition(first, 3);
+
+    let owned = |account: &str, from_index, limit| -> Vec<(TokenId, u64)> {
+        let mut owned: Vec<(TokenId, u64)> = contract.tokens_by_owner(account.to_string(), from_index, limit).into_iter().map(|edition| (edition.token_id, edition.edition_number)).collect();
+        owned.sort_unstable();
+        owned
+    };
+    assert_eq!(owned(ALICE, 0, 10), vec![(first, 1), (second, 1)]);
+    assert_eq!(contract.tokens_by_owner_count(ALICE.to_string()), 2);
+    assert_eq!(owned(ALICE, 0, 1).len(), 1);
+    assert_eq!(owned(ALICE, 1, 10).len(), 1);
+    assert_ne!(owned(ALICE, 0, 1), owned(ALICE, 1, 10));
+    assert_eq!(owned(BOB, 0, 10), vec![(first, 2)]);
+    assert!(owned(CAROL, 0, 10).is_empty());
+}
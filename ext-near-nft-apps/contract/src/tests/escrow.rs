@@ -0,0 +1,189 @@
+use crate::NEP4;
+use super::*;
+
+#[test]
+fn transfer_from_within_deadline() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.grant_access(BOB.to_string(), Some(NOW + 10), None);
+    call_at(BOB, 1, NOW + 10);
+    contract.transfer_from(ALICE.to_string(), CAROL.to_string(), token_id, 1);
+    assert_eq!(contract.owner_of(token_id, 1), CAROL.to_string());
+}
+
+#[test]
+#[should_panic(expected = "TRANSFER AUTHORIZATION EXPIRED")]
+fn transfer_from_past_deadline_fails_while_access_is_granted() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.grant_access(BOB.to_string(), Some(NOW + 10), None);
+    call_at(BOB, 1, NOW + 11);
+    assert!(contract.check_access(ALICE.to_string(), BOB.to_string()));
+    contract.transfer_from(ALICE.to_string(), CAROL.to_string(), token_id, 1);
+}
+
+#[test]
+fn single_use_grant_is_consumed() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    call_as(ALICE, 0);
+    contract.grant_access(BOB.to_string(), None, Some(true));
+    call_as(BOB, 1);
+    contract.transfer_from(ALICE.to_string(), CAROL.to_string(), token_id, 1);
+    assert_eq!(contract.transfer_nonce(ALICE.to_string(), BOB.to_string()), 1);
+    assert!(!contract.check_access(ALICE.to_string(), BOB.to_string()));
+}
+
+#[test]
+#[should_panic(expected = "You don't have rights to access this account's funds.")]
+fn single_use_grant_cannot_be_replayed() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    call_as(ALICE, 0);
+    contract.grant_access(BOB.to_string(), None, Some(true));
+    call_as(BOB, 1);
+    contract.transfer_from(ALICE.to_string(), CAROL.to_string(), token_id, 1);
+    contract.transfer_from(ALICE.to_string(), CAROL.to_string(), token_id, 2);
+}
+
+#[test]
+fn legacy_transfer_restarts_the_hold_period() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_at(ALICE, 0, NOW + 5);
+    contract.transfer(BOB.to_string(), token_id, 1);
+    assert_eq!(contract.owner_of(token_id, 1), BOB.to_string());
+    assert_eq!(contract.acquired_at.get(&(contract.tokens.get(&token_id).unwrap().edition_index + 1)), Some(NOW + 5));
+}
+
+#[test]
+fn revoke_all_access_cuts_off_every_escrow() {
+    let mut contract = setup();
+    call_as(ALICE, 0);
+    for escrow in &[BOB, CAROL, OWNER] {
+        contract.grant_access(escrow.to_string(), None, None);
+    }
+    assert_eq!(contract.get_escrows(ALICE.to_string()).len(), 3);
+    contract.revoke_all_access();
+    assert!(contract.get_escrows(ALICE.to_string()).is_empty());
+    for escrow in &[BOB, CAROL, OWNER] {
+        assert!(!contract.check_access(ALICE.to_string(), escrow.to_string()));
+    }
+}
+
+#[test]
+fn revoke_access_batch_keeps_the_escrows_it_was_not_given() {
+    let mut contract = setup();
+    call_as(ALICE, 0);
+    for escrow in &[BOB, CAROL, OWNER] {
+        contract.grant_access(escrow.to_string(), None, None);
+    }
+    contract.revoke_access_batch(vec![BOB.to_string(), OWNER.to_string()]);
+    assert_eq!(contract.get_escrows(ALICE.to_string()), vec![CAROL.to_string()]);
+    assert!(!contract.check_access(ALICE.to_string(), BOB.to_string()));
+}
+
+#[test]
+fn nft_transfer_by_standard_id() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    call_as(ALICE, 1);
+    contract.nft_transfer(BOB.to_string(), format!("{}::2", token_id), None, Some("gift".to_string()));
+    assert_eq!(contract.owner_of(token_id, 2), BOB.to_string());
+    assert_eq!(contract.owner_of(token_id, 1), ALICE.to_string());
+}
+
+#[test]
+#[should_panic(expected = "REQUIRES ATTACHED DEPOSIT OF EXACTLY 1 YOCTONEAR")]
+fn nft_transfer_without_one_yocto_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.nft_transfer(BOB.to_string(), format!("{}::1", token_id), None, None);
+}
+
+#[test]
+#[should_panic(expected = "You don't have rights to access this account's funds.")]
+fn nft_transfer_by_a_stranger_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(CAROL, 1);
+    contract.nft_transfer(CAROL.to_string(), format!("{}::1", token_id), None, None);
+}
+
+#[test]
+#[should_panic(expected = "Account ID is invalid.")]
+fn transfer_to_an_empty_account_id_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.transfer(String::new(), token_id, 1);
+}
+
+#[test]
+#[should_panic(expected = "Account ID is invalid.")]
+fn transfer_from_to_an_uppercase_account_id_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.grant_access(BOB.to_string(), None, None);
+    call_as(BOB, 0);
+    contract.transfer_from(ALICE.to_string(), "Carol.Near".to_string(), token_id, 1);
+}
+
+#[test]
+fn transfer_batch_moves_every_listed_edition() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 3);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 2, "1000000000000000000000000".to_string());
+    contract.transfer_batch(BOB.to_string(), token_id, vec![1, 2]);
+    assert_eq!(contract.owner_of(token_id, 1), BOB.to_string());
+    assert_eq!(contract.owner_of(token_id, 2), BOB.to_string());
+    assert_eq!(contract.owner_of(token_id, 3), ALICE.to_string());
+    assert!(contract.all_listings(0, 10).is_empty());
+}
+
+#[test]
+fn transfer_batch_with_a_locked_edition_moves_nothing() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 3);
+    call_as(ALICE, 0);
+    contract.start_auction(token_id, 3, "1".to_string(), 1_000);
+    // the mocked blockchain doesn't roll storage back on a panic, so anything moved before it would stay moved
+    let failed = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| contract.transfer_batch(BOB.to_string(), token_id, vec![1, 2, 3])));
+    assert!(failed.is_err());
+    for edition_id in 1..=3 {
+        assert_eq!(contract.owner_of(token_id, edition_id), ALICE.to_string());
+    }
+}
+
+#[test]
+#[should_panic(expected = "This edition is burned or locked.")]
+fn transfer_batch_with_a_burned_edition_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    call_as(ALICE, 0);
+    contract.burn_edition(token_id, 2);
+    contract.transfer_batch(BOB.to_string(), token_id, vec![1, 2]);
+}
+
+#[test]
+#[should_panic(expected = "INVALID NUMBER OF EDITIONS")]
+fn empty_transfer_batch_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.transfer_batch(BOB.to_string(), token_id, vec![]);
+}
+
+#[test]
+#[should_panic(expected = "INVALID NUMBER OF EDITIONS")]
+fn transfer_batch_past_the_cap_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.transfer_batch(BOB.to_string(), token_id, vec![1; 51]);
+}
@@ -0,0 +1,37 @@
+use near_sdk::serde_json::{self, json, Value};
+use crate::logger::{nft_burn_event, nft_mint_event, nft_transfer_event};
+use super::*;
+
+/// the NEP-297 body of an event line
+fn event(line: String) -> Value {
+    assert!(line.starts_with("EVENT_JSON:"), "{}", line);
+    serde_json::from_str(&line["EVENT_JSON:".len()..]).unwrap()
+}
+
+#[test]
+fn mint_event_lists_every_edition() {
+    call_as(ALICE, 0);
+    assert_eq!(event(nft_mint_event(ALICE.to_string(), 4, 2)), json!({
+        "standard": "nep171",
+        "version": "1.0.0",
+        "event": "nft_mint",
+        "data": [{"owner_id": ALICE, "token_ids": ["4::1", "4::2"]}]
+    }));
+}
+
+#[test]
+fn transfer_event_names_the_escrow_that_moved_the_edition() {
+    call_as(ALICE, 0);
+    let by_owner = event(nft_transfer_event(ALICE.to_string(), BOB.to_string(), 4, 2));
+    assert_eq!(by_owner["event"], "nft_transfer");
+    assert_eq!(by_owner["data"][0], json!({"authorized_id": null, "old_owner_id": ALICE, "new_owner_id": BOB, "token_ids": ["4::2"]}));
+    call_as(CAROL, 0);
+    let by_escrow = event(nft_transfer_event(ALICE.to_string(), BOB.to_string(), 4, 2));
+    assert_eq!(by_escrow["data"][0]["authorized_id"], CAROL);
+}
+
+#[test]
+fn burn_event_carries_the_burned_edition() {
+    call_as(ALICE, 0);
+    assert_eq!(event(nft_burn_event(ALICE.to_string(), 4, 1))["data"][0], json!({"authorized_id": null, "owner_id": ALICE, "token_ids": ["4::1"]}));
+}
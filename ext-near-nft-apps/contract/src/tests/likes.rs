@@ -0,0 +1,88 @@
+use super::*;
+
+/// BOB likes edition 1 of a fresh token, attaching `deposit`
+fn liked_edition(contract: &mut NonFungibleToken, deposit: Balance) -> TokenId {
+    let token_id = mint(contract, 1);
+    call_as(BOB, deposit);
+    contract.like(token_id, 1);
+    token_id
+}
+
+#[test]
+fn like_and_unlike_toggle_the_count_once_per_account() {
+    let mut contract = setup();
+    let like_fee = contract.like_fee;
+    let token_id = liked_edition(&mut contract, like_fee);
+    call_as(CAROL, like_fee);
+    contract.like(token_id, 1);
+    assert_eq!(contract.likes_of(token_id, 1), 2);
+    assert!(contract.has_liked(BOB.to_string(), token_id, 1));
+    call_as(BOB, 0);
+    contract.unlike(token_id, 1);
+    assert_eq!(contract.likes_of(token_id, 1), 1);
+    assert!(!contract.has_liked(BOB.to_string(), token_id, 1));
+    call_as(BOB, like_fee);
+    contract.like(token_id, 1);
+    assert_eq!(contract.likes_of(token_id, 1), 2);
+}
+
+#[test]
+#[should_panic(expected = "ALREADY LIKED")]
+fn liking_twice_fails() {
+    let mut contract = setup();
+    let like_fee = contract.like_fee;
+    let token_id = liked_edition(&mut contract, like_fee);
+    call_as(BOB, like_fee);
+    contract.like(token_id, 1);
+}
+
+#[test]
+#[should_panic(expected = "NOT LIKED")]
+fn unliking_twice_fails() {
+    let mut contract = setup();
+    let like_fee = contract.like_fee;
+    let token_id = liked_edition(&mut contract, like_fee);
+    call_as(BOB, 0);
+    contract.unlike(token_id, 1);
+    contract.unlike(token_id, 1);
+}
+
+#[test]
+fn like_fee_is_booked_and_the_excess_refunded() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    let collected = contract.collected_fees;
+    call_as(BOB, contract.like_fee + 5);
+    contract.like(token_id, 1);
+    assert_eq!(contract.collected_fees, collected + contract.like_fee);
+    assert_eq!(transfers(), vec![(BOB.to_string(), 5)]);
+}
+
+#[test]
+#[should_panic(expected = "Deposit not enough")]
+fn like_without_the_fee_fails() {
+    let mut contract = setup();
+    let like_fee = contract.like_fee;
+    liked_edition(&mut contract, like_fee - 1);
+}
+
+#[test]
+#[should_panic(expected = "This account is blocked from using the contract.")]
+fn denied_account_cannot_like() {
+    let mut contract = setup();
+    call_as(OWNER, 0);
+    contract.deny(BOB.to_string());
+    let like_fee = contract.like_fee;
+    liked_edition(&mut contract, like_fee);
+}
+
+#[test]
+#[should_panic(expected = "Maintenance going on. Minting and transfers are temporarily disabled.")]
+fn like_while_paused_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(OWNER, 0);
+    contract.pause();
+    call_as(BOB, contract.like_fee);
+    contract.like(token_id, 1);
+}
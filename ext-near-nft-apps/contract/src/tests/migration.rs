@@ -0,0 +1,189 @@
+use borsh::BorshSerialize;
+use near_sdk::collections::{LookupMap, UnorderedSet, Vector};
+use near_sdk::env;
+use crate::{EditionState, OfferStatus, OldNonFungibleToken};
+use crate::model::{Edition, LegacyMetadata, Token};
+use super::*;
+
+const NEAR: Balance = 1_000_000_000_000_000_000_000_000;
+
+/// replaces offer `idx` of the first offer list with the pre storage fee layout of a bid
+fn store_legacy_bid(idx: u64, bidder: &str, amount: Balance, executed: bool) {
+    let mut key = b"ov1".to_vec();
+    key.extend(&idx.to_le_bytes());
+    let legacy = (bidder.to_string(), amount, "0".to_string(), executed);
+    env::storage_write(&key, &legacy.try_to_vec().unwrap());
+}
+
+#[test]
+fn migrate_offers_splits_storage_fee_off_open_legacy_bids() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    let storage_fee = contract.edition_storage_fee;
+    call_as(BOB, NEAR);
+    contract.offer(token_id, 1, None, None);
+    call_as(CAROL, NEAR);
+    contract.offer(token_id, 1, None, None);
+    store_legacy_bid(0, BOB, NEAR, false);
+    // a bid the old cancel_offer closed, its bidder overwritten with the "::" marker
+    store_legacy_bid(1, "::", NEAR, true);
+
+    call_as(OWNER, 0);
+    assert_eq!(contract.migrate_offers(0, 10), 1);
+    let offers = contract.get_offers(token_id, 1);
+    assert_eq!((offers[0].amount, offers[0].storage_fee, offers[0].executed), (NEAR - storage_fee, storage_fee, false));
+    assert_eq!((offers[1].amount, offers[1].storage_fee, offers[1].executed), (NEAR, 0, true));
+    assert!(contract.offer_status(token_id, 1, 1) == OfferStatus::CANCELLED);
+    assert_eq!(contract.claimable_of(BOB.to_string()).offers, NEAR.to_string());
+}
+
+#[test]
+fn migrate_collections_reads_the_pre_trade_window_layout() {
+    let mut contract = setup();
+    let collection_id = create_collection(&mut contract);
+    let mut key = b"c".to_vec();
+    key.extend(collection_id.try_to_vec().unwrap());
+    let legacy = ("old".to_string(), String::new(), "0".to_string(), "Q".repeat(46), ALICE.to_string(), vec![ALICE.to_string()]);
+    env::storage_write(&key, &legacy.try_to_vec().unwrap());
+
+    call_as(OWNER, 0);
+    assert_eq!(contract.migrate_collections(0, 10), collection_id + 1);
+    let collection = contract.get_collection(collection_id);
+    assert_eq!(collection.name, "old");
+    assert_eq!(collection.minters, vec![ALICE.to_string()]);
+    assert!(collection.trade_start.is_none() && collection.trade_end.is_none() && collection.royalties.is_empty());
+}
+
+/// state as the first deployment stored it: ALICE whitelisted, a two edition token of hers with a divisor
+/// royalty of 10 and its second edition burned, a legacy genesis collection and a trade fee divisor of 13
+fn store_old_state() {
+    call_as(OWNER, 0);
+    let mut old = OldNonFungibleToken {
+        owner_id: OWNER.to_string(),
+        current_supply: 1,
+        total_editions: 2,
+        total_collections: 0,
+        minters: UnorderedSet::new(b"mt".to_vec()),
+        metadata: LookupMap::new(b"md".to_vec()),
+        tokens: LookupMap::new(b"t".to_vec()),
+        collections: LookupMap::new(b"c".to_vec()),
+        editions: LookupMap::new(b"e".to_vec()),
+        edition_states: LookupMap::new(b"st".to_vec()),
+        marketplace: LookupMap::new(b"mp".to_vec()),
+        account_gives_access: LookupMap::new(b"esc".to_vec()),
+        edition_allowances: LookupMap::new(b"ea".to_vec()),
+        offers: LookupMap::new(b"O".to_vec()),
+        mint_storage_fee: 300_000_000_000_000_000_000_000,
+        edition_storage_fee: 35_000_000_000_000_000_000_000,
+        create_collection_fee: 2_000_000_000_000_000_000,
+        trade_fee: 13,
+        paused: false,
+        fee_receiver: FEES.to_string(),
+        MAX_NAME_LENGTH: 30,
+        MAX_DESCRIPTION_LENGTH: 250,
+        IPFS_HASH_LENGTH: 46,
+        MAX_EDITIONS: 25,
+        MAX_EXTERNAL_LINK: 100,
+    };
+    old.minters.insert(&ALICE.to_string());
+    old.tokens.insert(&0, &Token { edition_index: 0, editions: 2, metadata: 0 });
+    old.editions.insert(&1, &Edition { edition_number: 1, edition_owner: ALICE.to_string(), token_id: 0 });
+    old.edition_states.insert(&1, &EditionState::AVAILABLE);
+    old.edition_states.insert(&2, &EditionState::BURNED);
+    old.edition_allowances.insert(&1, &UnorderedSet::new(b"o1".to_vec()));
+    let legacy = LegacyMetadata {
+        name: "old".to_string(),
+        collection_id: 0,
+        creator: ALICE.to_string(),
+        description: String::new(),
+        thumbnail: "Q".repeat(46),
+        main: "Q".repeat(46),
+        nft_type: "image".to_string(),
+        file: String::new(),
+        external_link: String::new(),
+        royalty: 10,
+        editions: 2,
+        date: "0".to_string(),
+        tags: vec![],
+    };
+    env::storage_write(&[b"md".to_vec(), 0u64.try_to_vec().unwrap()].concat(), &legacy.try_to_vec().unwrap());
+    let genesis = ("Nearfolio".to_string(), String::new(), "0".to_string(), "Q".repeat(46), OWNER.to_string(), Vec::<String>::new());
+    env::storage_write(&[b"c".to_vec(), 0u64.try_to_vec().unwrap()].concat(), &genesis.try_to_vec().unwrap());
+    let offers: Vector<crate::model::Bid> = Vector::new(b"legacy".to_vec());
+    old.offers.insert(&"0::1".to_string(), &offers);
+    env::state_write(&old);
+}
+
+#[test]
+#[should_panic(expected = "Only contract owner can call this method.")]
+fn migrate_is_owner_only() {
+    store_old_state();
+    call_as(ALICE, 0);
+    NonFungibleToken::migrate();
+}
+
+#[test]
+#[should_panic(expected = "A state migration is in progress.")]
+fn state_changes_are_blocked_while_migrating() {
+    store_old_state();
+    let mut contract = NonFungibleToken::migrate();
+    assert!(contract.is_migration_in_progress());
+    call_as(ALICE, 0);
+    contract.set_price(0, 1, NEAR.to_string());
+}
+
+#[test]
+fn migrate_from_the_first_deployment() {
+    store_old_state();
+    let mut contract = NonFungibleToken::migrate();
+    assert_eq!((contract.current_supply, contract.total_editions, contract.trade_fee), (1, 2, 13));
+    assert!(contract.is_minter(ALICE.to_string()));
+    contract.migrate_metadata(0, 10);
+    contract.migrate_collections(0, 10);
+    contract.migrate_offers(0, 10);
+    contract.migrate_owned_editions(1, 1);
+    contract.migrate_owned_editions(1, 10);
+    contract.migrate_owned_editions(1, 10);
+    contract.migrate_allowances(1, 10);
+    contract.migrate_royalties(0, 10);
+    contract.migrate_trade_fee();
+    contract.finish_migration();
+    assert_eq!(contract.trade_fee, 769);
+    assert_eq!(contract.royalty_bps_of(0), Some(1_000));
+    assert_eq!(contract.get_metadata(0).name, "old");
+    assert_eq!(contract.get_collection(0).name, "Nearfolio");
+    assert_eq!(contract.tokens_by_owner_count(ALICE.to_string()), 1);
+    assert_eq!(contract.nft_total_supply().0, 1);
+
+    call_as(ALICE, 0);
+    contract.set_price(0, 1, NEAR.to_string());
+    call_as(BOB, NEAR);
+    contract.buy(0, 1);
+    assert_eq!(contract.owner_of(0, 1), BOB.to_string());
+}
+
+#[test]
+fn migrate_royalties_converts_legacy_divisors_in_batches() {
+    let mut contract = setup();
+    let tokens: Vec<TokenId> = (0..3).map(|_| mint(&mut contract, 1)).collect();
+    // the first two tokens predate royalty_bps, their royalty is still a divisor of the proceeds
+    for (token_id, divisor) in tokens[..2].iter().zip(&[10, 4]) {
+        let mut md = contract.metadata.get(token_id).unwrap();
+        md.royalty = *divisor;
+        contract.metadata.insert(token_id, &md);
+        contract.royalty_bps.remove(token_id);
+    }
+
+    call_as(OWNER, 0);
+    assert_eq!(contract.migrate_royalties(tokens[0], 1), tokens[0] + 1);
+    assert_eq!(contract.royalty_bps_of(tokens[0]), Some(1_000));
+    assert_eq!(contract.royalty_bps_of(tokens[1]), None);
+    assert_eq!(contract.migrate_royalties(tokens[0] + 1, 10), tokens[2] + 1);
+    assert_eq!(contract.royalty_bps_of(tokens[1]), Some(2_500));
+    assert_eq!(contract.metadata.get(&tokens[1]).unwrap().royalty, 2_500);
+    // already migrated tokens keep their bps instead of being read as a divisor again
+    assert_eq!(contract.royalty_bps_of(tokens[2]), Some(1_000));
+    assert_eq!(contract.migrate_royalties(tokens[0], 10), tokens[2] + 1);
+    assert_eq!(contract.royalty_bps_of(tokens[0]), Some(1_000));
+    assert_eq!(contract.metadata.get(&tokens[0]).unwrap().royalty, 1_000);
+}
@@ -0,0 +1,254 @@
+use super::*;
+
+#[test]
+#[should_panic(expected = "Metadata exceeds character limits.: A token needs at least one edition.")]
+fn minting_zero_editions_fails() {
+    let mut contract = setup();
+    mint(&mut contract, 0);
+}
+
+#[test]
+fn mint_fee_is_collected_and_withdrawn_to_the_fee_receiver() {
+    let mut contract = setup();
+    let collected: Balance = contract.collected_fees().parse().unwrap();
+    let cost: Balance = contract.mint_cost(3).parse().unwrap();
+    mint(&mut contract, 3);
+    assert_eq!(contract.collected_fees(), (collected + cost).to_string());
+    call_as(OWNER, 0);
+    contract.withdraw_fees(cost.to_string());
+    assert_eq!(transfers(), vec![(FEES.to_string(), cost)]);
+    assert_eq!(contract.collected_fees(), collected.to_string());
+}
+
+#[test]
+#[should_panic(expected = "AMOUNT EXCEEDS COLLECTED FEES")]
+fn withdrawing_more_than_the_collected_fees_fails() {
+    let mut contract = setup();
+    let collected: Balance = contract.collected_fees().parse().unwrap();
+    call_as(OWNER, 0);
+    contract.withdraw_fees((collected + 1).to_string());
+}
+
+#[test]
+fn removed_collection_minter_stays_removed() {
+    let mut contract = setup();
+    let collection_id = create_collection(&mut contract);
+    call_as(ALICE, 0);
+    contract.add_collection_minter(collection_id, BOB.to_string());
+    assert!(contract.get_collection(collection_id).minters.contains(&BOB.to_string()));
+    contract.remove_collection_minter(collection_id, BOB.to_string());
+    assert!(!contract.get_collection(collection_id).minters.contains(&BOB.to_string()));
+}
+
+#[test]
+#[should_panic(expected = "Deposit must exactly match the required fee.")]
+fn strict_mint_overpaying_the_mint_cost_fails() {
+    let mut contract = setup();
+    contract.set_strict_deposits(true);
+    call_as(ALICE, contract.mint_cost(1).parse::<Balance>().unwrap() + 1);
+    contract.mint_token(metadata(1));
+}
+
+#[test]
+#[should_panic(expected = "Deposit not enough to cover metadata storage fee.")]
+fn minting_with_one_yocto_less_than_mint_cost_fails() {
+    let mut contract = setup();
+    call_as(OWNER, 0);
+    contract.set_mint_fee("3000".to_string());
+    contract.set_edition_fee("200".to_string());
+    assert_eq!(contract.mint_cost(2), "3400");
+    call_as(ALICE, 3_399);
+    contract.mint_token(metadata(2));
+}
+
+#[test]
+fn mint_cost_and_collection_cost_cover_the_deposits_exactly() {
+    let mut contract = setup();
+    call_as(OWNER, 0);
+    contract.set_strict_deposits(true);
+    // strict deposits reject anything but the exact cost
+    let token_id = mint(&mut contract, 2);
+    assert_eq!(contract.owner_of(token_id, 2), ALICE.to_string());
+    let collection_id = create_collection(&mut contract);
+    assert_eq!(contract.get_collection(collection_id).name, "collection");
+}
+
+#[test]
+fn mint_to_gives_the_editions_to_the_recipient_and_keeps_the_minter_as_creator() {
+    let mut contract = setup();
+    let token_id = contract.current_supply;
+    call_as(ALICE, contract.mint_cost(2).parse().unwrap());
+    contract.mint_to(metadata(2), CAROL.to_string());
+    assert_eq!(contract.owner_of(token_id, 1), CAROL.to_string());
+    assert_eq!(contract.owner_of(token_id, 2), CAROL.to_string());
+    assert_eq!(contract.get_metadata(token_id).creator, ALICE.to_string());
+    assert_eq!(contract.tokens_by_owner_count(ALICE.to_string()), 0);
+}
+
+#[test]
+#[should_panic(expected = "Account ID is invalid.")]
+fn mint_to_an_invalid_account_fails() {
+    let mut contract = setup();
+    call_as(ALICE, contract.mint_cost(1).parse().unwrap());
+    contract.mint_to(metadata(1), "Not Valid".to_string());
+}
+
+#[test]
+fn mint_at_exactly_the_royalty_floor() {
+    let mut contract = setup();
+    call_as(OWNER, 0);
+    contract.set_min_royalty_bps(500);
+    assert_eq!(contract.min_royalty_bps(), 500);
+    let token_id = contract.current_supply;
+    call_as(ALICE, contract.mint_cost(1).parse().unwrap());
+    contract.mint_token(Metadata { royalty: 500, ..metadata(1) });
+    assert_eq!(contract.get_metadata(token_id).royalty, 500);
+}
+
+#[test]
+#[should_panic(expected = "Royalty is below the platform minimum of bps 500")]
+fn mint_just_below_the_royalty_floor_fails() {
+    let mut contract = setup();
+    call_as(OWNER, 0);
+    contract.set_min_royalty_bps(500);
+    call_as(ALICE, contract.mint_cost(1).parse().unwrap());
+    contract.mint_token(Metadata { royalty: 499, ..metadata(1) });
+}
+
+#[test]
+fn names_are_limited_in_characters_not_bytes() {
+    let mut contract = setup();
+    // names have to stay under the limit, descriptions may reach it
+    let longest_name = contract.MAX_NAME_LENGTH as usize - 1;
+    let longest_description = contract.MAX_DESCRIPTION_LENGTH as usize;
+    let cjk = contract.current_supply;
+    call_as(ALICE, contract.mint_cost(1).parse().unwrap());
+    contract.mint_token(Metadata { name: "漢".repeat(longest_name), ..metadata(1) });
+    let emoji = contract.current_supply;
+    call_as(ALICE, contract.mint_cost(1).parse().unwrap());
+    contract.mint_token(Metadata { name: "🎨".repeat(longest_name), description: "🖼".repeat(longest_description), ..metadata(1) });
+    assert_eq!(contract.metadata.get(&cjk).unwrap().name.len(), 3 * longest_name);
+    assert_eq!(contract.metadata.get(&emoji).unwrap().description.len(), 4 * longest_description);
+}
+
+#[test]
+#[should_panic(expected = "Name must be under 50 characters long.")]
+fn a_name_at_the_character_limit_fails() {
+    let mut contract = setup();
+    call_as(ALICE, contract.mint_cost(1).parse().unwrap());
+    contract.mint_token(Metadata { name: "漢".repeat(contract.MAX_NAME_LENGTH as usize), ..metadata(1) });
+}
+
+#[test]
+fn validate_metadata_returns_the_failing_check() {
+    let mut contract = setup();
+    call_as(OWNER, 0);
+    contract.set_min_royalty_bps(500);
+    // the messages name fixed limits, the checks use the configured ones
+    let reason = |meta: Metadata| contract.validate_metadata(meta).unwrap_err().split(": ").nth(1).unwrap().to_string();
+    let cases = vec![
+        (metadata(0), "A token needs at least one edition."),
+        (metadata(contract.MAX_EDITIONS as u64 + 1), "Max Edition Number is 20."),
+        (Metadata { description: "d".repeat(contract.MAX_DESCRIPTION_LENGTH as usize + 1), ..metadata(1) }, "Description must be under 250 characters long."),
+        (Metadata { name: "n".repeat(contract.MAX_NAME_LENGTH as usize), ..metadata(1) }, "Name must be under 50 characters long."),
+        (Metadata { external_link: format!("https://{}", "l".repeat(contract.MAX_EXTERNAL_LINK as usize)), ..metadata(1) }, "External link must be under 100 characters long. Please use a url shortener or ipfs."),
+        (Metadata { external_link: "ftp://link".to_string(), ..metadata(1) }, "External link must start with http://, https:// or ipfs://."),
+        (Metadata { tags: vec!["tag".to_string(); 4], ..metadata(1) }, "Only 3 tags allowed."),
+        (Metadata { media_type: Some("png".to_string()), ..metadata(1) }, "Media type must be a MIME type like image/png."),
+        (Metadata { width: Some(0), ..metadata(1) }, "Media dimensions and duration must be positive when given."),
+        (Metadata { royalty: 10_001, ..metadata(1) }, "Royalty cannot exceed 10000 bps."),
+        (Metadata { royalty: 499, ..metadata(1) }, "Royalty is below the platform minimum of bps 500"),
+    ];
+    for (meta, expected) in cases {
+        assert_eq!(reason(meta), expected);
+    }
+    // only the first failure is reported
+    assert_eq!(reason(Metadata { tags: vec!["tag".to_string(); 4], ..metadata(0) }), "A token needs at least one edition.");
+    assert!(contract.validate_metadata(metadata(1)).is_ok());
+}
+
+#[test]
+fn mint_set_links_its_tokens() {
+    let mut contract = setup();
+    let single = mint(&mut contract, 1);
+    let deposit: Balance = contract.mint_cost(1).parse::<Balance>().unwrap() + contract.mint_cost(2).parse::<Balance>().unwrap();
+    call_as(ALICE, deposit);
+    let diptych = contract.mint_set(vec![metadata(1), metadata(2)]);
+    assert_eq!(diptych, vec![single + 1, single + 2]);
+    let set_id = contract.set_of(diptych[0]).unwrap();
+    assert_eq!(contract.set_of(diptych[1]), Some(set_id));
+    assert_eq!(contract.tokens_in_set(set_id), diptych);
+    assert_eq!(contract.set_of(single), None);
+    assert_eq!(contract.get_edition(diptych[1], 2).edition_owner, ALICE);
+}
+
+#[test]
+fn https_and_ipfs_external_links_are_accepted() {
+    let mut contract = setup();
+    for link in &["https://example.org/art", "IPFS://QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG", ""] {
+        let token_id = contract.current_supply;
+        call_as(ALICE, contract.mint_cost(1).parse().unwrap());
+        contract.mint_token(Metadata { external_link: link.to_string(), ..metadata(1) });
+        assert_eq!(contract.get_metadata(token_id).external_link, *link);
+    }
+}
+
+#[test]
+#[should_panic(expected = "External link must start with http://, https:// or ipfs://.")]
+fn javascript_external_link_is_rejected() {
+    let mut contract = setup();
+    call_as(ALICE, contract.mint_cost(1).parse().unwrap());
+    contract.mint_token(Metadata { external_link: "javascript:alert(1)".to_string(), ..metadata(1) });
+}
+
+/// ALICE may mint twice per minute and has minted twice at NOW
+fn rate_limited(contract: &mut NonFungibleToken) -> u64 {
+    let window = 60_000_000_000;
+    call_as(OWNER, 0);
+    contract.set_mint_rate_limit(2, window);
+    assert_eq!(contract.mints_remaining(ALICE.to_string()), 2);
+    mint(contract, 1);
+    mint(contract, 1);
+    assert_eq!(contract.mints_remaining(ALICE.to_string()), 0);
+    window
+}
+
+#[test]
+#[should_panic(expected = "MINT RATE LIMIT REACHED, TRY AGAIN LATER")]
+fn minting_past_the_rate_limit_fails() {
+    let mut contract = setup();
+    let window = rate_limited(&mut contract);
+    call_at(ALICE, contract.mint_cost(1).parse().unwrap(), NOW + window - 1);
+    contract.mint_token(metadata(1));
+}
+
+#[test]
+fn mint_rate_limit_resets_with_the_window() {
+    let mut contract = setup();
+    let window = rate_limited(&mut contract);
+    call_at(ALICE, contract.mint_cost(1).parse().unwrap(), NOW + window);
+    assert_eq!(contract.mints_remaining(ALICE.to_string()), 2);
+    contract.mint_token(metadata(1));
+    assert_eq!(contract.mints_remaining(ALICE.to_string()), 1);
+    // the limit is per account
+    assert_eq!(contract.mints_remaining(BOB.to_string()), 2);
+}
+
+#[test]
+fn overpaid_mint_refunds_the_excess_and_keeps_only_the_cost() {
+    let mut contract = setup();
+    let collected: Balance = contract.collected_fees().parse().unwrap();
+    let cost: Balance = contract.mint_cost(2).parse().unwrap();
+    let attached = 1_000_000_000_000_000_000_000_000;
+    call_as(ALICE, attached);
+    contract.mint_token(metadata(2));
+    assert_eq!(transfers(), vec![(ALICE.to_string(), attached - cost)]);
+    assert_eq!(contract.collected_fees(), (collected + cost).to_string());
+}
+
+#[test]
+fn exactly_paid_mint_refunds_nothing() {
+    let mut contract = setup();
+    mint(&mut contract, 2);
+    assert!(transfers().is_empty());
+}
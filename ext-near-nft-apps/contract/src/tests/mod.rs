@@ -0,0 +1,138 @@
+//! unit tests against the mocked blockchain, one module per feature area.
+//! every call runs in a fresh VMContext while the storage carries over between calls.
+use near_sdk::{env, testing_env, Balance, MockedBlockchain, VMContext};
+use crate::NonFungibleToken;
+use crate::model::{Collection, Metadata};
+use crate::types::{CollectionId, EditionNumber, TokenId};
+
+mod admin;
+mod approvals;
+mod claims;
+mod enumeration;
+mod escrow;
+mod events;
+mod likes;
+mod migration;
+mod minting;
+mod trading;
+
+pub(crate) const OWNER: &str = "owner.near";
+pub(crate) const FEES: &str = "fees.near";
+pub(crate) const ALICE: &str = "alice.near";
+pub(crate) const BOB: &str = "bob.near";
+pub(crate) const CAROL: &str = "carol.near";
+
+// block timestamp calls run at unless a test moves the clock
+pub(crate) const NOW: u64 = 1_000_000_000_000;
+
+pub(crate) fn context(predecessor: &str, deposit: Balance, block_timestamp: u64) -> VMContext {
+    VMContext {
+        current_account_id: "nft.near".to_string(),
+        signer_account_id: predecessor.to_string(),
+        signer_account_pk: vec![0, 1, 2],
+        predecessor_account_id: predecessor.to_string(),
+        input: vec![],
+        block_index: 0,
+        block_timestamp,
+        epoch_height: 0,
+        // enough to cover the staking reserve of the storage headroom below
+        account_balance: 1_000_000_000_000_000_000_000_000_000_000,
+        account_locked_balance: 0,
+        // headroom so a call freeing more storage than it wrote doesn't underflow the fresh context
+        storage_usage: 1_000_000_000,
+        attached_deposit: deposit,
+        prepaid_gas: 300_000_000_000_000,
+        random_seed: vec![0, 1, 2],
+        is_view: false,
+        output_data_receivers: vec![],
+    }
+}
+
+/// next call comes from `predecessor` with `deposit` attached
+pub(crate) fn call_as(predecessor: &str, deposit: Balance) {
+    testing_env!(context(predecessor, deposit, NOW));
+}
+
+/// next call comes from `predecessor` at `block_timestamp`
+pub(crate) fn call_at(predecessor: &str, deposit: Balance, block_timestamp: u64) {
+    testing_env!(context(predecessor, deposit, block_timestamp));
+}
+
+/// (receiver, amount) of every NEAR transfer the current call has made so far. the mocked receipts
+/// only implement Debug, so the transfers are read off their debug output.
+pub(crate) fn transfers() -> Vec<(String, Balance)> {
+    let blockchain = env::take_blockchain_interface().unwrap();
+    let receipts = format!("{:?}", blockchain.as_mocked_blockchain().unwrap().created_receipts());
+    env::set_blockchain_interface(blockchain);
+    receipts
+        .split("receiver_id: \"")
+        .skip(1)
+        .flat_map(|receipt| {
+            let receiver = receipt.split('"').next().unwrap().to_string();
+            receipt
+                .split("Transfer(TransferAction { deposit: ")
+                .skip(1)
+                .map(move |transfer| (receiver.clone(), transfer.split(' ').next().unwrap().parse().unwrap()))
+        })
+        .collect()
+}
+
+/// an initialized, unpaused contract with the genesis collection and ALICE whitelisted as minter
+pub(crate) fn setup() -> NonFungibleToken {
+    call_as(OWNER, 0);
+    let mut contract = NonFungibleToken::new(OWNER.to_string(), FEES.to_string());
+    contract.generate_genesis_collection("Q".repeat(46));
+    contract.add_minter(ALICE.to_string());
+    contract
+}
+
+pub(crate) fn metadata(editions: EditionNumber) -> Metadata {
+    Metadata {
+        name: "token".to_string(),
+        collection_id: 0,
+        creator: String::new(),
+        description: String::new(),
+        thumbnail: "Q".repeat(46),
+        main: "Q".repeat(46),
+        nft_type: "image".to_string(),
+        file: String::new(),
+        external_link: String::new(),
+        royalty: 1_000,
+        editions,
+        date: String::new(),
+        tags: vec![],
+        media_type: None,
+        width: None,
+        height: None,
+        duration_ms: None,
+    }
+}
+
+/// ALICE mints a token with `editions` editions in the genesis collection, all owned by her
+pub(crate) fn mint(contract: &mut NonFungibleToken, editions: EditionNumber) -> TokenId {
+    mint_in(contract, 0, editions)
+}
+
+pub(crate) fn mint_in(contract: &mut NonFungibleToken, collection_id: CollectionId, editions: EditionNumber) -> TokenId {
+    let token_id = contract.current_supply;
+    call_as(ALICE, contract.mint_cost(editions).parse().unwrap());
+    contract.mint_token(Metadata { collection_id, ..metadata(editions) });
+    token_id
+}
+
+/// ALICE creates a collection she is the creator and only minter of
+pub(crate) fn create_collection(contract: &mut NonFungibleToken) -> CollectionId {
+    call_as(ALICE, contract.collection_cost().parse().unwrap());
+    contract.create_collection(Collection {
+        name: "collection".to_string(),
+        description: String::new(),
+        date: String::new(),
+        thumbnail: "Q".repeat(46),
+        creator: String::new(),
+        minters: vec![],
+        trade_start: None,
+        trade_end: None,
+        royalties: vec![],
+    });
+    contract.total_collections
+}
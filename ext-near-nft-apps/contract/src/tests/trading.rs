@@ -0,0 +1,1479 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::Base64VecU8;
+use crate::{EditionState, OfferStatus};
+use crate::model::StoredVector;
+use super::*;
+
+const PRICE: Balance = 1_000_000_000_000_000_000_000_000;
+
+/// edition 1 of a token in a collection trading only between NOW + 100 and NOW + 200, listed by ALICE
+fn windowed_listing(contract: &mut NonFungibleToken) -> TokenId {
+    let collection_id = create_collection(contract);
+    let token_id = mint_in(contract, collection_id, 1);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 1, PRICE.to_string());
+    contract.set_trade_window(collection_id, Some(NOW + 100), Some(NOW + 200));
+    token_id
+}
+
+#[test]
+#[should_panic(expected = "Trading for this collection is closed outside its trade window.")]
+fn buy_before_trade_window_fails() {
+    let mut contract = setup();
+    let token_id = windowed_listing(&mut contract);
+    call_at(BOB, PRICE, NOW + 99);
+    contract.buy(token_id, 1);
+}
+
+#[test]
+fn buy_within_trade_window() {
+    let mut contract = setup();
+    let token_id = windowed_listing(&mut contract);
+    call_at(BOB, PRICE, NOW + 150);
+    contract.buy(token_id, 1);
+    assert_eq!(contract.owner_of(token_id, 1), BOB.to_string());
+}
+
+#[test]
+#[should_panic(expected = "Trading for this collection is closed outside its trade window.")]
+fn offer_after_trade_window_fails() {
+    let mut contract = setup();
+    let token_id = windowed_listing(&mut contract);
+    call_at(BOB, PRICE, NOW + 201);
+    contract.offer(token_id, 1, None, None);
+}
+
+const DAY: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+#[test]
+#[should_panic(expected = "Trading for this collection is closed outside its trade window.")]
+fn auction_cannot_outlast_trade_window() {
+    let mut contract = setup();
+    let collection_id = create_collection(&mut contract);
+    let token_id = mint_in(&mut contract, collection_id, 1);
+    call_as(ALICE, 0);
+    contract.set_trade_window(collection_id, None, Some(NOW + DAY));
+    contract.start_auction(token_id, 1, PRICE.to_string(), 2 * DAY);
+}
+
+/// ALICE's token that needs resale approval, edition 1 sold to BOB
+fn resale_approval_token(contract: &mut NonFungibleToken) -> TokenId {
+    let token_id = mint(contract, 1);
+    call_as(ALICE, 0);
+    contract.set_require_resale_approval(token_id, true);
+    contract.set_price(token_id, 1, PRICE.to_string());
+    call_as(BOB, PRICE);
+    contract.buy(token_id, 1);
+    token_id
+}
+
+#[test]
+#[should_panic(expected = "RESALES OF THIS TOKEN NEED APPROVAL AND CAN'T BE AUCTIONED")]
+fn resale_approval_tokens_cannot_be_auctioned_by_collectors() {
+    let mut contract = setup();
+    let token_id = resale_approval_token(&mut contract);
+    call_as(BOB, 0);
+    contract.start_auction(token_id, 1, PRICE.to_string(), DAY);
+}
+
+#[test]
+#[should_panic(expected = "RESALES OF THIS TOKEN NEED APPROVAL AND CAN'T BE AUCTIONED")]
+fn resale_approval_tokens_cannot_be_sealed_auctioned_by_collectors() {
+    let mut contract = setup();
+    let token_id = resale_approval_token(&mut contract);
+    call_as(BOB, 0);
+    contract.start_sealed_auction(token_id, 1, PRICE.to_string(), DAY, DAY);
+}
+
+#[test]
+#[should_panic(expected = "RESALE TO THIS BUYER IS NOT APPROVED BY THE CREATOR")]
+fn unapproved_resale_fails() {
+    let mut contract = setup();
+    let token_id = resale_approval_token(&mut contract);
+    call_as(BOB, 0);
+    contract.set_price(token_id, 1, PRICE.to_string());
+    call_as(CAROL, PRICE);
+    contract.buy(token_id, 1);
+}
+
+#[test]
+fn approved_resale_uses_up_the_approval() {
+    let mut contract = setup();
+    let token_id = resale_approval_token(&mut contract);
+    call_as(ALICE, 0);
+    contract.approve_resale(token_id, 1, CAROL.to_string());
+    call_as(BOB, 0);
+    contract.set_price(token_id, 1, PRICE.to_string());
+    call_as(CAROL, PRICE);
+    contract.buy(token_id, 1);
+    assert_eq!(contract.owner_of(token_id, 1), CAROL.to_string());
+    assert!(!contract.is_resale_approved(token_id, 1, CAROL.to_string()));
+}
+
+/// ALICE sells edition 1 to BOB, who lists it again at PRICE
+fn resale_listing(contract: &mut NonFungibleToken) -> TokenId {
+    let token_id = mint(contract, 1);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 1, PRICE.to_string());
+    call_as(BOB, PRICE);
+    contract.buy(token_id, 1);
+    call_as(BOB, 0);
+    contract.set_price(token_id, 1, PRICE.to_string());
+    token_id
+}
+
+#[test]
+fn prepaid_royalty_is_not_taken_out_of_the_proceeds() {
+    let mut contract = setup();
+    let token_id = resale_listing(&mut contract);
+    let due: Balance = contract.royalty_for(token_id, PRICE.to_string())[0].1.parse().unwrap();
+    call_as(BOB, due);
+    contract.prepay_royalty(token_id, 1);
+    call_as(CAROL, PRICE);
+    contract.buy(token_id, 1);
+    let proceeds = PRICE - contract._trade_fee_of(PRICE);
+    assert!(transfers().contains(&(BOB.to_string(), proceeds)));
+    assert!(transfers().contains(&(ALICE.to_string(), due)));
+}
+
+#[test]
+fn co_owners_are_paid_the_proceeds_after_royalties() {
+    let mut contract = setup();
+    let token_id = resale_listing(&mut contract);
+    call_as(BOB, 0);
+    contract.fractionalize(token_id, 1, vec![(BOB.to_string(), 5_000), (CAROL.to_string(), 5_000)]);
+    call_as(OWNER, PRICE);
+    contract.buy(token_id, 1);
+    let due: Balance = contract.royalty_for(token_id, PRICE.to_string())[0].1.parse().unwrap();
+    let proceeds = PRICE - contract._trade_fee_of(PRICE) - due;
+    let paid: Balance = transfers().iter().filter(|(account, _)| account == BOB || account == CAROL).map(|(_, amount)| amount).sum();
+    assert_eq!(paid, proceeds);
+}
+
+const LISTING_FEE: Balance = 10_000_000_000_000_000_000_000;
+
+#[test]
+fn listing_fee_is_booked_with_the_collected_fees() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    call_as(OWNER, 0);
+    contract.set_listing_fee(LISTING_FEE.to_string());
+    let collected = contract.collected_fees;
+    call_as(ALICE, 2 * LISTING_FEE);
+    contract.batch_set_price(token_id, vec![1, 2], PRICE.to_string());
+    assert_eq!(contract.collected_fees, collected + 2 * LISTING_FEE);
+}
+
+/// ALICE lists edition 1 paying the listing fee, with a grace period of a day
+fn listing_with_fee(contract: &mut NonFungibleToken) -> TokenId {
+    let token_id = mint(contract, 1);
+    call_as(OWNER, 0);
+    contract.set_listing_fee(LISTING_FEE.to_string());
+    contract.set_listing_grace(DAY);
+    call_as(ALICE, LISTING_FEE);
+    contract.set_price(token_id, 1, PRICE.to_string());
+    token_id
+}
+
+#[test]
+fn cancelling_within_listing_grace_refunds_the_listing_fee() {
+    let mut contract = setup();
+    let token_id = listing_with_fee(&mut contract);
+    let collected = contract.collected_fees;
+    call_at(ALICE, 0, NOW + DAY);
+    assert!(contract.is_within_listing_grace(token_id, 1));
+    contract.cancel_sale(token_id, 1);
+    assert_eq!(transfers(), vec![(ALICE.to_string(), LISTING_FEE)]);
+    assert_eq!(contract.collected_fees, collected - LISTING_FEE);
+}
+
+#[test]
+fn cancelling_after_listing_grace_keeps_the_listing_fee() {
+    let mut contract = setup();
+    let token_id = listing_with_fee(&mut contract);
+    let collected = contract.collected_fees;
+    call_at(ALICE, 0, NOW + DAY + 1);
+    assert!(!contract.is_within_listing_grace(token_id, 1));
+    contract.cancel_sale(token_id, 1);
+    assert!(transfers().is_empty());
+    assert_eq!(contract.collected_fees, collected);
+}
+
+#[test]
+fn grace_refund_is_capped_at_the_fees_still_booked() {
+    let mut contract = setup();
+    let token_id = listing_with_fee(&mut contract);
+    let collected = contract.collected_fees;
+    call_as(OWNER, 0);
+    contract.withdraw_fees((collected - LISTING_FEE / 4).to_string());
+    call_as(ALICE, 0);
+    contract.cancel_sale(token_id, 1);
+    assert_eq!(transfers(), vec![(ALICE.to_string(), LISTING_FEE / 4)]);
+    assert_eq!(contract.collected_fees, 0);
+}
+
+#[test]
+#[should_panic(expected = "DEPOSIT NOT ENOUGH")]
+fn listing_without_the_listing_fee_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(OWNER, 0);
+    contract.set_listing_fee(LISTING_FEE.to_string());
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 1, PRICE.to_string());
+}
+
+#[test]
+fn transferring_a_listed_edition_refunds_the_prepaid_royalty() {
+    let mut contract = setup();
+    let token_id = resale_listing(&mut contract);
+    let due: Balance = contract.royalty_for(token_id, PRICE.to_string())[0].1.parse().unwrap();
+    call_as(BOB, due);
+    contract.prepay_royalty(token_id, 1);
+    call_as(BOB, 1);
+    contract.nft_transfer(CAROL.to_string(), format!("{}::1", token_id), None, None);
+    assert!(transfers().contains(&(BOB.to_string(), due)));
+    assert_eq!(contract.prepaid_royalty(token_id, 1), "0");
+}
+
+/// BOB buys edition 1 of a token whose creator requires a day's hold before resales
+fn held_edition(contract: &mut NonFungibleToken) -> TokenId {
+    let token_id = mint(contract, 1);
+    call_as(ALICE, 0);
+    contract.set_min_hold(token_id, DAY);
+    contract.set_price(token_id, 1, PRICE.to_string());
+    call_as(BOB, PRICE);
+    contract.buy(token_id, 1);
+    token_id
+}
+
+#[test]
+#[should_panic(expected = "This edition is still within the token's minimum hold period and can't be resold yet.")]
+fn auction_within_min_hold_fails() {
+    let mut contract = setup();
+    let token_id = held_edition(&mut contract);
+    call_as(BOB, 0);
+    contract.start_auction(token_id, 1, PRICE.to_string(), DAY);
+}
+
+#[test]
+#[should_panic(expected = "This edition is still within the token's minimum hold period and can't be resold yet.")]
+fn sealed_auction_within_min_hold_fails() {
+    let mut contract = setup();
+    let token_id = held_edition(&mut contract);
+    call_as(BOB, 0);
+    contract.start_sealed_auction(token_id, 1, PRICE.to_string(), DAY, DAY);
+}
+
+#[test]
+fn auction_after_min_hold() {
+    let mut contract = setup();
+    let token_id = held_edition(&mut contract);
+    call_at(BOB, 0, NOW + DAY);
+    contract.start_auction(token_id, 1, PRICE.to_string(), DAY);
+}
+
+#[test]
+fn offer_history_keeps_closed_bids_with_their_status() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(BOB, PRICE);
+    contract.offer(token_id, 1, None, None);
+    call_as(CAROL, PRICE);
+    contract.offer(token_id, 1, None, None);
+    call_as(BOB, 0);
+    contract.cancel_offer(token_id, 1, 0);
+    call_as(ALICE, 0);
+    contract.accept_offer(token_id, 1, 1);
+    let history = contract.offer_history(token_id, 1, 0, 10);
+    let bid_value = PRICE - contract.edition_storage_fee;
+    assert_eq!((history[0].1.bidder.as_str(), history[0].1.amount), (BOB, bid_value));
+    assert!(history[0].2 == OfferStatus::CANCELLED);
+    assert_eq!((history[1].1.bidder.as_str(), history[1].1.amount), (CAROL, bid_value));
+    assert!(history[1].2 == OfferStatus::ACCEPTED);
+}
+
+#[test]
+fn cancel_sale_clears_the_listing() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 2, PRICE.to_string());
+    contract.cancel_sale(token_id, 2);
+    assert!(contract.state_of(token_id, 2) == EditionState::AVAILABLE);
+    assert!(contract.all_listings(0, 10).is_empty());
+}
+
+#[test]
+#[should_panic]
+fn cancelled_listing_has_no_price() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 1, PRICE.to_string());
+    contract.cancel_sale(token_id, 1);
+    contract.get_price(token_id, 1);
+}
+
+/// BOB bids PRICE on edition 1 of a fresh token, the bid expires a day from now
+fn expiring_offer(contract: &mut NonFungibleToken) -> TokenId {
+    let token_id = mint(contract, 1);
+    call_as(BOB, PRICE);
+    contract.offer(token_id, 1, Some(NOW + DAY), None);
+    token_id
+}
+
+#[test]
+fn expired_offer_is_refunded_by_anyone() {
+    let mut contract = setup();
+    let token_id = expiring_offer(&mut contract);
+    call_at(BOB, 0, NOW + DAY + 1);
+    assert!(contract.offer_status(token_id, 1, 0) == OfferStatus::EXPIRED);
+    call_at(CAROL, 0, NOW + DAY + 1);
+    contract.reclaim_expired_offer(token_id, 1, 0);
+    let storage_fee = contract.edition_storage_fee;
+    assert_eq!(transfers(), vec![(BOB.to_string(), PRICE - storage_fee), (BOB.to_string(), storage_fee)]);
+    assert!(contract.offer_status(token_id, 1, 0) == OfferStatus::CANCELLED);
+}
+
+#[test]
+#[should_panic(expected = "OFFER HAS NOT EXPIRED")]
+fn offer_within_its_expiry_cannot_be_reclaimed() {
+    let mut contract = setup();
+    let token_id = expiring_offer(&mut contract);
+    call_at(CAROL, 0, NOW + DAY);
+    contract.reclaim_expired_offer(token_id, 1, 0);
+}
+
+#[test]
+#[should_panic(expected = "OFFER HAS EXPIRED")]
+fn expired_offer_cannot_be_accepted() {
+    let mut contract = setup();
+    let token_id = expiring_offer(&mut contract);
+    call_at(ALICE, 0, NOW + DAY + 1);
+    contract.accept_offer(token_id, 1, 0);
+}
+
+/// a collection of ALICE's splitting royalties 6% to her and 4% to CAROL, with BOB owning edition 1 of a token in it
+fn split_royalty_resale(contract: &mut NonFungibleToken) -> TokenId {
+    let collection_id = create_collection(contract);
+    call_as(ALICE, 0);
+    contract.set_collection_royalties(collection_id, vec![(ALICE.to_string(), 600), (CAROL.to_string(), 400)]);
+    let token_id = mint_in(contract, collection_id, 1);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 1, PRICE.to_string());
+    call_as(BOB, PRICE);
+    contract.buy(token_id, 1);
+    call_as(BOB, 0);
+    contract.set_price(token_id, 1, PRICE.to_string());
+    token_id
+}
+
+#[test]
+fn collection_royalties_are_split_between_recipients() {
+    let mut contract = setup();
+    let token_id = split_royalty_resale(&mut contract);
+    call_as(OWNER, PRICE);
+    contract.buy(token_id, 1);
+    let rest = PRICE - contract._trade_fee_of(PRICE);
+    assert!(transfers().contains(&(ALICE.to_string(), rest * 600 / 10_000)));
+    assert!(transfers().contains(&(CAROL.to_string(), rest * 400 / 10_000)));
+    assert!(transfers().contains(&(BOB.to_string(), rest - rest / 10)));
+}
+
+#[test]
+fn collection_royalty_changes_only_apply_to_later_mints() {
+    let mut contract = setup();
+    let token_id = split_royalty_resale(&mut contract);
+    let collection_id = contract.get_metadata(token_id).collection_id;
+    call_as(ALICE, 0);
+    contract.set_collection_royalties(collection_id, vec![(ALICE.to_string(), 9_000)]);
+    let rest = PRICE - contract._trade_fee_of(PRICE);
+    let royalties = contract.royalty_for(token_id, PRICE.to_string());
+    assert_eq!(royalties, vec![(ALICE.to_string(), (rest * 600 / 10_000).to_string()), (CAROL.to_string(), (rest * 400 / 10_000).to_string())]);
+    let later = mint_in(&mut contract, collection_id, 1);
+    assert_eq!(contract.royalty_for(later, PRICE.to_string()), vec![(ALICE.to_string(), (rest * 9_000 / 10_000).to_string())]);
+}
+
+#[test]
+#[should_panic(expected = "ROYALTIES CANNOT EXCEED 10000 BPS")]
+fn collection_royalties_above_100_percent_are_rejected() {
+    let mut contract = setup();
+    let collection_id = create_collection(&mut contract);
+    call_as(ALICE, 0);
+    contract.set_collection_royalties(collection_id, vec![(ALICE.to_string(), 6_000), (CAROL.to_string(), 4_001)]);
+}
+
+#[test]
+fn fresh_edition_has_no_offers() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    assert!(contract.get_offers(token_id, 1).is_empty());
+}
+
+#[test]
+fn offering_again_replaces_the_active_bid() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(BOB, PRICE);
+    contract.offer(token_id, 1, None, None);
+    call_as(BOB, 2 * PRICE);
+    contract.offer(token_id, 1, None, None);
+    let active = contract.active_offers(token_id, 1, 0, 10);
+    assert_eq!(active.len(), 1);
+    assert_eq!((active[0].0, active[0].1.amount), (1, 2 * PRICE - contract.edition_storage_fee));
+    assert!(contract.offer_status(token_id, 1, 0) == OfferStatus::CANCELLED);
+}
+
+#[test]
+#[should_panic(expected = "Fee and royalty exceed the sale amount. Check trade fee and royalty configuration.")]
+fn royalty_above_the_proceeds_panics_instead_of_wrapping() {
+    let mut contract = setup();
+    let token_id = resale_listing(&mut contract);
+    // a royalty only corrupted state could hold, every setter caps it at 10000 bps
+    let metadata_id = contract.tokens.get(&token_id).unwrap().metadata;
+    contract.royalty_bps.insert(&metadata_id, &20_000);
+    call_as(CAROL, PRICE);
+    contract.buy(token_id, 1);
+}
+
+const FT: &str = "usdc.near";
+
+/// edition 1 of ALICE's token listed at PRICE in FT, which the owner accepts
+fn ft_listing(contract: &mut NonFungibleToken) -> TokenId {
+    let token_id = mint(contract, 1);
+    call_as(OWNER, 0);
+    contract.add_accepted_ft(FT.to_string(), 6);
+    call_as(ALICE, 0);
+    contract.set_price_in_ft(token_id, 1, FT.to_string(), PRICE.to_string());
+    token_id
+}
+
+#[test]
+fn edition_listed_in_an_accepted_ft_is_bought_through_ft_on_transfer() {
+    let mut contract = setup();
+    let token_id = ft_listing(&mut contract);
+    assert_eq!(contract.accepted_fts(), vec![FT.to_string()]);
+    assert_eq!(contract.listing_ft(token_id, 1), Some(FT.to_string()));
+    call_as(FT, 0);
+    let unused = contract.ft_on_transfer(BOB.to_string(), (PRICE + 5).into(), format!("{{\"token_id\":{},\"edition_id\":1}}", token_id));
+    assert_eq!(unused.0, 5);
+    assert_eq!(contract.owner_of(token_id, 1), BOB.to_string());
+    // every payout is an ft_transfer, no NEAR moves
+    assert!(transfers().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "FT NOT ACCEPTED")]
+fn listing_in_an_unregistered_ft_is_rejected() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.set_price_in_ft(token_id, 1, FT.to_string(), PRICE.to_string());
+}
+
+#[test]
+#[should_panic(expected = "FT NOT ACCEPTED")]
+fn offer_in_an_unregistered_ft_is_rejected() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(FT, 0);
+    contract.ft_on_transfer(BOB.to_string(), PRICE.into(), format!("{{\"token_id\":{},\"edition_id\":1,\"offer\":true}}", token_id));
+}
+
+#[test]
+#[should_panic(expected = "FT NOT ACCEPTED")]
+fn ft_offer_cannot_be_accepted_once_its_ft_is_removed() {
+    let mut contract = setup();
+    let token_id = ft_listing(&mut contract);
+    call_as(FT, 0);
+    contract.ft_on_transfer(BOB.to_string(), PRICE.into(), format!("{{\"token_id\":{},\"edition_id\":1,\"offer\":true}}", token_id));
+    assert_eq!(contract.ft_offers(token_id, 1)[0].amount, PRICE);
+    call_as(OWNER, 0);
+    contract.remove_accepted_ft(FT.to_string());
+    call_as(ALICE, 0);
+    contract.accept_ft_offer(token_id, 1, 0);
+}
+
+#[test]
+#[should_panic(expected = "LISTING IS PRICED IN AN FT")]
+fn ft_listing_cannot_be_bought_with_near() {
+    let mut contract = setup();
+    let token_id = ft_listing(&mut contract);
+    call_as(BOB, PRICE);
+    contract.buy(token_id, 1);
+}
+
+#[test]
+fn relisting_an_available_edition() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 1, PRICE.to_string());
+    contract.cancel_sale(token_id, 1);
+    contract.relist(token_id, 1, (2 * PRICE).to_string());
+    assert!(contract.state_of(token_id, 1) == EditionState::LISTED);
+    assert_eq!(contract.get_price(token_id, 1), 2 * PRICE);
+}
+
+#[test]
+#[should_panic(expected = "Only an available edition can be relisted, it is listed, locked or burned.")]
+fn relisting_a_burned_edition_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    call_as(ALICE, 0);
+    contract.burn_edition(token_id, 2);
+    contract.relist(token_id, 2, PRICE.to_string());
+}
+
+#[test]
+fn otc_royalty_is_charged_on_the_proceeds_and_the_excess_refunded_to_the_seller() {
+    let mut contract = setup();
+    let token_id = split_royalty_resale(&mut contract);
+    call_as(OWNER, 0);
+    contract.set_otc_royalty_required(true);
+    let rest = PRICE - contract._trade_fee_of(PRICE);
+    let due = rest / 10;
+    call_as(BOB, due + 7);
+    contract.record_otc_sale(token_id, 1, CAROL.to_string(), PRICE.to_string());
+    assert_eq!(contract.owner_of(token_id, 1), CAROL.to_string());
+    assert_eq!(contract.last_sales.get(&(contract.tokens.get(&token_id).unwrap().edition_index + 1)), Some(PRICE));
+    assert_eq!(transfers(), vec![
+        (ALICE.to_string(), rest * 600 / 10_000),
+        (CAROL.to_string(), rest * 400 / 10_000),
+        (BOB.to_string(), 7),
+    ]);
+}
+
+#[test]
+#[should_panic(expected = "Deposit not enough")]
+fn otc_sale_without_the_required_royalty_fails() {
+    let mut contract = setup();
+    let token_id = split_royalty_resale(&mut contract);
+    call_as(OWNER, 0);
+    contract.set_otc_royalty_required(true);
+    call_as(BOB, 0);
+    contract.record_otc_sale(token_id, 1, CAROL.to_string(), PRICE.to_string());
+}
+
+#[test]
+fn partial_otc_royalty_is_shared_between_the_recipients() {
+    let mut contract = setup();
+    let token_id = split_royalty_resale(&mut contract);
+    let due = (PRICE - contract._trade_fee_of(PRICE)) / 10;
+    call_as(BOB, due / 2);
+    contract.record_otc_sale(token_id, 1, CAROL.to_string(), PRICE.to_string());
+    assert_eq!(transfers(), vec![(ALICE.to_string(), due * 6 / 10 / 2), (CAROL.to_string(), due * 4 / 10 / 2)]);
+}
+
+/// edition 1 of ALICE's token in a day-long auction with PRICE reserve, CAROL bid PRICE and BOB outbid her
+fn outbid_auction(contract: &mut NonFungibleToken) -> TokenId {
+    let token_id = mint(contract, 1);
+    call_as(ALICE, 0);
+    contract.start_auction(token_id, 1, PRICE.to_string(), DAY);
+    assert!(contract.state_of(token_id, 1) == EditionState::LOCKED);
+    call_as(CAROL, PRICE);
+    contract.auction_bid(token_id, 1);
+    call_as(BOB, 2 * PRICE);
+    contract.auction_bid(token_id, 1);
+    assert_eq!(transfers(), vec![(CAROL.to_string(), PRICE)]);
+    token_id
+}
+
+#[test]
+fn settled_auction_hands_the_edition_to_the_highest_bidder() {
+    let mut contract = setup();
+    let token_id = outbid_auction(&mut contract);
+    let auction = contract.get_auction(token_id, 1).unwrap();
+    assert_eq!(auction.highest_bidder, Some(BOB.to_string()));
+    assert_eq!(auction.highest_bid, 2 * PRICE);
+    call_at(CAROL, 0, NOW + DAY);
+    contract.settle_auction(token_id, 1);
+    assert_eq!(contract.owner_of(token_id, 1), BOB.to_string());
+    assert!(contract.state_of(token_id, 1) == EditionState::AVAILABLE);
+    assert!(contract.get_auction(token_id, 1).is_none());
+    let paid: Balance = transfers().iter().map(|(_, amount)| amount).sum();
+    assert_eq!(paid, 2 * PRICE);
+}
+
+#[test]
+#[should_panic(expected = "AUCTION HAS NOT ENDED")]
+fn auction_cannot_be_settled_before_it_ends() {
+    let mut contract = setup();
+    let token_id = outbid_auction(&mut contract);
+    call_at(CAROL, 0, NOW + DAY - 1);
+    contract.settle_auction(token_id, 1);
+}
+
+#[test]
+#[should_panic(expected = "This edition is burned or locked.")]
+fn edition_in_an_auction_cannot_be_transferred() {
+    let mut contract = setup();
+    let token_id = outbid_auction(&mut contract);
+    call_as(ALICE, 1);
+    contract.nft_transfer(CAROL.to_string(), format!("{}::1", token_id), None, None);
+}
+
+#[test]
+fn auction_without_bids_settles_back_to_the_seller() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.start_auction(token_id, 1, PRICE.to_string(), DAY);
+    call_at(BOB, 0, NOW + DAY);
+    contract.settle_auction(token_id, 1);
+    assert_eq!(contract.owner_of(token_id, 1), ALICE.to_string());
+    assert!(contract.state_of(token_id, 1) == EditionState::AVAILABLE);
+    assert!(transfers().is_empty());
+}
+
+fn sealed_bid(bidder: &str, amount: Balance, salt: &str) -> Base64VecU8 {
+    Base64VecU8(env::sha256(format!("{}:{}:{}", bidder, amount, salt).as_bytes()))
+}
+
+/// edition 1 of ALICE's token in a sealed auction with PRICE reserve, a day to commit and a day to reveal.
+/// BOB commits a bid of 2 * PRICE under a 3 * PRICE deposit, CAROL commits PRICE.
+fn committed_sealed_auction(contract: &mut NonFungibleToken) -> TokenId {
+    let token_id = mint(contract, 1);
+    call_as(ALICE, 0);
+    contract.start_sealed_auction(token_id, 1, PRICE.to_string(), DAY, DAY);
+    assert!(contract.state_of(token_id, 1) == EditionState::LOCKED);
+    call_as(BOB, 3 * PRICE);
+    contract.commit_bid(token_id, 1, sealed_bid(BOB, 2 * PRICE, "bob"));
+    call_as(CAROL, PRICE);
+    contract.commit_bid(token_id, 1, sealed_bid(CAROL, PRICE, "carol"));
+    assert_eq!(contract.get_sealed_auction(token_id, 1).unwrap().unrevealed, 4 * PRICE);
+    token_id
+}
+
+#[test]
+fn revealing_the_highest_bid_refunds_the_rest_of_the_deposit() {
+    let mut contract = setup();
+    let token_id = committed_sealed_auction(&mut contract);
+    call_at(BOB, 0, NOW + DAY);
+    contract.reveal_bid(token_id, 1, (2 * PRICE).to_string(), "bob".to_string());
+    assert_eq!(transfers(), vec![(BOB.to_string(), PRICE)]);
+    let auction = contract.get_sealed_auction(token_id, 1).unwrap();
+    assert_eq!(auction.highest_bidder, Some(BOB.to_string()));
+    assert_eq!(auction.highest_bid, 2 * PRICE);
+    assert_eq!(auction.unrevealed, PRICE);
+}
+
+#[test]
+#[should_panic(expected = "BID DOES NOT MATCH COMMIT")]
+fn revealing_another_amount_than_committed_fails() {
+    let mut contract = setup();
+    let token_id = committed_sealed_auction(&mut contract);
+    call_at(BOB, 0, NOW + DAY);
+    contract.reveal_bid(token_id, 1, (3 * PRICE).to_string(), "bob".to_string());
+}
+
+#[test]
+fn settled_sealed_auction_hands_the_edition_to_the_highest_revealed_bid() {
+    let mut contract = setup();
+    let token_id = committed_sealed_auction(&mut contract);
+    call_at(BOB, 0, NOW + DAY);
+    contract.reveal_bid(token_id, 1, (2 * PRICE).to_string(), "bob".to_string());
+    call_at(CAROL, 0, NOW + DAY);
+    contract.reveal_bid(token_id, 1, PRICE.to_string(), "carol".to_string());
+    assert_eq!(transfers(), vec![(CAROL.to_string(), PRICE)]);
+    call_at(CAROL, 0, NOW + 2 * DAY);
+    contract.settle_sealed_auction(token_id, 1);
+    assert_eq!(contract.owner_of(token_id, 1), BOB.to_string());
+    assert!(contract.state_of(token_id, 1) == EditionState::AVAILABLE);
+    assert!(contract.get_sealed_auction(token_id, 1).is_none());
+    let paid: Balance = transfers().iter().map(|(_, amount)| amount).sum();
+    assert_eq!(paid, 2 * PRICE);
+}
+
+#[test]
+fn unrevealed_deposits_are_forfeited_to_the_fee_receiver() {
+    let mut contract = setup();
+    let token_id = committed_sealed_auction(&mut contract);
+    call_at(BOB, 0, NOW + DAY);
+    contract.reveal_bid(token_id, 1, (2 * PRICE).to_string(), "bob".to_string());
+    call_at(BOB, 0, NOW + 2 * DAY);
+    contract.settle_sealed_auction(token_id, 1);
+    assert_eq!(transfers()[0], (FEES.to_string(), PRICE));
+    assert_eq!(contract.owner_of(token_id, 1), BOB.to_string());
+}
+
+#[test]
+fn sealed_auction_without_reveals_forfeits_every_deposit_and_returns_to_the_seller() {
+    let mut contract = setup();
+    let token_id = committed_sealed_auction(&mut contract);
+    call_at(BOB, 0, NOW + 2 * DAY);
+    contract.settle_sealed_auction(token_id, 1);
+    assert_eq!(transfers(), vec![(FEES.to_string(), 4 * PRICE)]);
+    assert_eq!(contract.owner_of(token_id, 1), ALICE.to_string());
+    assert!(contract.state_of(token_id, 1) == EditionState::AVAILABLE);
+}
+
+/// edition 1 of a fresh token of ALICE's, with strict deposits set to `strict`
+fn offer_target(contract: &mut NonFungibleToken, strict: bool) -> TokenId {
+    let token_id = mint(contract, 1);
+    call_as(OWNER, 0);
+    contract.set_strict_deposits(strict);
+    token_id
+}
+
+#[test]
+fn strict_offer_of_the_exact_amount_plus_storage_fee() {
+    let mut contract = setup();
+    let token_id = offer_target(&mut contract, true);
+    call_as(BOB, PRICE + contract.edition_storage_fee);
+    contract.offer(token_id, 1, None, Some(PRICE.to_string()));
+    assert_eq!(contract.get_offers(token_id, 1)[0].amount, PRICE);
+    assert!(transfers().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "Deposit must exactly match the required fee.")]
+fn strict_offer_overpaying_its_amount_fails() {
+    let mut contract = setup();
+    let token_id = offer_target(&mut contract, true);
+    call_as(BOB, PRICE + contract.edition_storage_fee + 1);
+    contract.offer(token_id, 1, None, Some(PRICE.to_string()));
+}
+
+#[test]
+#[should_panic(expected = "STRICT DEPOSITS NEED THE OFFERED AMOUNT")]
+fn strict_offer_without_an_amount_fails() {
+    let mut contract = setup();
+    let token_id = offer_target(&mut contract, true);
+    call_as(BOB, PRICE + contract.edition_storage_fee);
+    contract.offer(token_id, 1, None, None);
+}
+
+#[test]
+fn lenient_offer_refunds_the_deposit_above_its_amount() {
+    let mut contract = setup();
+    let token_id = offer_target(&mut contract, false);
+    call_as(BOB, PRICE + contract.edition_storage_fee + 7);
+    contract.offer(token_id, 1, None, Some(PRICE.to_string()));
+    assert_eq!(contract.get_offers(token_id, 1)[0].amount, PRICE);
+    assert_eq!(transfers(), vec![(BOB.to_string(), 7)]);
+}
+
+#[test]
+fn lenient_offer_without_an_amount_bids_the_deposit() {
+    let mut contract = setup();
+    let token_id = offer_target(&mut contract, false);
+    call_as(BOB, PRICE + contract.edition_storage_fee + 7);
+    contract.offer(token_id, 1, None, None);
+    assert_eq!(contract.get_offers(token_id, 1)[0].amount, PRICE + 7);
+    assert!(transfers().is_empty());
+}
+
+/// edition 1 of ALICE's token in her own collection, with BOB's collection offer of PRICE as offer 0
+fn collection_offer(contract: &mut NonFungibleToken) -> (CollectionId, TokenId) {
+    let collection_id = create_collection(contract);
+    let token_id = mint_in(contract, collection_id, 1);
+    call_as(BOB, PRICE + contract.edition_storage_fee);
+    contract.offer_collection(collection_id);
+    (collection_id, token_id)
+}
+
+#[test]
+fn accepted_collection_offer_sells_the_edition_to_the_bidder() {
+    let mut contract = setup();
+    let (collection_id, token_id) = collection_offer(&mut contract);
+    call_as(ALICE, 0);
+    contract.accept_collection_offer(collection_id, token_id, 1, 0);
+    assert_eq!(contract.owner_of(token_id, 1), BOB.to_string());
+    assert!(contract.get_collection_offers(collection_id)[0].executed);
+}
+
+#[test]
+#[should_panic(expected = "This account is blocked from using the contract.")]
+fn collection_offer_of_a_denied_bidder_cannot_be_accepted() {
+    let mut contract = setup();
+    let (collection_id, token_id) = collection_offer(&mut contract);
+    call_as(OWNER, 0);
+    contract.deny(BOB.to_string());
+    call_as(ALICE, 0);
+    contract.accept_collection_offer(collection_id, token_id, 1, 0);
+}
+
+#[test]
+#[should_panic(expected = "Maintenance going on. Minting and transfers are temporarily disabled.")]
+fn collection_offer_cannot_be_accepted_while_paused() {
+    let mut contract = setup();
+    let (collection_id, token_id) = collection_offer(&mut contract);
+    call_as(OWNER, 0);
+    contract.pause();
+    call_as(ALICE, 0);
+    contract.accept_collection_offer(collection_id, token_id, 1, 0);
+}
+
+#[test]
+fn collector_volume_accumulates_over_buys_and_won_auctions() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 1, PRICE.to_string());
+    contract.start_auction(token_id, 2, PRICE.to_string(), DAY);
+    call_as(BOB, PRICE);
+    contract.buy(token_id, 1);
+    assert_eq!(contract.collector_volume_of(BOB.to_string()), PRICE.to_string());
+    call_as(BOB, 2 * PRICE);
+    contract.auction_bid(token_id, 2);
+    call_at(BOB, 0, NOW + DAY);
+    contract.settle_auction(token_id, 2);
+    let summary = contract.account_summary(BOB.to_string());
+    assert_eq!(summary.collector_volume, (3 * PRICE).to_string());
+    assert_eq!(summary.editions_owned, 2);
+    assert_eq!(contract.collector_volume_of(ALICE.to_string()), "0");
+}
+
+#[test]
+fn force_unlock_ends_the_auction_and_refunds_its_highest_bidder() {
+    let mut contract = setup();
+    let token_id = outbid_auction(&mut contract);
+    call_as(OWNER, 0);
+    contract.set_emergency_stop(true);
+    contract.force_unlock_edition(token_id, 1);
+    assert!(contract.state_of(token_id, 1) == EditionState::AVAILABLE);
+    assert!(contract.get_auction(token_id, 1).is_none());
+    assert_eq!(transfers(), vec![(BOB.to_string(), 2 * PRICE)]);
+    assert_eq!(contract.owner_of(token_id, 1), ALICE.to_string());
+}
+
+#[test]
+#[should_panic(expected = "SET THE EMERGENCY STOP BEFORE FORCE UNLOCKING")]
+fn force_unlock_needs_the_emergency_stop() {
+    let mut contract = setup();
+    let token_id = outbid_auction(&mut contract);
+    call_as(OWNER, 0);
+    contract.pause();
+    contract.force_unlock_edition(token_id, 1);
+}
+
+#[test]
+fn royalty_of_750_bps_pays_7_5_percent_of_the_proceeds() {
+    let mut contract = setup();
+    let token_id = contract.current_supply;
+    call_as(ALICE, contract.mint_cost(1).parse().unwrap());
+    contract.mint_token(Metadata { royalty: 750, ..metadata(1) });
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 1, PRICE.to_string());
+    call_as(BOB, PRICE);
+    contract.buy(token_id, 1);
+    call_as(BOB, 0);
+    contract.set_price(token_id, 1, PRICE.to_string());
+    call_as(CAROL, PRICE);
+    contract.buy(token_id, 1);
+    let fee = contract._trade_fee_of(PRICE);
+    // 7.5% of what is left after the trade fee
+    let royalty = (PRICE - fee) * 75 / 1_000;
+    assert_eq!(transfers(), vec![(FEES.to_string(), fee), (ALICE.to_string(), royalty), (BOB.to_string(), PRICE - fee - royalty)]);
+}
+
+#[test]
+fn cancelled_offer_refunds_the_whole_deposit() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    let storage_fee = contract.edition_storage_fee;
+    call_as(BOB, PRICE + storage_fee);
+    contract.offer(token_id, 1, None, None);
+    assert_eq!(contract.get_offers(token_id, 1)[0].amount, PRICE);
+    call_as(BOB, 0);
+    contract.cancel_offer(token_id, 1, 0);
+    // the net bid and its storage reservation add up to what was attached
+    assert_eq!(transfers(), vec![(BOB.to_string(), PRICE), (BOB.to_string(), storage_fee)]);
+    assert!(contract.get_offers(token_id, 1)[0].cancelled);
+}
+
+#[test]
+fn accepted_offer_pays_the_bid_value_and_returns_the_storage_reservation() {
+    let mut contract = setup();
+    let token_id = resale_listing(&mut contract);
+    let storage_fee = contract.edition_storage_fee;
+    call_as(CAROL, PRICE + storage_fee);
+    contract.offer(token_id, 1, None, None);
+    call_as(BOB, 0);
+    contract.accept_offer(token_id, 1, 0);
+    let fee = contract._trade_fee_of(PRICE);
+    let royalty = (PRICE - fee) / 10;
+    assert_eq!(transfers(), vec![
+        (CAROL.to_string(), storage_fee),
+        (FEES.to_string(), fee),
+        (ALICE.to_string(), royalty),
+        (BOB.to_string(), PRICE - fee - royalty),
+    ]);
+    assert_eq!(contract.owner_of(token_id, 1), CAROL.to_string());
+}
+
+#[test]
+fn set_prices_lists_each_edition_at_its_own_price() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 3);
+    call_as(ALICE, 0);
+    contract.set_prices(token_id, vec![(1, PRICE.to_string()), (2, (2 * PRICE).to_string()), (3, (3 * PRICE).to_string())]);
+    assert_eq!(contract.get_price(token_id, 1), PRICE);
+    assert_eq!(contract.get_price(token_id, 2), 2 * PRICE);
+    assert_eq!(contract.get_price(token_id, 3), 3 * PRICE);
+}
+
+#[test]
+#[should_panic(expected = "Invalid amount")]
+fn set_prices_rejects_an_unparsable_price() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    call_as(ALICE, 0);
+    contract.set_prices(token_id, vec![(1, PRICE.to_string()), (2, "two".to_string())]);
+}
+
+#[test]
+fn export_edition_bundles_the_listing_offers_allowances_and_last_sale() {
+    let mut contract = setup();
+    let token_id = resale_listing(&mut contract);
+    call_as(CAROL, PRICE);
+    contract.offer(token_id, 1, None, None);
+    call_as(BOB, 1);
+    contract.nft_approve(format!("{}::1", token_id), OWNER.to_string(), None);
+    let export = contract.export_edition(token_id, 1);
+    assert_eq!(export.edition.unwrap().edition_owner, BOB.to_string());
+    assert!(export.state == EditionState::LISTED);
+    assert_eq!(export.price, Some(PRICE.to_string()));
+    assert_eq!(export.offers.len(), 1);
+    assert_eq!(export.offers[0].bidder, CAROL.to_string());
+    assert_eq!(export.allowances, vec![OWNER.to_string()]);
+    assert_eq!(export.last_sale, Some(PRICE.to_string()));
+}
+
+#[test]
+fn offer_percent_bids_that_share_of_the_listing_price() {
+    let mut contract = setup();
+    let token_id = resale_listing(&mut contract);
+    let storage_fee = contract.edition_storage_fee;
+    call_as(CAROL, PRICE + storage_fee);
+    contract.offer_percent(token_id, 1, 90);
+    assert_eq!(contract.get_offers(token_id, 1)[0].amount, PRICE * 90 / 100);
+    assert_eq!(transfers(), vec![(CAROL.to_string(), PRICE / 10)]);
+}
+
+#[test]
+#[should_panic(expected = "EDITION IS NOT LISTED")]
+fn offer_percent_on_an_unlisted_edition_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(BOB, PRICE);
+    contract.offer_percent(token_id, 1, 90);
+}
+
+#[test]
+fn displayed_bid_is_what_the_sale_pays_out() {
+    let mut contract = setup();
+    let token_id = resale_listing(&mut contract);
+    let storage_fee = contract.edition_storage_fee;
+    call_as(CAROL, PRICE + storage_fee);
+    contract.offer(token_id, 1, None, None);
+    let bid = contract.highest_offer(token_id, 1).unwrap();
+    assert_eq!((bid.amount, bid.storage_fee), (PRICE, storage_fee));
+    call_as(BOB, 0);
+    contract.accept_offer(token_id, 1, 0);
+    // fee, royalty and seller proceeds add up to the displayed bid
+    let paid_out: Balance = transfers().iter().filter(|(account, _)| account != CAROL).map(|(_, amount)| amount).sum();
+    assert_eq!(paid_out, bid.amount);
+}
+
+#[test]
+fn offer_status_of_each_bid() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    assert!(contract.offer_status(token_id, 1, 0) == OfferStatus::NOTFOUND);
+    call_as(BOB, PRICE);
+    contract.offer(token_id, 1, Some(NOW + DAY), None);
+    call_as(CAROL, PRICE);
+    contract.offer(token_id, 1, None, None);
+    call_as(OWNER, 2 * PRICE);
+    contract.offer(token_id, 1, None, None);
+    assert!(contract.offer_status(token_id, 1, 0) == OfferStatus::ACTIVE);
+    call_as(CAROL, 0);
+    contract.cancel_offer(token_id, 1, 1);
+    call_at(ALICE, 0, NOW + DAY + 1);
+    contract.accept_offer(token_id, 1, 2);
+    assert!(contract.offer_status(token_id, 1, 0) == OfferStatus::EXPIRED);
+    assert!(contract.offer_status(token_id, 1, 1) == OfferStatus::CANCELLED);
+    assert!(contract.offer_status(token_id, 1, 2) == OfferStatus::ACCEPTED);
+    assert!(contract.offer_status(token_id, 1, 3) == OfferStatus::NOTFOUND);
+}
+
+#[test]
+fn sweep_buys_the_cheapest_listings_and_refunds_the_rest() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 4);
+    call_as(ALICE, 0);
+    contract.set_prices(token_id, vec![(1, (4 * PRICE).to_string()), (2, PRICE.to_string()), (3, (3 * PRICE).to_string()), (4, (2 * PRICE).to_string())]);
+    call_as(BOB, 4 * PRICE);
+    contract.sweep(token_id, 2);
+    assert_eq!(contract.owner_of(token_id, 2), BOB.to_string());
+    assert_eq!(contract.owner_of(token_id, 4), BOB.to_string());
+    assert_eq!(contract.owner_of(token_id, 1), ALICE.to_string());
+    assert_eq!(contract.owner_of(token_id, 3), ALICE.to_string());
+    assert_eq!(transfers().last(), Some(&(BOB.to_string(), PRICE)));
+}
+
+#[test]
+fn creator_recalls_an_edition_paying_the_owner_the_recall_price() {
+    let mut contract = setup();
+    let token_id = contract.current_supply;
+    call_as(ALICE, contract.mint_cost(2).parse().unwrap());
+    contract.mint_recallable(metadata(2), PRICE.to_string());
+    call_as(ALICE, 1);
+    contract.nft_transfer(BOB.to_string(), format!("{}::1", token_id), None, None);
+
+    call_as(ALICE, PRICE);
+    contract.recall_edition(token_id, 1);
+    assert_eq!(contract.get_edition(token_id, 1).edition_owner, ALICE);
+    assert_eq!(transfers(), vec![(BOB.to_string(), PRICE)]);
+    assert_eq!(contract.recall_price(token_id), Some(PRICE.to_string()));
+}
+
+#[test]
+#[should_panic(expected = "TOKEN IS NOT RECALLABLE")]
+fn recalling_an_edition_of_a_regular_token_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 1);
+    contract.nft_transfer(BOB.to_string(), format!("{}::1", token_id), None, None);
+    call_as(ALICE, PRICE);
+    contract.recall_edition(token_id, 1);
+}
+
+#[test]
+fn transfer_and_clear_offers_refunds_every_active_offer() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    for bidder in &[BOB, CAROL] {
+        call_as(bidder, PRICE);
+        contract.offer(token_id, 1, None, None);
+    }
+
+    call_as(ALICE, 0);
+    assert_eq!(contract.transfer_and_clear_offers(OWNER.to_string(), token_id, 1), 0);
+    assert_eq!(contract.get_edition(token_id, 1).edition_owner, OWNER);
+    let refunded = |bidder: &str| transfers().into_iter().filter(|(to, _)| to == bidder).map(|(_, amount)| amount).sum::<Balance>();
+    assert_eq!((refunded(BOB), refunded(CAROL)), (PRICE, PRICE));
+    assert!(contract.offer_status(token_id, 1, 0) == OfferStatus::CANCELLED);
+    assert!(contract.offer_status(token_id, 1, 1) == OfferStatus::CANCELLED);
+}
+
+#[test]
+fn royalty_for_quotes_what_a_sale_at_that_price_pays() {
+    let mut contract = setup();
+    let token_id = split_royalty_resale(&mut contract);
+    // a price that doesn't split evenly, the quote has to round like the sale does
+    let price = PRICE + 7;
+    call_as(BOB, 0);
+    contract.cancel_sale(token_id, 1);
+    contract.set_price(token_id, 1, price.to_string());
+    let quote: Vec<(String, Balance)> = contract
+        .royalty_for(token_id, price.to_string())
+        .into_iter()
+        .map(|(account, amount)| (account, amount.parse().unwrap()))
+        .collect();
+    assert_eq!(quote.len(), 2);
+
+    call_as(OWNER, price);
+    contract.buy(token_id, 1);
+    for royalty in quote {
+        assert!(transfers().contains(&royalty));
+    }
+}
+
+#[test]
+fn outbid_bidder_is_refunded_minus_the_outbid_fee() {
+    let mut contract = setup();
+    call_as(OWNER, 0);
+    contract.set_outbid_fee_bps(200);
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.start_auction(token_id, 1, PRICE.to_string(), DAY);
+    call_as(CAROL, PRICE);
+    contract.auction_bid(token_id, 1);
+    call_as(BOB, 2 * PRICE);
+    contract.auction_bid(token_id, 1);
+    let fee = PRICE * 2 / 100;
+    assert_eq!(transfers(), vec![(FEES.to_string(), fee), (CAROL.to_string(), PRICE - fee)]);
+}
+
+#[test]
+fn active_offers_skip_cancelled_bids_and_keep_their_positions() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    for bidder in &[BOB, CAROL, OWNER] {
+        call_as(bidder, PRICE);
+        contract.offer(token_id, 1, None, None);
+    }
+    call_as(CAROL, 0);
+    contract.cancel_offer(token_id, 1, 1);
+    let active = |from_index, limit| -> Vec<(u64, String)> {
+        contract.active_offers(token_id, 1, from_index, limit).into_iter().map(|(idx, bid)| (idx, bid.bidder)).collect()
+    };
+    assert_eq!(active(0, 10), vec![(0, BOB.to_string()), (2, OWNER.to_string())]);
+    // from_index counts live bids, not positions
+    assert_eq!(active(1, 10), vec![(2, OWNER.to_string())]);
+    assert_eq!(active(0, 1), vec![(0, BOB.to_string())]);
+}
+
+#[test]
+fn each_edition_keeps_its_offers_under_its_own_prefix() {
+    let mut contract = setup();
+    let first = mint(&mut contract, 11);
+    let second = mint(&mut contract, 1);
+    call_as(BOB, PRICE);
+    contract.offer(first, 11, None, None);
+    call_as(CAROL, 2 * PRICE);
+    contract.offer(second, 1, None, None);
+    call_as(BOB, PRICE);
+    contract.offer(first, 1, None, None);
+
+    assert_eq!(contract.offer_lists, 3);
+    let prefix = |token_id, edition_id| {
+        let offers = contract.offers.get(&contract.gen_token_x_edition(token_id, edition_id)).unwrap();
+        StoredVector::try_from_slice(&offers.try_to_vec().unwrap()).unwrap().prefix
+    };
+    assert_eq!(prefix(first, 11), b"ov1".to_vec());
+    assert_eq!(prefix(second, 1), b"ov2".to_vec());
+    assert_eq!(prefix(first, 1), b"ov3".to_vec());
+    let bidders = |token_id, edition_id| contract.get_offers(token_id, edition_id).into_iter().map(|bid| bid.bidder).collect::<Vec<_>>();
+    assert_eq!(bidders(first, 11), vec![BOB.to_string()]);
+    assert_eq!(bidders(second, 1), vec![CAROL.to_string()]);
+    assert_eq!(bidders(first, 1), vec![BOB.to_string()]);
+}
+
+#[test]
+fn all_listings_follows_listing_cancelling_buying_and_transferring() {
+    let mut contract = setup();
+    let first = mint(&mut contract, 3);
+    let second = mint(&mut contract, 2);
+    call_as(ALICE, 0);
+    for (token_id, edition_id) in &[(first, 1), (first, 2), (first, 3), (second, 1), (second, 2)] {
+        contract.set_price(*token_id, *edition_id, PRICE.to_string());
+    }
+    contract.cancel_sale(first, 2);
+    call_as(ALICE, 1);
+    contract.nft_transfer(CAROL.to_string(), format!("{}::1", second), None, None);
+    call_as(BOB, PRICE);
+    contract.buy(first, 3);
+
+    let mut listed: Vec<(TokenId, EditionNumber)> = contract.all_listings(0, 10).into_iter().map(|(token_id, edition_id, _)| (token_id, edition_id)).collect();
+    listed.sort_unstable();
+    assert_eq!(listed, vec![(first, 1), (second, 2)]);
+    assert_eq!(contract.all_listings(1, 10).len(), 1);
+    assert!(contract.all_listings(0, 10).iter().all(|(_, _, price)| *price == PRICE.to_string()));
+}
+
+#[test]
+fn accruing_creator_collects_royalties_from_buys_and_offers_in_one_withdrawal() {
+    let mut contract = setup();
+    call_as(ALICE, 0);
+    contract.set_accrue_earnings(true);
+    let token_id = resale_listing(&mut contract);
+    let royalty = contract.royalty_for(token_id, PRICE.to_string())[0].1.parse::<Balance>().unwrap();
+    call_as(CAROL, PRICE);
+    contract.buy(token_id, 1);
+    assert!(transfers().iter().all(|(to, _)| to != ALICE));
+    assert_eq!(contract.earnings_of(ALICE.to_string()), royalty.to_string());
+
+    call_as(BOB, PRICE + contract.edition_storage_fee);
+    contract.offer(token_id, 1, None, None);
+    call_as(CAROL, 0);
+    contract.accept_offer(token_id, 1, 0);
+    assert!(transfers().iter().all(|(to, _)| to != ALICE));
+    assert_eq!(contract.earnings_of(ALICE.to_string()), (2 * royalty).to_string());
+
+    call_as(ALICE, 0);
+    contract.withdraw_earnings();
+    assert_eq!(transfers(), vec![(ALICE.to_string(), 2 * royalty)]);
+    assert_eq!(contract.earnings_of(ALICE.to_string()), "0");
+}
+
+#[test]
+#[should_panic(expected = "NOTHING TO WITHDRAW")]
+fn withdrawing_without_accrued_earnings_fails() {
+    let mut contract = setup();
+    call_as(ALICE, 0);
+    contract.set_accrue_earnings(true);
+    contract.withdraw_earnings();
+}
+
+#[test]
+fn accepting_an_offer_on_a_listed_edition_removes_the_listing() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 1, PRICE.to_string());
+    call_as(BOB, PRICE);
+    contract.offer(token_id, 1, None, None);
+    call_as(ALICE, 0);
+    contract.accept_offer(token_id, 1, 0);
+    assert_eq!(contract.get_edition(token_id, 1).edition_owner, BOB);
+    assert!(contract.state_of(token_id, 1) == EditionState::AVAILABLE);
+    assert!(contract.all_listings(0, 10).is_empty());
+    assert!(contract.listing_ft(token_id, 1).is_none());
+    let idx = contract.tokens.get(&token_id).unwrap().edition_index + 1;
+    assert!(contract.marketplace.get(&idx).is_none());
+}
+
+#[test]
+fn claim_all_pays_out_active_offers_and_accrued_royalties() {
+    let mut contract = setup();
+    call_as(ALICE, 0);
+    contract.set_accrue_earnings(true);
+    let token_id = resale_listing(&mut contract);
+    let royalty = contract.royalty_for(token_id, PRICE.to_string())[0].1.parse::<Balance>().unwrap();
+    call_as(CAROL, PRICE);
+    contract.buy(token_id, 1);
+    // ALICE bids on her sold edition and on one she gave away, then withdraws the second bid herself
+    let given = mint(&mut contract, 1);
+    call_as(ALICE, 1);
+    contract.nft_transfer(BOB.to_string(), format!("{}::1", given), None, None);
+    for (bid_on, deposit) in &[(token_id, PRICE), (given, 2 * PRICE)] {
+        call_as(ALICE, *deposit);
+        contract.offer(*bid_on, 1, None, None);
+    }
+    call_as(ALICE, 0);
+    contract.cancel_offer(given, 1, 0);
+
+    let claimable = contract.claimable_of(ALICE.to_string());
+    assert_eq!((claimable.offers, claimable.earnings), (PRICE.to_string(), royalty.to_string()));
+    assert_eq!(claimable.total, (PRICE + royalty).to_string());
+    call_as(ALICE, 0);
+    assert_eq!(contract.claim_all(), 0);
+    assert_eq!(transfers().into_iter().map(|(_, amount)| amount).sum::<Balance>(), PRICE + royalty);
+    assert!(transfers().iter().all(|(to, _)| to == ALICE));
+    assert_eq!(contract.claimable_of(ALICE.to_string()).total, "0");
+}
+
+/// BOB's offer on ALICE's edition was accepted and CAROL's cancelled, leaving a list of closed bids only
+fn closed_offers(contract: &mut NonFungibleToken) -> TokenId {
+    let token_id = mint(contract, 1);
+    for bidder in &[BOB, CAROL] {
+        call_as(bidder, PRICE);
+        contract.offer(token_id, 1, None, None);
+    }
+    call_as(CAROL, 0);
+    contract.cancel_offer(token_id, 1, 1);
+    call_as(ALICE, 0);
+    contract.accept_offer(token_id, 1, 0);
+    token_id
+}
+
+#[test]
+fn pruning_a_list_of_closed_offers_removes_it() {
+    let mut contract = setup();
+    let token_id = closed_offers(&mut contract);
+    call_as(CAROL, 0);
+    // their storage fees went back with the bids, nothing is left to reward
+    assert_eq!(contract.prune_offers(token_id, 1), "0");
+    assert!(transfers().is_empty());
+    assert!(contract.offers.get(&contract.gen_token_x_edition(token_id, 1)).is_none());
+    assert!(contract.get_offers(token_id, 1).is_empty());
+}
+
+#[test]
+fn pruning_storage_fees_held_for_older_bids_pays_the_capped_reward() {
+    let mut contract = setup();
+    let token_id = closed_offers(&mut contract);
+    let storage_fee = contract.edition_storage_fee;
+    let mut offers = contract.offers.get(&contract.gen_token_x_edition(token_id, 1)).unwrap();
+    let mut older = offers.get(1).unwrap();
+    older.storage_fee = storage_fee;
+    offers.replace(1, &older);
+    let fees_before: Balance = contract.collected_fees().parse().unwrap();
+
+    call_as(CAROL, 0);
+    let reward: Balance = contract.prune_offers(token_id, 1).parse().unwrap();
+    assert_eq!(reward, crate::MAX_PRUNE_REWARD);
+    assert_eq!(transfers(), vec![(CAROL.to_string(), reward)]);
+    assert_eq!(contract.collected_fees().parse::<Balance>().unwrap(), fees_before + storage_fee - reward);
+}
+
+#[test]
+#[should_panic(expected = "EDITION STILL HAS ACTIVE OFFERS")]
+fn pruning_never_touches_an_active_bid() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    for bidder in &[BOB, CAROL] {
+        call_as(bidder, PRICE);
+        contract.offer(token_id, 1, None, None);
+    }
+    call_as(CAROL, 0);
+    contract.cancel_offer(token_id, 1, 1);
+    contract.prune_offers(token_id, 1);
+}
+
+#[test]
+#[should_panic(expected = "Invalid amount, expected a whole number of yoctoNEAR.")]
+fn set_price_rejects_a_non_numeric_price() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 1, "1.5 NEAR".to_string());
+}
+
+#[test]
+#[should_panic(expected = "Invalid amount, expected a whole number of yoctoNEAR.")]
+fn batch_set_price_rejects_a_price_past_u128() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    call_as(ALICE, 0);
+    contract.batch_set_price(token_id, vec![1, 2], format!("{}0", u128::MAX));
+}
+
+/// BOB relisted the edition he bought and prepaid its royalty, returns the token and the prepaid amount
+fn prepaid_resale(contract: &mut NonFungibleToken) -> (TokenId, Balance) {
+    let token_id = resale_listing(contract);
+    let due: Balance = contract.royalty_for(token_id, PRICE.to_string())[0].1.parse().unwrap();
+    call_as(BOB, due);
+    contract.prepay_royalty(token_id, 1);
+    (token_id, due)
+}
+
+#[test]
+fn plain_transfer_of_a_listed_edition_refunds_the_prepaid_royalty() {
+    let mut contract = setup();
+    let (token_id, due) = prepaid_resale(&mut contract);
+    call_as(BOB, 1);
+    contract.nft_transfer(CAROL.to_string(), format!("{}::1", token_id), None, None);
+    // a TRANSFER isn't a sale, nothing is pooled with proceeds
+    assert_eq!(transfers(), vec![(BOB.to_string(), due)]);
+    assert_eq!(contract.prepaid_royalty(token_id, 1), "0");
+}
+
+#[test]
+fn offer_accept_transfer_pools_the_prepaid_royalty_like_a_sale() {
+    let mut contract = setup();
+    let (token_id, due) = prepaid_resale(&mut contract);
+    call_as(CAROL, PRICE + contract.edition_storage_fee);
+    contract.offer(token_id, 1, None, None);
+    call_as(BOB, 0);
+    contract.accept_offer(token_id, 1, 0);
+    let proceeds = PRICE - contract._trade_fee_of(PRICE);
+    assert!(transfers().contains(&(BOB.to_string(), proceeds)));
+    assert!(transfers().contains(&(ALICE.to_string(), due)));
+    assert!(!transfers().contains(&(BOB.to_string(), due)));
+}
+
+#[test]
+#[should_panic(expected = "This edition is burned or locked.")]
+fn listing_a_locked_edition_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.start_auction(token_id, 1, PRICE.to_string(), DAY);
+    contract.set_price(token_id, 1, PRICE.to_string());
+}
+
+#[test]
+#[should_panic(expected = "This edition is burned or locked.")]
+fn listing_a_burned_edition_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 2);
+    call_as(ALICE, 0);
+    contract.burn_edition(token_id, 2);
+    contract.set_price(token_id, 2, PRICE.to_string());
+}
+
+#[test]
+#[should_panic(expected = "This edition is already listed, cancel the sale before listing it at a new price.")]
+fn listing_a_listed_edition_again_fails() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 1, PRICE.to_string());
+    contract.set_price(token_id, 1, (2 * PRICE).to_string());
+}
+
+/// ALICE's three edition token with listings limited to [PRICE, 10 * PRICE]
+fn price_range(contract: &mut NonFungibleToken) -> TokenId {
+    call_as(OWNER, 0);
+    contract.set_listing_price_range(PRICE.to_string(), (10 * PRICE).to_string());
+    assert_eq!(contract.listing_price_range(), (PRICE.to_string(), (10 * PRICE).to_string()));
+    mint(contract, 3)
+}
+
+#[test]
+fn listing_at_either_bound_of_the_price_range() {
+    let mut contract = setup();
+    let token_id = price_range(&mut contract);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 1, PRICE.to_string());
+    contract.batch_set_price(token_id, vec![2, 3], (10 * PRICE).to_string());
+    assert_eq!(contract.get_price(token_id, 1), PRICE);
+    assert_eq!(contract.get_price(token_id, 3), 10 * PRICE);
+}
+
+#[test]
+#[should_panic(expected = "PRICE OUT OF RANGE")]
+fn listing_below_the_min_price_fails() {
+    let mut contract = setup();
+    let token_id = price_range(&mut contract);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 1, (PRICE - 1).to_string());
+}
+
+#[test]
+#[should_panic(expected = "PRICE OUT OF RANGE")]
+fn listing_above_the_max_price_fails() {
+    let mut contract = setup();
+    let token_id = price_range(&mut contract);
+    call_as(ALICE, 0);
+    contract.batch_set_price(token_id, vec![1, 2], (10 * PRICE + 1).to_string());
+}
+
+#[test]
+#[should_panic(expected = "PRICE OUT OF RANGE")]
+fn listing_for_nothing_fails_with_the_default_range() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    call_as(ALICE, 0);
+    contract.set_price(token_id, 1, "0".to_string());
+}
+
+#[test]
+#[should_panic(expected = "INVALID LISTING PRICE RANGE")]
+fn inverted_listing_price_range_is_rejected() {
+    let mut contract = setup();
+    call_as(OWNER, 0);
+    contract.set_listing_price_range((2 * PRICE).to_string(), PRICE.to_string());
+}
+
+#[test]
+fn highest_offer_of_an_edition_without_offers_is_none() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    assert!(contract.highest_offer(token_id, 1).is_none());
+}
+
+#[test]
+fn highest_offer_skips_closed_bids() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    for bidder in &[BOB, CAROL] {
+        call_as(bidder, PRICE);
+        contract.offer(token_id, 1, None, None);
+        call_as(bidder, 0);
+        contract.cancel_offer(token_id, 1, contract.get_offers(token_id, 1).len() as u64 - 1);
+    }
+    assert!(contract.highest_offer(token_id, 1).is_none());
+}
+
+#[test]
+fn highest_offer_picks_the_largest_active_bid() {
+    let mut contract = setup();
+    let token_id = mint(&mut contract, 1);
+    for (bidder, deposit) in &[(BOB, PRICE), (CAROL, 3 * PRICE), (OWNER, 2 * PRICE)] {
+        call_as(bidder, *deposit);
+        contract.offer(token_id, 1, None, None);
+    }
+    assert_eq!(contract.highest_offer(token_id, 1).unwrap().bidder, CAROL);
+    call_as(CAROL, 0);
+    contract.cancel_offer(token_id, 1, 1);
+    assert_eq!(contract.highest_offer(token_id, 1).unwrap().bidder, OWNER);
+    // a view, the offers are left as they were
+    assert_eq!(contract.active_offers(token_id, 1, 0, 10).len(), 2);
+}
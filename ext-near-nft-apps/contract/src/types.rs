@@ -2,7 +2,4 @@ pub type TokenId = u64;
 pub type EditionNumber = u64;
 pub type TokenPrice = u128;
 pub type CollectionId = u64;
-pub type AccountIdHash = Vec<u8>;
-pub type Fee = f32;
-pub type Allow = bool;
 